@@ -1,7 +1,17 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
-use crate::{app::Mode, database::system_query};
+use crate::{
+    app::Mode,
+    components::{
+        column_picker::ColumnChoice,
+        prompt::{PromptPurpose, PromptRequest},
+    },
+    database::{connection::QueryResult, system_query, value::Value},
+    external_editor::{EditPurpose, EditRequest},
+};
 
 /// Actions are user-driven events, which differ from AppEvents
 #[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
@@ -14,11 +24,21 @@ pub enum Action {
     Quit,
     ClearScreen,
     Error(String),
+    Info(String),
     Help,
     ChangeMode(Mode),
     MakeSelection,
     OpenDbConnection(String),
     ViewStructure,
+    ShowDdl,
+    RefreshMaterializedView,
+    CancelBackend,
+    TerminateBackend,
+    ShowDiagram,
+    CycleSort,
+    ZoomIn,
+    ZoomOut,
+    ExportDiagram,
     ChangeSchema(String),
     ExecuteQuery(system_query::Query),
     NavDown,
@@ -27,9 +47,60 @@ pub enum Action {
     NavRight,
     PageLeft,
     PageRight,
+    PageUp,
+    PageDown,
+    GrowColumn,
+    ShrinkColumn,
+    /// Reset the width of the selected column to fit its contents
+    FitColumn,
+    /// Keep the columns up to the selected one in view while paging columns
+    FreezeColumns,
+    /// Switch between the results table and showing each row as a record of column and value
+    /// lines, like psql's `\x`
+    ToggleExpanded,
+    /// Open the popup to hide, show and reorder the columns of the results
+    PickColumns,
+    ShowColumnPicker(Vec<ColumnChoice>),
+    ColumnsPicked(Vec<ColumnChoice>),
+    MoveUp,
+    MoveDown,
+    /// Keep the current results to compare them with the next ones, or unpin the kept ones
+    PinResult,
+    ResultPinned(QueryResult),
     Yank,
+    ToggleWrap,
+    YankPath,
     Search,
+    SearchNext,
     Clear,
-    SelectCell(String),
+    SelectCell(Value),
     SelectRow(Vec<String>, Vec<Option<String>>), // columns, row
+    ViewJson(String),
+    /// Edit the selected cell in the external editor
+    EditCell,
+    /// Edit the query editor buffer in the external editor
+    EditBuffer,
+    OpenInEditor(EditRequest),
+    EditorClosed(EditPurpose, String),
+    InsertQuery(String),
+    RunSelection,
+    Explain,
+    ExplainAnalyze,
+    SaveSnippet,
+    Prompt(PromptRequest),
+    PromptSubmitted(PromptPurpose, Vec<String>),
+    OpenFile(PathBuf),
+    PromptOpenFile,
+    SaveFile,
+    NewBuffer,
+    CloseBuffer,
+    RenameBuffer,
+    NextBuffer,
+    PreviousBuffer,
+    DisplayResult(QueryResult),
+    FollowReference,
+    ShowReferences,
+    Back,
+    NextTab,
+    PreviousTab,
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::Rect;
@@ -10,12 +10,31 @@ use crate::{
     action::Action,
     app_event::{AppEvent, MessageType, QueryTag},
     components::{
-        Component, connection_menu::ConnectionMenu, detail_popup::DetailPopup, messages::Messages,
-        results_table::ResultsTable, schema_list::SchemaList, structure_table::StructureTable,
-        table_list::TableList, text_editor::TextEditor, title::Title,
+        Component,
+        activity_monitor::ActivityMonitor,
+        column_picker::ColumnPicker,
+        connection_menu::ConnectionMenu,
+        detail_popup::DetailPopup,
+        diagram::Diagram,
+        json_viewer::JsonViewer,
+        messages::Messages,
+        overview::Overview,
+        plan_tree::PlanTree,
+        prompt::{Prompt, PromptPurpose, PromptRequest},
+        references_popup::ReferencesPopup,
+        result_diff::{DiffSide, ResultDiff},
+        results_table::ResultsTable,
+        saved_queries::SavedQueries,
+        schema_list::SchemaList,
+        structure_table::StructureTable,
+        table_list::TableList,
+        table_stats::TableStats,
+        text_editor::TextEditor,
+        title::Title,
     },
     config::Config,
-    database::connection::DbConnection,
+    database::{connection::DbConnection, params::QueryParameters, system_query::Query},
+    external_editor::{self, EditRequest},
     render_plan::RenderPlan,
     tui::Tui,
 };
@@ -28,9 +47,13 @@ pub struct App {
     render_plan: RenderPlan,
     should_quit: bool,
     should_suspend: bool,
+    /// Text to edit in the external editor, which takes over the terminal until it exits
+    pending_edit: Option<EditRequest>,
     mode: Mode,
     zoom: bool,
     db_connection: Option<DbConnection>,
+    /// Last value entered for each query parameter name, offered again the next time it's used
+    bind_values: HashMap<String, String>,
     last_tick_key_events: Vec<KeyEvent>,
     action_tx: mpsc::UnboundedSender<Action>,
     action_rx: mpsc::UnboundedReceiver<Action>,
@@ -55,6 +78,28 @@ pub enum Mode {
     ExploreSchemas,
     /// Navigate to the table's structure
     ExploreStructure,
+    /// Navigate the list of saved queries
+    ExploreSavedQueries,
+    /// Fill in a popup prompt
+    Prompt,
+    /// Navigate the rows referencing a result row
+    ExploreReferences,
+    /// Navigate a JSON value of the results
+    ExploreJson,
+    /// Page through a value of the results
+    ExploreValue,
+    /// Hide, show and reorder the columns of the results
+    PickColumns,
+    /// Compare pinned results with the results run after them
+    CompareResults,
+    /// Navigate the ER diagram of a schema
+    ExploreDiagram,
+    /// Navigate the plan of a query
+    ExplorePlan,
+    /// Watch the sessions running on the server
+    MonitorActivity,
+    /// Health of the database, shown after connecting
+    Overview,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -68,11 +113,35 @@ pub enum ComponentId {
     SchemaList,
     StructureTable,
     DetailPopup,
+    SavedQueries,
+    Prompt,
+    ReferencesPopup,
+    JsonViewer,
+    ColumnPicker,
+    DiffBefore,
+    DiffAfter,
+    Diagram,
+    TableStats,
+    PlanTree,
+    ActivityMonitor,
+    Overview,
 }
 
+/// Popups are drawn over the standard layout, in this order.
+const POPUPS: [ComponentId; 5] = [
+    ComponentId::DetailPopup,
+    ComponentId::ReferencesPopup,
+    ComponentId::JsonViewer,
+    ComponentId::ColumnPicker,
+    ComponentId::Prompt,
+];
+
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> color_eyre::Result<Self> {
+    pub fn new(tick_rate: f64, frame_rate: f64, file: Option<PathBuf>) -> color_eyre::Result<Self> {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
+        if let Some(path) = file {
+            action_tx.send(Action::OpenFile(path))?;
+        }
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         let mut components: HashMap<ComponentId, Box<dyn Component>> = HashMap::new();
         components.insert(ComponentId::Title, Box::new(Title::default()));
@@ -87,6 +156,30 @@ impl App {
             Box::new(StructureTable::default()),
         );
         components.insert(ComponentId::DetailPopup, Box::new(DetailPopup::default()));
+        components.insert(ComponentId::SavedQueries, Box::new(SavedQueries::default()));
+        components.insert(ComponentId::Prompt, Box::new(Prompt::default()));
+        components.insert(
+            ComponentId::ReferencesPopup,
+            Box::new(ReferencesPopup::default()),
+        );
+        components.insert(ComponentId::JsonViewer, Box::new(JsonViewer::default()));
+        components.insert(ComponentId::ColumnPicker, Box::new(ColumnPicker::default()));
+        components.insert(ComponentId::Diagram, Box::new(Diagram::default()));
+        components.insert(ComponentId::TableStats, Box::new(TableStats::default()));
+        components.insert(ComponentId::PlanTree, Box::new(PlanTree::default()));
+        components.insert(
+            ComponentId::ActivityMonitor,
+            Box::new(ActivityMonitor::default()),
+        );
+        components.insert(ComponentId::Overview, Box::new(Overview::default()));
+        components.insert(
+            ComponentId::DiffBefore,
+            Box::new(ResultDiff::new(DiffSide::Before)),
+        );
+        components.insert(
+            ComponentId::DiffAfter,
+            Box::new(ResultDiff::new(DiffSide::After)),
+        );
         let render_plan = RenderPlan::default();
 
         Ok(Self {
@@ -96,10 +189,12 @@ impl App {
             render_plan,
             should_quit: false,
             should_suspend: false,
+            pending_edit: None,
             config: Config::new()?,
             mode: Mode::default(),
             zoom: false,
             db_connection: None,
+            bind_values: HashMap::new(),
             last_tick_key_events: Vec::new(),
             action_tx,
             action_rx,
@@ -136,6 +231,15 @@ impl App {
                 action_tx.send(Action::ClearScreen)?;
                 // tui.mouse(true);
                 tui.enter()?;
+            } else if let Some(request) = self.pending_edit.take() {
+                tui.exit()?;
+                let edited = external_editor::edit(&request);
+                tui.enter()?;
+                action_tx.send(Action::ClearScreen)?;
+                action_tx.send(match edited {
+                    Ok(contents) => Action::EditorClosed(request.purpose, contents),
+                    Err(e) => Action::Error(format!("Failed to edit in the external editor: {e}")),
+                })?;
             } else if self.should_quit {
                 tui.stop()?;
                 break;
@@ -216,6 +320,20 @@ impl App {
                             action_tx.send(Action::ChangeMode(Mode::ExploreStructure))?;
                             self.zoom = zoom;
                         }
+                        KeyCode::Char('5') if key.modifiers == KeyModifiers::ALT => {
+                            let zoom = self.mode == Mode::ExploreSavedQueries && !self.zoom;
+                            action_tx.send(Action::ChangeMode(Mode::ExploreSavedQueries))?;
+                            self.zoom = zoom;
+                        }
+                        // The activity monitor always takes the whole width, so it has no zoom.
+                        KeyCode::Char('6') if key.modifiers == KeyModifiers::ALT => {
+                            action_tx.send(Action::ChangeMode(Mode::MonitorActivity))?;
+                            self.zoom = false;
+                        }
+                        KeyCode::Char('7') if key.modifiers == KeyModifiers::ALT => {
+                            action_tx.send(Action::ChangeMode(Mode::Overview))?;
+                            self.zoom = false;
+                        }
                         _ => {}
                     }
                 }
@@ -236,10 +354,17 @@ impl App {
                 Action::Quit => self.should_quit = true,
                 Action::Suspend => self.should_suspend = true,
                 Action::Resume => self.should_suspend = false,
+                Action::OpenInEditor(ref request) => self.pending_edit = Some(request.clone()),
                 Action::ClearScreen => tui.terminal.clear()?,
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
                 Action::Render => self.render(tui)?,
                 Action::ChangeMode(new_mode) => self.mode = new_mode,
+                Action::Error(ref message) => self
+                    .event_tx
+                    .send(AppEvent::UserMessage(MessageType::Error, message.clone()))?,
+                Action::Info(ref message) => self
+                    .event_tx
+                    .send(AppEvent::UserMessage(MessageType::Info, message.clone()))?,
                 Action::OpenDbConnection(connection_name) => {
                     if let Some(db_config) = self.config.db_connections.0.get(&connection_name) {
                         let config = db_config.clone();
@@ -269,30 +394,43 @@ impl App {
                     }
                 }
                 Action::ExecuteQuery(query) => {
-                    // When a query is executed, report the result back via an app event.
-                    let tx = self.event_tx.clone();
-                    if let Some(connection) = self.db_connection.clone() {
-                        tokio::spawn(async move {
-                            let res = connection
-                                .get_query_result(query.query.as_str(), query.binds)
-                                .await;
-                            match res {
-                                Ok(query_result) => {
-                                    tx.send(AppEvent::QueryResult(query_result, query.tag))
-                                }
-                                Err(db_error) => tx.send(AppEvent::UserMessage(
-                                    MessageType::Error,
-                                    db_error.to_string(),
-                                )),
-                            }
-                        });
+                    let params = QueryParameters::parse(&query.query);
+                    let user_query = matches!(query.tag, QueryTag::User | QueryTag::Explain { .. });
+                    if user_query && query.binds.is_none() && !params.names.is_empty() {
+                        // Ask for parameter values first, the query is run when they're submitted.
+                        self.action_tx.send(Action::Prompt(PromptRequest {
+                            title: String::from("Bind parameters"),
+                            fields: params
+                                .names
+                                .iter()
+                                .map(|name| {
+                                    let last = self.bind_values.get(name).cloned();
+                                    (name.clone(), last.unwrap_or_default())
+                                })
+                                .collect(),
+                            purpose: PromptPurpose::BindParameters {
+                                tag: query.tag,
+                                query: params.query,
+                                names: params.names,
+                            },
+                        }))?;
                     } else {
-                        self.event_tx.send(AppEvent::UserMessage(
-                            MessageType::Error,
-                            String::from("No connection established."),
-                        ))?;
+                        self.execute_query(query)?;
                     }
                 }
+                Action::PromptSubmitted(
+                    PromptPurpose::BindParameters { tag, query, names },
+                    values,
+                ) => {
+                    for (name, value) in names.into_iter().zip(values.iter()) {
+                        self.bind_values.insert(name, value.clone());
+                    }
+                    self.action_tx.send(Action::ExecuteQuery(Query {
+                        tag,
+                        query,
+                        binds: Some(values),
+                    }))?;
+                }
                 _ => {}
             }
             for (_, component) in self.components.iter_mut() {
@@ -304,13 +442,52 @@ impl App {
         Ok(())
     }
 
+    fn execute_query(&self, query: Query) -> color_eyre::Result<()> {
+        // When a query is executed, report the result back via an app event.
+        let tx = self.event_tx.clone();
+        if let Some(connection) = self.db_connection.clone() {
+            tokio::spawn(async move {
+                let res = async {
+                    let sql = match (&query.tag, &query.binds) {
+                        (QueryTag::User | QueryTag::Explain { .. }, Some(_)) => {
+                            connection.cast_params(&query.query).await?
+                        }
+                        _ => query.query.clone(),
+                    };
+                    // Analyzing runs the statement, whose changes are not kept
+                    match query.tag {
+                        QueryTag::Explain { analyze: true, .. } => {
+                            connection
+                                .get_rolled_back_result(sql.as_str(), query.binds)
+                                .await
+                        }
+                        _ => connection.get_query_result(sql.as_str(), query.binds).await,
+                    }
+                }
+                .await;
+                match res {
+                    Ok(query_result) => tx.send(AppEvent::QueryResult(query_result, query.tag)),
+                    Err(db_error) => tx.send(AppEvent::UserMessage(
+                        MessageType::Error,
+                        db_error.to_string(),
+                    )),
+                }
+            });
+        } else {
+            self.event_tx.send(AppEvent::UserMessage(
+                MessageType::Error,
+                String::from("No connection established."),
+            ))?;
+        }
+        Ok(())
+    }
+
     fn handle_app_events(&mut self) -> color_eyre::Result<()> {
         while let Ok(app_event) = self.event_rx.try_recv() {
             match app_event.clone() {
                 AppEvent::DbConnectionEstablished(connection) => {
                     self.db_connection = Some(connection);
-                    self.action_tx
-                        .send(Action::ChangeMode(Mode::ExploreTables))?;
+                    self.action_tx.send(Action::ChangeMode(Mode::Overview))?;
                 }
                 AppEvent::QueryResult(result, QueryTag::User) => {
                     self.event_tx.send(AppEvent::UserMessage(
@@ -318,7 +495,8 @@ impl App {
                         format!("{} results", result.rows.len()),
                     ))?
                 }
-                AppEvent::QueryResult(result, QueryTag::InitialTable(_)) => {
+                AppEvent::QueryResult(result, QueryTag::InitialTable(_))
+                | AppEvent::QueryResult(result, QueryTag::FollowReference(_)) => {
                     self.event_tx.send(AppEvent::UserMessage(
                         MessageType::Info,
                         format!("{} results", result.rows.len()),
@@ -363,12 +541,14 @@ impl App {
             }
 
             // Draw popups so they render outside of the standard layout.
-            if let Some(popup) = self.components.get_mut(&ComponentId::DetailPopup)
-                && let Err(err) = popup.draw(frame, frame.area())
-            {
-                let _ = self
-                    .action_tx
-                    .send(Action::Error(format!("Failed to draw: {:?}", err)));
+            for popup_id in POPUPS {
+                if let Some(popup) = self.components.get_mut(&popup_id)
+                    && let Err(err) = popup.draw(frame, frame.area())
+                {
+                    let _ = self
+                        .action_tx
+                        .send(Action::Error(format!("Failed to draw: {:?}", err)));
+                }
             }
         })?;
         Ok(())
@@ -3,7 +3,7 @@ use strum::Display;
 
 use crate::database::{
     connection::{DbConnection, QueryResult},
-    system_query::Table,
+    system_query::{CellUpdate, Function, Reference, Table},
 };
 
 /// App events are events triggered in the system that are not direct user actions (e.g. they will
@@ -30,4 +30,37 @@ pub enum QueryTag {
     ListTables,
     InitialTable(Table),
     TableStructure(Table),
+    TableIndexes(Table),
+    TableConstraints(Table),
+    TableTriggers(Table),
+    TablePartitions(Table),
+    TableStats(Table),
+    TableDdl(Table),
+    FunctionDefinition(Function),
+    TypeDefinition(Table),
+    RefreshMaterializedView(Table),
+    /// Foreign keys of the tables with the given OIDs
+    ForeignKeys(Vec<u32>),
+    /// Foreign keys referencing the tables with the given OIDs
+    ReferencingForeignKeys(Vec<u32>),
+    /// Number of rows matching each reference
+    ReferenceCounts(Vec<Reference>),
+    FollowReference(Reference),
+    /// Tables of a schema with their columns and foreign keys
+    SchemaDiagram(String),
+    /// Plan of a user query, which is run when analyzed
+    Explain {
+        query: String,
+        analyze: bool,
+    },
+    /// Health of the current database, as a single row
+    Overview,
+    LargestTables,
+    /// Sessions of client connections
+    Activity,
+    CancelBackend(i32),
+    TerminateBackend(i32),
+    /// Columns of the table with the given OID, marking those of its primary key
+    TableKey(u32),
+    UpdateCell(Box<CellUpdate>),
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 
 use crate::config::{get_config_dir, get_data_dir};
@@ -12,6 +14,10 @@ pub struct Cli {
     /// Frame rate, i.e. number of frames per second
     #[arg(short, long, value_name = "FLOAT", default_value_t = 60.0)]
     pub frame_rate: f64,
+
+    /// SQL file to open in the editor
+    #[arg(value_name = "FILE")]
+    pub file: Option<PathBuf>,
 }
 
 const VERSION_MESSAGE: &str = concat!(
@@ -7,13 +7,24 @@ use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{action::Action, app_event::AppEvent, config::Config, tui::Event};
 
+pub mod activity_monitor;
+pub mod column_picker;
 pub mod connection_menu;
 pub mod detail_popup;
+pub mod diagram;
+pub mod json_viewer;
 pub mod messages;
+pub mod overview;
+pub mod plan_tree;
+pub mod prompt;
+pub mod references_popup;
+pub mod result_diff;
 pub mod results_table;
+pub mod saved_queries;
 pub mod schema_list;
 pub mod structure_table;
 pub mod table_list;
+pub mod table_stats;
 pub mod text_editor;
 pub mod title;
 
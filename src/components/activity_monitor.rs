@@ -0,0 +1,389 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::{
+        Component,
+        prompt::{PromptPurpose, PromptRequest},
+    },
+    config::Config,
+    database::{connection::QueryResult, system_query::SystemQuery, value::Value},
+};
+
+/// How often the sessions are fetched again while the monitor is open
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A server process of a client connection, from `pg_stat_activity`.
+#[derive(Debug, Clone)]
+struct Session {
+    pid: i32,
+    user: String,
+    database: String,
+    state: String,
+    /// Seconds spent in the current state
+    duration: Option<f64>,
+    wait_event: Option<String>,
+    /// Processes holding the locks this one waits for
+    blocked_by: Vec<i32>,
+    /// The lock waited for, e.g. `ShareLock on transactionid`
+    waiting_for: Option<String>,
+    query: String,
+}
+
+/// Sessions running on the server, with the tree of sessions blocking each other.
+#[derive(Default)]
+pub struct ActivityMonitor {
+    /// The last result, shown in the detail popup
+    result: QueryResult,
+    sessions: Vec<Session>,
+    state: TableState,
+    last_refresh: Option<Instant>,
+    /// Whether the detail popup of a session is open
+    detail_open: bool,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for ActivityMonitor {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::MonitorActivity) => {
+                self.focused = true;
+                return self.refresh();
+            }
+            Action::ChangeMode(_) => self.focused = false,
+            Action::PromptSubmitted(PromptPurpose::CancelBackend(pid), _) => {
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::CancelBackend(pid),
+                )?)));
+            }
+            Action::PromptSubmitted(PromptPurpose::TerminateBackend(pid), _) => {
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::TerminateBackend(pid),
+                )?)));
+            }
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        match action {
+            Action::Tick
+                if self
+                    .last_refresh
+                    .is_none_or(|last| last.elapsed() >= REFRESH_INTERVAL) =>
+            {
+                return self.refresh();
+            }
+            // The detail popup scrolls instead
+            Action::NavDown | Action::NavUp if self.detail_open => {}
+            // protect against excess navigation
+            Action::NavDown
+                if self
+                    .state
+                    .selected()
+                    .is_some_and(|idx| idx + 1 < self.sessions.len()) =>
+            {
+                self.state.select_next();
+            }
+            Action::NavUp => self.state.select_previous(),
+            Action::MakeSelection => {
+                if let Some(idx) = self.state.selected()
+                    && let Some(row) = self.result.rows.get(idx)
+                {
+                    self.detail_open = true;
+                    return Ok(Some(Action::SelectRow(
+                        self.result.columns.clone(),
+                        row.iter().map(Value::text).collect(),
+                    )));
+                }
+            }
+            Action::CancelBackend => {
+                if let Some(session) = self.selection() {
+                    return Ok(Some(Action::Prompt(PromptRequest {
+                        title: format!("Cancel the query of backend {}?", session.pid),
+                        purpose: PromptPurpose::CancelBackend(session.pid),
+                        fields: vec![],
+                    })));
+                }
+            }
+            Action::TerminateBackend => {
+                if let Some(session) = self.selection() {
+                    return Ok(Some(Action::Prompt(PromptRequest {
+                        title: format!(
+                            "Terminate backend {}, closing its connection?",
+                            session.pid
+                        ),
+                        purpose: PromptPurpose::TerminateBackend(session.pid),
+                        fields: vec![],
+                    })));
+                }
+            }
+            // The detail popup closes on the same key
+            Action::Clear if self.detail_open => self.detail_open = false,
+            Action::Clear => return Ok(Some(Action::ChangeMode(Mode::ExploreTables))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        match event {
+            AppEvent::QueryResult(result, QueryTag::Activity) => {
+                // Keep the same session selected as rows move around
+                let selected = self.selection().map(|s| s.pid);
+                self.sessions = result
+                    .rows
+                    .iter()
+                    .map(|row| Session::from_row(row))
+                    .collect();
+                self.result = result;
+                let idx = selected
+                    .and_then(|pid| self.sessions.iter().position(|s| s.pid == pid))
+                    .unwrap_or_default();
+                self.state
+                    .select((!self.sessions.is_empty()).then_some(idx));
+            }
+            AppEvent::QueryResult(result, QueryTag::CancelBackend(pid))
+            | AppEvent::QueryResult(result, QueryTag::TerminateBackend(pid)) => {
+                let signalled = result
+                    .rows
+                    .first()
+                    .and_then(|row| row.first().and_then(Value::text))
+                    == Some(String::from("true"));
+                return Ok(Some(if signalled {
+                    Action::Info(format!("Signalled backend {pid}"))
+                } else {
+                    Action::Error(format!("Backend {pid} could not be signalled"))
+                }));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let block = Block::bordered()
+            .title(format!(
+                "Activity ({} sessions) [alt+6]",
+                self.sessions.len()
+            ))
+            .title_bottom("enter: details  c: cancel query  t: terminate  esc: close")
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(if self.focused {
+                Color::Cyan
+            } else {
+                Color::Blue
+            }))
+            .border_type(if self.focused {
+                BorderType::Thick
+            } else {
+                BorderType::Plain
+            });
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let tree = self.lock_tree();
+        let [sessions_area, locks_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length((tree.len() as u16 + 1).clamp(2, inner.height / 3)),
+        ])
+        .areas(inner);
+
+        let header = Row::new([
+            "PID", "User", "Database", "State", "Duration", "Wait", "Query",
+        ])
+        .style(Style::new().bold())
+        .bottom_margin(1);
+        let rows = self.sessions.iter().map(|session| {
+            Row::new([
+                Cell::from(session.pid.to_string()),
+                Cell::from(session.user.as_str()),
+                Cell::from(session.database.as_str()),
+                Cell::from(session.state.as_str()).fg(session.color()),
+                Cell::from(session.duration.map(format_duration).unwrap_or_default()),
+                Cell::from(session.wait_event.clone().unwrap_or_default()),
+                Cell::from(one_line(&session.query)),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(20),
+                Constraint::Length(9),
+                Constraint::Length(20),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .style(Color::White)
+        .row_highlight_style(Style::new().on_dark_gray().bold())
+        .highlight_symbol("▷ ");
+        frame.render_stateful_widget(table, sessions_area, &mut self.state);
+
+        let lines: Vec<Line> = if tree.is_empty() {
+            vec![Line::from("No blocked sessions").dark_gray()]
+        } else {
+            tree.iter()
+                .map(|(depth, idx)| {
+                    let session = &self.sessions[*idx];
+                    let branch = if *depth == 0 {
+                        String::new()
+                    } else {
+                        format!("{}└ ", "  ".repeat(depth - 1))
+                    };
+                    let mut spans = vec![
+                        Span::from(branch),
+                        Span::from(session.pid.to_string()).bold(),
+                        Span::from(format!(" {} ", session.state)).fg(session.color()),
+                    ];
+                    if let Some(duration) = session.duration {
+                        spans.push(Span::from(format_duration(duration)));
+                    }
+                    if let Some(lock) = &session.waiting_for {
+                        spans.push(Span::from(format!(" waiting for {lock}")).red());
+                    }
+                    spans.push(Span::from(format!("  {}", one_line(&session.query))).dark_gray());
+                    Line::from(spans)
+                })
+                .collect()
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::new()
+                    .borders(Borders::TOP)
+                    .title("Blocking sessions")
+                    .fg(Color::Cyan),
+            ),
+            locks_area,
+        );
+        Ok(())
+    }
+}
+
+impl ActivityMonitor {
+    fn refresh(&mut self) -> color_eyre::Result<Option<Action>> {
+        self.last_refresh = Some(Instant::now());
+        Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+            QueryTag::Activity,
+        )?)))
+    }
+
+    fn selection(&self) -> Option<&Session> {
+        self.state.selected().and_then(|idx| self.sessions.get(idx))
+    }
+
+    /// Sessions blocking others, each followed by the sessions waiting for it, with their depth in
+    /// the tree. Sessions waiting for a process that isn't listed, like a background worker, are
+    /// shown at the top level.
+    fn lock_tree(&self) -> Vec<(usize, usize)> {
+        let pids: HashSet<i32> = self.sessions.iter().map(|s| s.pid).collect();
+        let blocks = |blocker: i32| {
+            self.sessions
+                .iter()
+                .enumerate()
+                .filter(move |(_, s)| s.blocked_by.contains(&blocker))
+                .map(|(idx, _)| idx)
+        };
+        let mut tree = vec![];
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(usize, usize)> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, s)| {
+                let blocked = s.blocked_by.iter().any(|pid| pids.contains(pid));
+                !blocked && (blocks(s.pid).next().is_some() || !s.blocked_by.is_empty())
+            })
+            .map(|(idx, _)| (0, idx))
+            .collect();
+        while let Some((depth, idx)) = stack.pop() {
+            if !visited.insert(idx) {
+                continue;
+            }
+            tree.push((depth, idx));
+            let waiting: Vec<usize> = blocks(self.sessions[idx].pid).collect();
+            stack.extend(waiting.into_iter().rev().map(|w| (depth + 1, w)));
+        }
+        tree
+    }
+}
+
+impl Session {
+    fn from_row(row: &[Value]) -> Self {
+        let value = |idx: usize| row.get(idx).and_then(Value::text);
+        Self {
+            pid: value(0).and_then(|v| v.parse().ok()).unwrap_or_default(),
+            user: value(1).unwrap_or_default(),
+            database: value(2).unwrap_or_default(),
+            state: value(3).unwrap_or_default(),
+            duration: value(4).and_then(|v| v.parse().ok()),
+            wait_event: value(5),
+            blocked_by: value(6)
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pid| pid.parse().ok())
+                .collect(),
+            waiting_for: value(7),
+            query: value(8).unwrap_or_default(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        if !self.blocked_by.is_empty() {
+            Color::Red
+        } else if self.state.starts_with("idle in transaction") {
+            Color::Yellow
+        } else if self.state == "active" {
+            Color::Green
+        } else {
+            Color::DarkGray
+        }
+    }
+}
+
+/// Formats seconds as e.g. `4.2s`, `12m05s` or `3h20m`.
+fn format_duration(seconds: f64) -> String {
+    let whole = seconds.max(0.0) as u64;
+    if whole < 60 {
+        format!("{seconds:.1}s")
+    } else if whole < 3600 {
+        format!("{}m{:02}s", whole / 60, whole % 60)
+    } else {
+        format!("{}h{:02}m", whole / 3600, whole % 3600 / 60)
+    }
+}
+
+/// Collapses the whitespace of a query to fit it on one line.
+fn one_line(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,149 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Modifier, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, List, ListState},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{action::Action, app::Mode, components::Component, config::Config};
+
+/// A column of the results, in the order chosen to show them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnChoice {
+    /// Index of the column in the results
+    pub index: usize,
+    pub name: String,
+    pub shown: bool,
+}
+
+/// Popup listing the columns of the results, to hide, show and reorder them.
+#[derive(Default)]
+pub struct ColumnPicker {
+    columns: Vec<ColumnChoice>,
+    list_state: ListState,
+    /// The mode to return to once the popup is closed
+    return_mode: Mode,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for ColumnPicker {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::PickColumns) => {
+                self.focused = true;
+                return Ok(None);
+            }
+            Action::ChangeMode(mode) => {
+                if !self.focused {
+                    self.return_mode = mode;
+                }
+                self.focused = false;
+                return Ok(None);
+            }
+            Action::ShowColumnPicker(columns) => {
+                self.columns = columns;
+                self.list_state = ListState::default().with_selected(Some(0));
+                return Ok(Some(Action::ChangeMode(Mode::PickColumns)));
+            }
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        let selected = self.list_state.selected().unwrap_or_default();
+        match action {
+            // protect against excess navigation
+            Action::NavDown if selected + 1 < self.columns.len() => self.list_state.select_next(),
+            Action::NavUp => self.list_state.select_previous(),
+            Action::MakeSelection => {
+                let shown = self.columns.iter().filter(|c| c.shown).count();
+                let Some(column) = self.columns.get_mut(selected) else {
+                    return Ok(None);
+                };
+                if column.shown && shown == 1 {
+                    return Ok(Some(Action::Info(String::from(
+                        "At least one column has to be shown",
+                    ))));
+                }
+                column.shown = !column.shown;
+                return Ok(Some(Action::ColumnsPicked(self.columns.clone())));
+            }
+            Action::MoveUp if selected > 0 && selected < self.columns.len() => {
+                self.columns.swap(selected, selected - 1);
+                self.list_state.select_previous();
+                return Ok(Some(Action::ColumnsPicked(self.columns.clone())));
+            }
+            Action::MoveDown if selected + 1 < self.columns.len() => {
+                self.columns.swap(selected, selected + 1);
+                self.list_state.select_next();
+                return Ok(Some(Action::ColumnsPicked(self.columns.clone())));
+            }
+            Action::Clear => return Ok(Some(Action::ChangeMode(self.return_mode))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+    ) -> color_eyre::Result<()> {
+        if !self.focused {
+            return Ok(());
+        }
+
+        let hint = "enter: show/hide  K/J: move  esc: close";
+        let width = self
+            .columns
+            .iter()
+            .map(|c| c.name.width() + 8)
+            .chain(std::iter::once(hint.width() + 2))
+            .max()
+            .unwrap_or_default() as u16;
+        let vertical = Layout::vertical([Constraint::Length(self.columns.len() as u16 + 2)])
+            .flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+
+        let block = Block::bordered()
+            .title("Columns")
+            .title_bottom(hint)
+            .style(Color::Cyan)
+            .border_type(BorderType::Thick);
+        let list = List::new(self.columns.iter().map(|column| {
+            if column.shown {
+                Line::from(vec![
+                    Span::from("[x] "),
+                    Span::from(column.name.as_str()).white(),
+                ])
+            } else {
+                Line::from(format!("[ ] {}", column.name)).dark_gray()
+            }
+        }))
+        .highlight_style(Modifier::REVERSED)
+        .highlight_symbol("▹ ")
+        .block(block);
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.list_state.clone());
+        Ok(())
+    }
+}
@@ -1,17 +1,48 @@
 use ratatui::{
     layout::{Constraint, Flex, Layout},
-    style::{Color, Stylize},
-    text::Text,
-    widgets::{Block, Clear, List, ListItem, ListState, Paragraph},
+    style::{Color, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph},
 };
 use tokio::sync::mpsc::UnboundedSender;
 use unicode_width::UnicodeWidthStr;
 
-use crate::{action::Action, components::Component, config::Config};
+use crate::{
+    action::Action, app::Mode, components::Component, config::Config, database::value::Value,
+};
+
+mod pager;
+
+use pager::Encoding;
+
+/// Share of the screen the value pager takes at most, in percent
+const MAX_PAGER_SIZE: u16 = 80;
+const MIN_PAGER_WIDTH: u16 = 50;
 
 #[derive(Default)]
 pub struct DetailPopup {
-    content: Option<String>,
+    /// The value of the selected cell, shown in a pager
+    value: Option<Value>,
+    /// How a binary value is shown
+    encoding: Encoding,
+    /// Lines of the value in the pager
+    lines: Vec<String>,
+    /// First row shown, counting the rows of wrapped lines
+    scroll: usize,
+    /// Characters scrolled to the right, when lines are not wrapped
+    column: usize,
+    no_wrap: bool,
+    /// Text searched for, with the line and byte offset of each occurrence
+    search: Option<String>,
+    matches: Vec<(usize, usize)>,
+    current_match: Option<usize>,
+    searching: bool,
+    /// Size of the pager text when last drawn
+    width: usize,
+    height: usize,
+    /// The mode to return to once the pager is closed
+    return_mode: Mode,
+    focused: bool,
     row_content: Option<(Vec<String>, Vec<Option<String>>)>,
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
@@ -34,23 +65,41 @@ impl Component for DetailPopup {
         action: crate::action::Action,
     ) -> color_eyre::Result<Option<crate::action::Action>> {
         match action {
+            Action::ChangeMode(Mode::ExploreValue) => self.focused = true,
+            Action::ChangeMode(mode) => {
+                if self.focused {
+                    self.value = None;
+                } else {
+                    self.return_mode = mode;
+                }
+                self.focused = false;
+            }
             Action::SelectRow(columns, row) => {
-                self.content = None;
+                self.value = None;
                 self.row_content = Some((columns, row));
             }
-            Action::SelectCell(content) => {
+            Action::SelectCell(value) => {
                 self.row_content = None;
-                self.content = Some(content);
+                // Binary values that aren't text are shown as a hex dump
+                self.encoding = match &value {
+                    Value::Bytes(bytes) if str::from_utf8(bytes).is_err() => Encoding::Hex,
+                    _ => Encoding::Text,
+                };
+                self.value = Some(value);
+                self.search = None;
+                self.searching = false;
+                self.set_lines();
+                return Ok(Some(Action::ChangeMode(Mode::ExploreValue)));
             }
+            _ if self.focused => return self.update_pager(action),
             Action::Clear => {
                 self.row_content = None;
-                self.content = None;
                 self.list_state = ListState::default().with_selected(Some(0));
             }
-            Action::NavDown if self.is_focused() => {
+            Action::NavDown if self.row_content.is_some() => {
                 self.list_state.select_next();
             }
-            Action::NavUp if self.is_focused() => {
+            Action::NavUp if self.row_content.is_some() => {
                 self.list_state.select_previous();
             }
             _ => {}
@@ -58,23 +107,36 @@ impl Component for DetailPopup {
         Ok(None)
     }
 
+    fn handle_key_event(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+    ) -> color_eyre::Result<Option<Action>> {
+        use crossterm::event::KeyCode;
+
+        if self.focused
+            && self.searching
+            && let Some(search) = &mut self.search
+        {
+            match key.code {
+                KeyCode::Char(c) => search.push(c),
+                KeyCode::Backspace => {
+                    search.pop();
+                }
+                _ => return Ok(None),
+            }
+            self.matches = pager::find(&self.lines, search);
+            self.current_match = None;
+        }
+        Ok(None)
+    }
+
     fn draw(
         &mut self,
         frame: &mut ratatui::Frame,
         area: ratatui::prelude::Rect,
     ) -> color_eyre::Result<()> {
-        if let Some(content) = &self.content {
-            let percent_y = 20;
-            let width = content.width() as u16 + 4;
-            let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
-            let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
-            let [area] = vertical.areas(area);
-            let [area] = horizontal.areas(area);
-
-            let block = Block::bordered().title("Value").style(Color::Cyan);
-            let text = Paragraph::new(content.as_str()).block(block);
-            frame.render_widget(Clear, area);
-            frame.render_widget(text, area);
+        if self.focused && self.value.is_some() {
+            self.draw_pager(frame, area);
         } else if let Some((columns, row)) = &self.row_content {
             let vertical =
                 Layout::vertical([Constraint::Length(columns.len() as u16 + 2)]).flex(Flex::Center);
@@ -108,7 +170,243 @@ impl Component for DetailPopup {
 }
 
 impl DetailPopup {
-    fn is_focused(&self) -> bool {
-        self.content.is_some() || self.row_content.is_some()
+    fn update_pager(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        // Key inputs for typing the search are in `handle_key_event`
+        if self.searching {
+            match action {
+                Action::MakeSelection => {
+                    self.searching = false;
+                    self.next_match();
+                }
+                Action::Clear => {
+                    self.searching = false;
+                    self.search = None;
+                    self.matches.clear();
+                }
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        let last_row = self.rows().len().saturating_sub(self.height);
+        match action {
+            Action::NavDown => self.scroll = (self.scroll + 1).min(last_row),
+            Action::NavUp => self.scroll = self.scroll.saturating_sub(1),
+            Action::PageDown => self.scroll = (self.scroll + self.height).min(last_row),
+            Action::PageUp => self.scroll = self.scroll.saturating_sub(self.height),
+            Action::NavRight if self.no_wrap => {
+                let longest = self.lines.iter().map(|l| l.chars().count()).max();
+                if self.column + self.width < longest.unwrap_or_default() {
+                    self.column += 1;
+                }
+            }
+            Action::NavLeft if self.no_wrap => self.column = self.column.saturating_sub(1),
+            Action::ToggleWrap => {
+                // Keep the first line shown at the top
+                let line = self.rows().get(self.scroll).map(|row| row.0);
+                self.no_wrap = !self.no_wrap;
+                self.column = 0;
+                self.scroll = self
+                    .rows()
+                    .iter()
+                    .position(|row| Some(row.0) == line)
+                    .unwrap_or_default();
+            }
+            Action::NextTab if matches!(self.value, Some(Value::Bytes(_))) => {
+                self.encoding = self.encoding.next();
+                self.set_lines();
+            }
+            Action::Search => {
+                self.searching = true;
+                self.search = Some(String::new());
+                self.matches.clear();
+                self.current_match = None;
+            }
+            Action::SearchNext => self.next_match(),
+            Action::Clear => return Ok(Some(Action::ChangeMode(self.return_mode))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Splits the value into the lines shown, and goes back to the top.
+    fn set_lines(&mut self) {
+        self.lines = match &self.value {
+            Some(Value::Bytes(bytes)) => self.encoding.lines(bytes),
+            Some(value) => value
+                .text()
+                .unwrap_or(String::from("NULL"))
+                .lines()
+                .map(String::from)
+                .collect(),
+            None => vec![],
+        };
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.scroll = 0;
+        self.column = 0;
+        self.matches = self
+            .search
+            .as_deref()
+            .map(|search| pager::find(&self.lines, search))
+            .unwrap_or_default();
+        self.current_match = None;
+    }
+
+    /// Line index and byte range of each row shown, wrapping lines to the pager width
+    fn rows(&self) -> Vec<(usize, usize, usize)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, line)| {
+                let rows = if self.no_wrap {
+                    vec![(0, line.len())]
+                } else {
+                    pager::wrap(line, self.width)
+                };
+                rows.into_iter().map(move |(start, end)| (idx, start, end))
+            })
+            .collect()
+    }
+
+    /// Scrolls so that the given byte of a line is in view.
+    fn scroll_to(&mut self, line: usize, offset: usize) {
+        let rows = self.rows();
+        let row = rows
+            .iter()
+            .rposition(|(idx, start, _)| *idx == line && *start <= offset)
+            .unwrap_or_default();
+        if row < self.scroll || row >= self.scroll + self.height.max(1) {
+            self.scroll = row.min(rows.len().saturating_sub(self.height));
+        }
+        if self.no_wrap {
+            let column = self.lines[line][..offset].chars().count();
+            if column < self.column || column >= self.column + self.width.max(1) {
+                self.column = column;
+            }
+        }
+    }
+
+    fn next_match(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let next = self
+            .current_match
+            .map_or(0, |current| (current + 1) % self.matches.len());
+        self.current_match = Some(next);
+        let (line, offset) = self.matches[next];
+        self.scroll_to(line, offset);
+    }
+
+    fn draw_pager(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) {
+        let gutter = self.lines.len().to_string().len();
+        // Small values get a popup fitting them
+        let content_width = self
+            .lines
+            .iter()
+            .map(|l| l.width())
+            .max()
+            .unwrap_or_default();
+        let max_width = area.width * MAX_PAGER_SIZE / 100;
+        let max_height = area.height * MAX_PAGER_SIZE / 100;
+        // The minimum width gives way to narrow terminals
+        let width = ((content_width + gutter + 3) as u16)
+            .clamp(MIN_PAGER_WIDTH, max_width.max(MIN_PAGER_WIDTH))
+            .min(area.width);
+        let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
+        let [area] = horizontal.areas(area);
+        // Rows depend on the width lines are wrapped to
+        self.width = (area.width as usize).saturating_sub(gutter + 3);
+        let height = (self.rows().len() as u16 + 2).clamp(3, max_height.max(3));
+        let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+
+        let title = match &self.value {
+            Some(Value::Bytes(_)) => format!("Value ({})", self.encoding.name()),
+            _ => String::from("Value"),
+        };
+        let position = match self.rows().get(self.scroll) {
+            Some((line, _, _)) => format!("{}/{}", line + 1, self.lines.len()),
+            None => String::new(),
+        };
+        let title_bottom = match &self.search {
+            Some(search) => format!("/{search}  {} matches", self.matches.len()),
+            None if matches!(self.value, Some(Value::Bytes(_))) => {
+                String::from("w: wrap  /: search  n: next match  tab: UTF-8/hex/base64  esc: close")
+            }
+            None => String::from("w: wrap  /: search  n: next match  esc: close"),
+        };
+        let block = Block::bordered()
+            .title(title)
+            .title(Line::from(position).right_aligned())
+            .title_bottom(title_bottom)
+            .style(Color::Cyan)
+            .border_type(BorderType::Thick);
+        let inner = block.inner(area);
+        self.height = inner.height as usize;
+
+        let rows = self.rows();
+        self.scroll = self.scroll.min(rows.len().saturating_sub(self.height));
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.height)
+            .map(|(row_idx, (line, start, end))| {
+                // Only the first row of a line is numbered
+                let number = if row_idx == 0 || rows[row_idx - 1].0 != *line {
+                    format!("{:>gutter$} ", line + 1)
+                } else {
+                    " ".repeat(gutter + 1)
+                };
+                let mut spans = vec![Span::from(number).dark_gray()];
+                spans.extend(self.row_spans(*line, *start, *end));
+                Line::from(spans)
+            })
+            .collect();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).white().block(block), area);
+    }
+
+    /// The text of a row, scrolled to the right when lines are not wrapped, with occurrences of
+    /// the search highlighted.
+    fn row_spans(&self, line: usize, start: usize, end: usize) -> Vec<Span<'_>> {
+        let text = &self.lines[line];
+        let start = if self.no_wrap {
+            text.char_indices()
+                .nth(self.column)
+                .map_or(end, |(idx, _)| idx)
+        } else {
+            start
+        };
+        let search_len = self.search.as_ref().map_or(0, String::len);
+
+        let mut spans = vec![];
+        let mut position = start;
+        for (match_idx, (_, offset)) in self
+            .matches
+            .iter()
+            .enumerate()
+            .filter(|(_, (match_line, _))| *match_line == line)
+        {
+            let (match_start, match_end) =
+                ((*offset).max(position), (offset + search_len).min(end));
+            if match_start >= match_end {
+                continue;
+            }
+            spans.push(Span::from(&text[position..match_start]));
+            let style = if self.current_match == Some(match_idx) {
+                Style::new().black().on_yellow()
+            } else {
+                Style::new().yellow().underlined()
+            };
+            spans.push(Span::styled(&text[match_start..match_end], style));
+            position = match_end;
+        }
+        spans.push(Span::from(&text[position..end]));
+        spans
     }
 }
@@ -0,0 +1,194 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Bytes shown on each line of a hex dump
+const HEX_DUMP_WIDTH: usize = 16;
+/// Characters on each line of base64, as in MIME
+const BASE64_WIDTH: usize = 76;
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How a binary value is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Text,
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    pub fn next(self) -> Self {
+        match self {
+            Encoding::Text => Encoding::Hex,
+            Encoding::Hex => Encoding::Base64,
+            Encoding::Base64 => Encoding::Text,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Text => "UTF-8",
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+        }
+    }
+
+    /// The lines showing the bytes in this encoding. Invalid UTF-8 is replaced.
+    pub fn lines(self, bytes: &[u8]) -> Vec<String> {
+        match self {
+            Encoding::Text => String::from_utf8_lossy(bytes)
+                .lines()
+                .map(String::from)
+                .collect(),
+            Encoding::Hex => hex_dump(bytes),
+            Encoding::Base64 => base64(bytes)
+                .as_bytes()
+                .chunks(BASE64_WIDTH)
+                .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Offset, bytes and printable characters of each line, like `hexdump -C`.
+pub fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(HEX_DUMP_WIDTH)
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+            let (left, right) = hex.split_at(hex.len().min(HEX_DUMP_WIDTH / 2));
+            let printable: String = chunk
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:08x}  {:<23}  {:<23}  |{printable}|",
+                idx * HEX_DUMP_WIDTH,
+                left.join(" "),
+                right.join(" ")
+            )
+        })
+        .collect()
+}
+
+/// Standard base64, with padding.
+pub fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (idx, b)| {
+            group | (*b as u32) << (16 - 8 * idx)
+        });
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let sextet = (group >> (18 - 6 * idx)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Byte ranges of the rows a line is wrapped into, breaking after spaces when possible. Every
+/// line has at least one row.
+pub fn wrap(line: &str, width: usize) -> Vec<(usize, usize)> {
+    if width == 0 {
+        return vec![(0, line.len())];
+    }
+    let mut rows = vec![];
+    let mut start = 0;
+    let mut row_width = 0;
+    // Where the row can break, after the last space
+    let mut last_break = None;
+    for (idx, c) in line.char_indices() {
+        let char_width = c.width().unwrap_or_default();
+        if row_width + char_width > width && idx > start {
+            let end = last_break.filter(|b| *b > start).unwrap_or(idx);
+            rows.push((start, end));
+            start = end;
+            row_width = line[start..idx].chars().filter_map(|c| c.width()).sum();
+            last_break = None;
+        }
+        row_width += char_width;
+        if c == ' ' {
+            last_break = Some(idx + 1);
+        }
+    }
+    rows.push((start, line.len()));
+    rows
+}
+
+/// Line and byte offset of each occurrence of the search, ignoring ASCII case.
+pub fn find(lines: &[String], search: &str) -> Vec<(usize, usize)> {
+    if search.is_empty() {
+        return vec![];
+    }
+    let search = search.to_ascii_lowercase();
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line_idx, line)| {
+            let line = line.to_ascii_lowercase();
+            line.match_indices(&search)
+                .map(|(offset, _)| (line_idx, offset))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump() {
+        let dump = hex_dump(b"Hello, world!\n\x00\xff\x01");
+        assert_eq!(
+            dump,
+            vec![
+                "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 ff  |Hello, world!...|",
+                "00000010  01                                                |.|",
+            ]
+        );
+        assert!(hex_dump(b"").is_empty());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe]), "//4=");
+        assert_eq!(Encoding::Base64.lines(&[0; 60]).len(), 2);
+    }
+
+    #[test]
+    fn test_wrap() {
+        let line = "the quick brown fox";
+        let rows: Vec<&str> = wrap(line, 10).iter().map(|(s, e)| &line[*s..*e]).collect();
+        assert_eq!(rows, vec!["the quick ", "brown fox"]);
+        let line = "abcdefghij";
+        let rows: Vec<&str> = wrap(line, 4).iter().map(|(s, e)| &line[*s..*e]).collect();
+        assert_eq!(rows, vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![(0, 0)]);
+        // Wide characters take two columns
+        assert_eq!(wrap("日本語", 4), vec![(0, 6), (6, 9)]);
+    }
+
+    #[test]
+    fn test_find() {
+        let lines = vec![String::from("Select 1"), String::from("select SELECT")];
+        assert_eq!(find(&lines, "SELECT"), vec![(0, 0), (1, 0), (1, 7)]);
+        assert!(find(&lines, "").is_empty());
+    }
+}
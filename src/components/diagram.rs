@@ -0,0 +1,497 @@
+use std::{fs, path::PathBuf};
+
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    widgets::{
+        Block, BorderType, Paragraph,
+        canvas::{Canvas, Line as CanvasLine},
+    },
+};
+use tokio::sync::mpsc::UnboundedSender;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::{
+        Component,
+        prompt::{PromptPurpose, PromptRequest},
+        text_editor::expand_home,
+    },
+    config::Config,
+    database::system_query::{SystemQuery, Table},
+};
+
+mod graph;
+
+use graph::{Detail, Graph, GraphLayout};
+
+/// Cell where a line meets the side of a box, with the border character marking it
+type Anchor = (u16, u16, char);
+
+/// Entity-relationship diagram of the tables of a schema, connected by their foreign keys.
+#[derive(Default)]
+pub struct Diagram {
+    graph: Graph,
+    layout: GraphLayout,
+    detail: Detail,
+    /// Index of the selected table
+    selected: Option<usize>,
+    /// Cell of the diagram shown in the top left corner
+    offset: (u16, u16),
+    /// Size of the area the diagram was last drawn in
+    viewport: (u16, u16),
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for Diagram {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::ExploreDiagram) => self.focused = true,
+            Action::ChangeMode(_) => self.focused = false,
+            Action::PromptSubmitted(PromptPurpose::ExportDiagram, ref values) => {
+                if let Some(path) = values.first() {
+                    return Ok(Some(self.export(PathBuf::from(path.trim()))));
+                }
+            }
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        let (width, height) = self.viewport;
+        match action {
+            Action::NavUp | Action::NavDown => {
+                if let Some((layer, row)) = self.selected.and_then(|t| self.layout.position(t))
+                    && let Some(tables) = self.layout.layers.get(layer)
+                {
+                    let row = match action {
+                        Action::NavUp => row.saturating_sub(1),
+                        _ => (row + 1).min(tables.len() - 1),
+                    };
+                    self.select(tables[row]);
+                }
+            }
+            Action::NavLeft | Action::NavRight => {
+                if let Some(current) = self.selected
+                    && let Some((layer, _)) = self.layout.position(current)
+                {
+                    let layer = match action {
+                        Action::NavLeft => layer.checked_sub(1),
+                        _ => Some(layer + 1),
+                    };
+                    // The table of the next layer closest to the same height
+                    let center = |table: usize| {
+                        let rect = self.layout.boxes[table];
+                        rect.y as i32 + rect.height as i32 / 2
+                    };
+                    if let Some(table) = layer
+                        .and_then(|layer| self.layout.layers.get(layer))
+                        .and_then(|tables| {
+                            tables
+                                .iter()
+                                .min_by_key(|table| (center(**table) - center(current)).abs())
+                        })
+                    {
+                        self.select(*table);
+                    }
+                }
+            }
+            Action::PageLeft => self.offset.0 = self.offset.0.saturating_sub(width / 2),
+            Action::PageRight => {
+                self.offset.0 =
+                    (self.offset.0 + width / 2).min(self.extent().0.saturating_sub(width))
+            }
+            Action::PageUp => self.offset.1 = self.offset.1.saturating_sub(height / 2),
+            Action::PageDown => {
+                self.offset.1 =
+                    (self.offset.1 + height / 2).min(self.extent().1.saturating_sub(height))
+            }
+            Action::ZoomIn => self.set_detail(self.detail.zoom_in()),
+            Action::ZoomOut => self.set_detail(self.detail.zoom_out()),
+            Action::MakeSelection => {
+                if let Some(table) = self.selected.and_then(|t| self.graph.tables.get(t)) {
+                    return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                        QueryTag::TableStructure(Table {
+                            schema: self.graph.schema.clone(),
+                            name: table.name.clone(),
+                        }),
+                    )?)));
+                }
+            }
+            Action::ExportDiagram => {
+                return Ok(Some(Action::Prompt(PromptRequest {
+                    purpose: PromptPurpose::ExportDiagram,
+                    title: String::from("Export diagram (.dot or .gv for Graphviz, else Mermaid)"),
+                    fields: vec![(String::from("Path"), format!("{}.mmd", self.graph.schema))],
+                })));
+            }
+            Action::Clear => return Ok(Some(Action::ChangeMode(Mode::ExploreTables))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        if let AppEvent::QueryResult(result, QueryTag::SchemaDiagram(schema)) = event {
+            // Keep the selected table when showing the same schema again
+            let previous = self
+                .selected
+                .and_then(|t| self.graph.tables.get(t))
+                .filter(|_| self.graph.schema == schema)
+                .map(|t| t.name.clone());
+            self.graph = Graph::from_rows(schema, &result.rows);
+            self.layout = self.graph.layout(self.detail);
+            self.offset = (0, 0);
+            self.selected = previous
+                .and_then(|name| self.graph.index_of(&name))
+                .or_else(|| self.layout.layers.first().and_then(|l| l.first().copied()));
+            if let Some(table) = self.selected {
+                self.select(table);
+            }
+            return Ok(Some(Action::ChangeMode(Mode::ExploreDiagram)));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let block = Block::bordered()
+            .title(format!("ER diagram: {}", self.graph.schema))
+            .title_bottom("enter: structure  +/-: zoom  shift+hjkl: pan  x: export  esc: close")
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(if self.focused {
+                Color::Cyan
+            } else {
+                Color::Blue
+            }))
+            .border_type(if self.focused {
+                BorderType::Thick
+            } else {
+                BorderType::Plain
+            });
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        self.viewport = (inner.width, inner.height);
+
+        if self.graph.tables.is_empty() {
+            frame.render_widget(Paragraph::new("No tables in this schema").centered(), inner);
+            return Ok(());
+        }
+
+        // Lines are drawn first, the boxes are drawn over them.
+        let (x, y) = (self.offset.0 as f64, self.offset.1 as f64);
+        let edges = self.edge_points();
+        let canvas = Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([x, x + inner.width as f64])
+            .y_bounds([-(y + inner.height as f64), -y])
+            .paint(|ctx| {
+                for ((x1, y1, _), (x2, y2, _), highlighted) in &edges {
+                    ctx.draw(&CanvasLine {
+                        x1: *x1 as f64 + 0.5,
+                        y1: -(*y1 as f64 + 0.5),
+                        x2: *x2 as f64 + 0.5,
+                        y2: -(*y2 as f64 + 0.5),
+                        color: if *highlighted {
+                            Color::Yellow
+                        } else {
+                            Color::DarkGray
+                        },
+                    });
+                }
+            });
+        frame.render_widget(canvas, inner);
+
+        let buf = frame.buffer_mut();
+        for (table, rect) in self.layout.boxes.iter().enumerate() {
+            let selected = self.selected == Some(table);
+            let border = if selected {
+                Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::new().fg(Color::Cyan)
+            };
+            for (row, line) in self.box_lines(table, *rect).iter().enumerate() {
+                for (col, symbol) in cells(line) {
+                    // Only the borders and the name are styled
+                    let is_text = row > 0
+                        && row + 1 < rect.height as usize
+                        && col > 0
+                        && col + 1 < rect.width as usize;
+                    let style = if is_text {
+                        Style::new().fg(Color::White)
+                    } else {
+                        border
+                    };
+                    self.put(
+                        buf,
+                        inner,
+                        rect.x + col as u16,
+                        rect.y + row as u16,
+                        &symbol,
+                        style,
+                    );
+                }
+            }
+        }
+        // Mark where lines meet the boxes
+        for (start, end, highlighted) in edges {
+            let style = Style::new().fg(if highlighted {
+                Color::Yellow
+            } else {
+                Color::Cyan
+            });
+            for (x, y, c) in [start, end] {
+                self.put(buf, inner, x, y, c.encode_utf8(&mut [0; 4]), style);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Diagram {
+    fn select(&mut self, table: usize) {
+        self.selected = Some(table);
+        let Some(rect) = self.layout.boxes.get(table) else {
+            return;
+        };
+        // Pan so that the whole box is visible, or at least its top left corner.
+        let (width, height) = self.viewport;
+        if width == 0 || height == 0 {
+            return;
+        }
+        if rect.right() > self.offset.0 + width {
+            self.offset.0 = rect.right().saturating_sub(width);
+        }
+        if rect.x < self.offset.0 {
+            self.offset.0 = rect.x;
+        }
+        if rect.bottom() > self.offset.1 + height {
+            self.offset.1 = rect.bottom().saturating_sub(height);
+        }
+        if rect.y < self.offset.1 {
+            self.offset.1 = rect.y;
+        }
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+        self.layout = self.graph.layout(detail);
+        if let Some(table) = self.selected {
+            self.select(table);
+        }
+    }
+
+    /// Width and height of the whole diagram
+    fn extent(&self) -> (u16, u16) {
+        self.layout
+            .boxes
+            .iter()
+            .fold((0, 0), |(w, h), r| (w.max(r.right()), h.max(r.bottom())))
+    }
+
+    /// Text of each line of a table's box, with its name in the top border.
+    fn box_lines(&self, table: usize, rect: Rect) -> Vec<String> {
+        let inner = rect.width.saturating_sub(2) as usize;
+        let name = self.graph.tables[table].name.as_str();
+        // Padded by display width, as `format!` pads by characters
+        let mut lines = vec![format!(
+            "┌ {name} {}┐",
+            "─".repeat(inner.saturating_sub(name.width() + 2))
+        )];
+        for text in self.graph.column_lines(table, self.detail) {
+            let padding = " ".repeat(inner.saturating_sub(text.width() + 2));
+            lines.push(format!("│ {text}{padding} │"));
+        }
+        lines.push(format!("└{}┘", "─".repeat(inner)));
+        lines
+    }
+
+    /// Row of a column on the side of a table's box, or the middle row when it isn't shown.
+    fn column_row(&self, table: usize, column: Option<usize>) -> u16 {
+        let rect = self.layout.boxes[table];
+        self.graph
+            .visible_columns(table, self.detail)
+            .iter()
+            .position(|(idx, _)| Some(*idx) == column)
+            .map_or(rect.y + rect.height / 2, |row| rect.y + 1 + row as u16)
+    }
+
+    /// Start and end cell of the line of each foreign key, on the sides of the boxes facing each
+    /// other, with the border character marking it, and whether it belongs to the selected table.
+    fn edge_points(&self) -> Vec<(Anchor, Anchor, bool)> {
+        self.graph
+            .edges()
+            .into_iter()
+            .filter(|edge| edge.from != edge.to)
+            .map(|edge| {
+                let from = self.layout.boxes[edge.from];
+                let to = self.layout.boxes[edge.to];
+                let primary_key = self.graph.tables[edge.to]
+                    .columns
+                    .iter()
+                    .position(|c| c.primary_key);
+                let from_row = self.column_row(edge.from, Some(edge.column));
+                let to_row = self.column_row(edge.to, primary_key);
+                let left = |rect: Rect, row: u16| (rect.x, row, '┤');
+                let right = |rect: Rect, row: u16| (rect.right() - 1, row, '├');
+                let (start, end) = if from.x > to.right() {
+                    (left(from, from_row), right(to, to_row))
+                } else if from.right() < to.x {
+                    (right(from, from_row), left(to, to_row))
+                } else {
+                    (left(from, from_row), left(to, to_row))
+                };
+                let highlighted =
+                    self.selected == Some(edge.from) || self.selected == Some(edge.to);
+                (start, end, highlighted)
+            })
+            .collect()
+    }
+
+    /// Sets a cell of the diagram, if it is in view.
+    fn put(
+        &self,
+        buf: &mut ratatui::buffer::Buffer,
+        area: Rect,
+        x: u16,
+        y: u16,
+        symbol: &str,
+        style: Style,
+    ) {
+        if x < self.offset.0 || y < self.offset.1 {
+            return;
+        }
+        let (x, y) = (area.x + x - self.offset.0, area.y + y - self.offset.1);
+        if x < area.right() && y < area.bottom() {
+            buf[(x, y)].set_symbol(symbol).set_style(style);
+        }
+    }
+
+    /// Writes the diagram to a file, as DOT for `.dot` and `.gv` files and Mermaid otherwise.
+    fn export(&self, path: PathBuf) -> Action {
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("dot" | "gv") => self.graph.to_dot(),
+            _ => self.graph.to_mermaid(),
+        };
+        match fs::write(expand_home(path.clone()), contents) {
+            Ok(()) => Action::Info(format!("Exported diagram to {}", path.display())),
+            Err(e) => Action::Error(format!("Failed to export {}: {e}", path.display())),
+        }
+    }
+}
+
+/// The cells a line of text takes, by their column. Wide characters take two cells, with the
+/// second blank as ratatui leaves it, and combining marks join the character before them.
+fn cells(line: &str) -> Vec<(usize, String)> {
+    let mut cells: Vec<(usize, String)> = vec![];
+    let mut col = 0;
+    for c in line.chars() {
+        match c.width().unwrap_or_default() {
+            0 => match cells
+                .iter_mut()
+                .rev()
+                .find(|(_, symbol)| !symbol.is_empty())
+            {
+                Some((_, symbol)) => symbol.push(c),
+                None => cells.push((col, c.to_string())),
+            },
+            width => {
+                cells.push((col, c.to_string()));
+                cells.extend((1..width).map(|offset| (col + offset, String::from(" "))));
+                col += width;
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::database::{connection::QueryResult, value::Value};
+
+    fn diagram(tables: &[[&str; 3]]) -> Diagram {
+        let mut diagram = Diagram::default();
+        let rows = tables
+            .iter()
+            .map(|[table, column, typ]| {
+                [*table, *column, *typ, "false", ""]
+                    .iter()
+                    .map(|v| match v {
+                        &"" => Value::Null,
+                        v => Value::Text(v.to_string()),
+                    })
+                    .collect()
+            })
+            .collect();
+        diagram
+            .handle_app_events(AppEvent::QueryResult(
+                QueryResult {
+                    rows,
+                    ..Default::default()
+                },
+                QueryTag::SchemaDiagram(String::from("public")),
+            ))
+            .unwrap();
+        diagram
+    }
+
+    fn render(diagram: &mut Diagram) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(30, 8)).unwrap();
+        terminal
+            .draw(|frame| diagram.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wide_names() {
+        let mut diagram = diagram(&[["ユーザー", "名前", "text"], ["ユーザー", "id", "int"]]);
+        let lines = render(&mut diagram);
+        // The cells after wide characters are blank, which terminals draw the characters over
+        assert_eq!(
+            lines[1..5],
+            [
+                "│┌ ユ ー ザ ー  ────┐            │",
+                "││    名 前  text │            │",
+                "││    id   int  │            │",
+                "│└──────────────┘            │",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_combining_marks() {
+        // An e followed by a combining acute accent
+        let mut diagram = diagram(&[["cafe\u{301}", "id", "int"]]);
+        diagram.set_detail(Detail::Names);
+        let lines = render(&mut diagram);
+        assert_eq!(lines[1], "│┌ cafe\u{301} ─────┐               │");
+    }
+}
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+
+use ratatui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
+use crate::database::value::Value;
+
+/// Horizontal space between the layers of tables, where the lines run
+const LAYER_GAP: u16 = 8;
+/// Vertical space between the tables of a layer
+const TABLE_GAP: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub data_type: String,
+    pub primary_key: bool,
+    /// Table referenced by a foreign key on the column, qualified when in another schema
+    pub references: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableNode {
+    pub name: String,
+    pub columns: Vec<Column>,
+}
+
+/// How much of each table is drawn, from zoomed in to zoomed out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Detail {
+    #[default]
+    Columns,
+    /// Only primary and foreign key columns
+    Keys,
+    Names,
+}
+
+impl Detail {
+    pub fn zoom_in(self) -> Self {
+        match self {
+            Detail::Names => Detail::Keys,
+            _ => Detail::Columns,
+        }
+    }
+
+    pub fn zoom_out(self) -> Self {
+        match self {
+            Detail::Columns => Detail::Keys,
+            _ => Detail::Names,
+        }
+    }
+}
+
+/// A foreign key relation between two tables of the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// Index of the referencing table
+    pub from: usize,
+    /// Index of the referencing column
+    pub column: usize,
+    /// Index of the referenced table
+    pub to: usize,
+}
+
+/// Position of each table, in cells.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GraphLayout {
+    /// Box of each table, by table index
+    pub boxes: Vec<Rect>,
+    /// Table indexes of each layer, from left to right and top to bottom
+    pub layers: Vec<Vec<usize>>,
+}
+
+impl GraphLayout {
+    /// Layer and position in the layer of a table
+    pub fn position(&self, table: usize) -> Option<(usize, usize)> {
+        self.layers.iter().enumerate().find_map(|(layer, tables)| {
+            tables
+                .iter()
+                .position(|t| *t == table)
+                .map(|row| (layer, row))
+        })
+    }
+}
+
+/// The tables of a schema and their foreign keys.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Graph {
+    pub schema: String,
+    pub tables: Vec<TableNode>,
+}
+
+impl Graph {
+    /// Builds the graph from rows of table, column, type, primary key and referenced table, ordered
+    /// by table.
+    pub fn from_rows(schema: String, rows: &[Vec<Value>]) -> Self {
+        let mut tables: Vec<TableNode> = vec![];
+        for row in rows {
+            let value = |idx: usize| row.get(idx).and_then(Value::text);
+            let Some(table) = value(0) else {
+                continue;
+            };
+            let column = Column {
+                name: value(1).unwrap_or_default(),
+                data_type: value(2).unwrap_or_default(),
+                primary_key: value(3).as_deref() == Some("true"),
+                references: value(4),
+            };
+            match tables.last_mut() {
+                Some(node) if node.name == table => node.columns.push(column),
+                _ => tables.push(TableNode {
+                    name: table,
+                    columns: vec![column],
+                }),
+            }
+        }
+        Self { schema, tables }
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.tables.iter().position(|t| t.name == name)
+    }
+
+    /// Foreign keys between tables of the graph. References to other schemas are left out.
+    pub fn edges(&self) -> Vec<Edge> {
+        let mut edges = vec![];
+        for (from, table) in self.tables.iter().enumerate() {
+            for (column, col) in table.columns.iter().enumerate() {
+                if let Some(to) = col.references.as_deref().and_then(|r| self.index_of(r)) {
+                    edges.push(Edge { from, column, to });
+                }
+            }
+        }
+        edges
+    }
+
+    /// Columns of a table shown at the given detail, with their index.
+    pub fn visible_columns(&self, table: usize, detail: Detail) -> Vec<(usize, &Column)> {
+        let Some(node) = self.tables.get(table) else {
+            return vec![];
+        };
+        node.columns
+            .iter()
+            .enumerate()
+            .filter(|(_, col)| match detail {
+                Detail::Columns => true,
+                Detail::Keys => col.primary_key || col.references.is_some(),
+                Detail::Names => false,
+            })
+            .collect()
+    }
+
+    /// Lines of text drawn inside the box of a table, below its name. Only the number of columns
+    /// is shown when zoomed out to names.
+    pub fn column_lines(&self, table: usize, detail: Detail) -> Vec<String> {
+        if detail == Detail::Names {
+            let count = self.tables.get(table).map_or(0, |t| t.columns.len());
+            return vec![format!("{count} columns")];
+        }
+        let columns = self.visible_columns(table, detail);
+        let name_width = columns
+            .iter()
+            .map(|(_, c)| c.name.width())
+            .max()
+            .unwrap_or_default();
+        columns
+            .iter()
+            .map(|(_, col)| {
+                let marker = match (col.primary_key, col.references.is_some()) {
+                    (true, _) => "PK",
+                    (false, true) => "FK",
+                    _ => "  ",
+                };
+                // Padded by display width, as `format!` pads by characters
+                let padding = " ".repeat(name_width - col.name.width());
+                format!("{marker} {}{padding} {}", col.name, col.data_type)
+            })
+            .collect()
+    }
+
+    /// Places referenced tables to the left of the tables referencing them, so lines mostly run
+    /// from right to left. Each layer is ordered by the position of the tables it references.
+    pub fn layout(&self, detail: Detail) -> GraphLayout {
+        let count = self.tables.len();
+        let edges: Vec<Edge> = self
+            .edges()
+            .into_iter()
+            .filter(|e| e.from != e.to)
+            .collect();
+
+        // Longest path to a table without references, bounded to terminate on cycles.
+        let mut depth = vec![0; count];
+        for _ in 0..count {
+            let mut changed = false;
+            for edge in &edges {
+                let candidate = (depth[edge.to] + 1).min(count.saturating_sub(1));
+                if candidate > depth[edge.from] {
+                    depth[edge.from] = candidate;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut layers: Vec<Vec<usize>> = vec![vec![]; depth.iter().max().map_or(0, |d| d + 1)];
+        for (table, layer) in depth.iter().enumerate() {
+            layers[*layer].push(table);
+        }
+        layers.retain(|layer| !layer.is_empty());
+
+        // Order by the average position of referenced tables in earlier layers.
+        let mut rank: HashMap<usize, f64> = HashMap::new();
+        for layer in layers.iter_mut() {
+            let mut keyed: Vec<(f64, usize)> = layer
+                .iter()
+                .map(|table| {
+                    let positions: Vec<f64> = edges
+                        .iter()
+                        .filter(|e| e.from == *table)
+                        .filter_map(|e| rank.get(&e.to).copied())
+                        .collect();
+                    let key = if positions.is_empty() {
+                        f64::MAX
+                    } else {
+                        positions.iter().sum::<f64>() / positions.len() as f64
+                    };
+                    (key, *table)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            *layer = keyed.into_iter().map(|(_, table)| table).collect();
+            for (position, table) in layer.iter().enumerate() {
+                rank.insert(*table, position as f64);
+            }
+        }
+
+        let mut boxes = vec![Rect::default(); count];
+        let mut x = 0;
+        for layer in &layers {
+            let mut y = 0;
+            let mut width = 0;
+            for table in layer {
+                let lines = self.column_lines(*table, detail);
+                let content = lines
+                    .iter()
+                    .map(|l| l.width())
+                    .chain([self.tables[*table].name.width()])
+                    .max()
+                    .unwrap_or_default() as u16;
+                let rect = Rect::new(x, y, content + 4, lines.len() as u16 + 2);
+                width = width.max(rect.width);
+                y += rect.height + TABLE_GAP;
+                boxes[*table] = rect;
+            }
+            x += width + LAYER_GAP;
+        }
+
+        GraphLayout { boxes, layers }
+    }
+
+    /// The graph in Graphviz DOT format, with each table as a record of its columns.
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| {
+            s.chars().fold(String::new(), |mut out, c| {
+                if matches!(c, '\\' | '"' | '{' | '}' | '|' | '<' | '>') {
+                    out.push('\\');
+                }
+                out.push(c);
+                out
+            })
+        };
+        let mut dot = format!(
+            "digraph \"{}\" {{\n  rankdir=LR;\n  node [shape=record];\n",
+            escape(&self.schema)
+        );
+        for table in &self.tables {
+            let columns: Vec<String> = table
+                .columns
+                .iter()
+                .map(|col| {
+                    let key = if col.primary_key { " (PK)" } else { "" };
+                    format!("{}: {}{key}\\l", escape(&col.name), escape(&col.data_type))
+                })
+                .collect();
+            dot.push_str(&format!(
+                "  \"{}\" [label=\"{{{}|{}}}\"];\n",
+                escape(&table.name),
+                escape(&table.name),
+                columns.join("")
+            ));
+        }
+        for table in &self.tables {
+            for col in &table.columns {
+                if let Some(referenced) = &col.references {
+                    dot.push_str(&format!(
+                        "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                        escape(&table.name),
+                        escape(referenced),
+                        escape(&col.name)
+                    ));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as a Mermaid ER diagram. Names are reduced to the characters Mermaid accepts.
+    pub fn to_mermaid(&self) -> String {
+        let word = |s: &str| -> String {
+            s.chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '_' | '-') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect()
+        };
+        let mut mermaid = String::from("erDiagram\n");
+        for table in &self.tables {
+            mermaid.push_str(&format!("    {} {{\n", word(&table.name)));
+            for col in &table.columns {
+                let keys: Vec<&str> = [
+                    col.primary_key.then_some("PK"),
+                    col.references.is_some().then_some("FK"),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+                let attribute = format!(
+                    "{} {} {}",
+                    word(&col.data_type),
+                    word(&col.name),
+                    keys.join(",")
+                );
+                mermaid.push_str(&format!("        {}\n", attribute.trim_end()));
+            }
+            mermaid.push_str("    }\n");
+        }
+        for table in &self.tables {
+            for col in &table.columns {
+                if let Some(referenced) = &col.references {
+                    mermaid.push_str(&format!(
+                        "    {} ||--o{{ {} : \"{}\"\n",
+                        word(referenced),
+                        word(&table.name),
+                        col.name.replace('"', "'")
+                    ));
+                }
+            }
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: [&str; 5]) -> Vec<Value> {
+        values
+            .iter()
+            .map(|v| match v {
+                &"" => Value::Null,
+                v => Value::Text(v.to_string()),
+            })
+            .collect()
+    }
+
+    fn graph() -> Graph {
+        Graph::from_rows(
+            String::from("public"),
+            &[
+                row(["authors", "id", "integer", "true", ""]),
+                row(["authors", "name", "text", "false", ""]),
+                row(["books", "id", "bigint", "true", ""]),
+                row(["books", "author_id", "integer", "false", "authors"]),
+                row(["reviews", "book_id", "bigint", "false", "books"]),
+                row(["reviews", "reviewer_id", "integer", "false", "authors"]),
+                row(["reviews", "tag_id", "integer", "false", "other.tags"]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let graph = graph();
+        assert_eq!(graph.tables.len(), 3);
+        assert_eq!(graph.tables[1].columns.len(), 2);
+        assert!(graph.tables[0].columns[0].primary_key);
+        assert_eq!(
+            graph.edges(),
+            vec![
+                Edge {
+                    from: 1,
+                    column: 1,
+                    to: 0
+                },
+                Edge {
+                    from: 2,
+                    column: 0,
+                    to: 1
+                },
+                Edge {
+                    from: 2,
+                    column: 1,
+                    to: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_layers() {
+        let graph = graph();
+        let layout = graph.layout(Detail::Columns);
+        assert_eq!(layout.layers, vec![vec![0], vec![1], vec![2]]);
+        assert!(layout.boxes[0].right() < layout.boxes[1].x);
+        assert!(layout.boxes[1].right() < layout.boxes[2].x);
+        assert_eq!(layout.boxes[2].height, 5);
+        assert_eq!(graph.layout(Detail::Keys).boxes[0].height, 3);
+        assert_eq!(graph.layout(Detail::Names).boxes[2].height, 3);
+    }
+
+    #[test]
+    fn test_column_lines_wide_names() {
+        let graph = Graph::from_rows(
+            String::from("public"),
+            &[
+                row(["users", "id", "integer", "true", ""]),
+                row(["users", "名前", "text", "false", ""]),
+            ],
+        );
+        assert_eq!(
+            graph.column_lines(0, Detail::Columns),
+            vec!["PK id   integer", "   名前 text"]
+        );
+        assert_eq!(graph.layout(Detail::Columns).boxes[0].width, 19);
+    }
+
+    #[test]
+    fn test_layout_cycle() {
+        let graph = Graph::from_rows(
+            String::from("public"),
+            &[
+                row(["a", "b_id", "integer", "false", "b"]),
+                row(["b", "a_id", "integer", "false", "a"]),
+                row(["c", "c_id", "integer", "false", "c"]),
+            ],
+        );
+        let layout = graph.layout(Detail::Columns);
+        assert_eq!(layout.layers.iter().flatten().count(), 3);
+    }
+
+    #[test]
+    fn test_exports() {
+        let graph = graph();
+        let dot = graph.to_dot();
+        assert!(
+            dot.contains("\"books\" [label=\"{books|id: bigint (PK)\\lauthor_id: integer\\l}\"];")
+        );
+        assert!(dot.contains("\"reviews\" -> \"other.tags\" [label=\"tag_id\"];"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.contains("        integer author_id FK\n"));
+        assert!(mermaid.contains("    authors ||--o{ books : \"author_id\"\n"));
+        assert!(mermaid.contains("    other_tags ||--o{ reviews : \"tag_id\"\n"));
+    }
+}
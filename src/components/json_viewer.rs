@@ -0,0 +1,320 @@
+use std::collections::HashSet;
+
+use arboard::Clipboard;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Modifier, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, List, ListState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{action::Action, app::Mode, components::Component, config::Config};
+
+mod tree;
+
+use tree::{JsonRow, JsonTree, NodeKind};
+
+/// Popup showing a JSON value as a pretty-printed, collapsible tree.
+#[derive(Default)]
+pub struct JsonViewer {
+    tree: JsonTree,
+    /// Objects and arrays whose children are hidden
+    collapsed: HashSet<usize>,
+    list_state: ListState,
+    /// Path searched for, with the nodes matching it
+    search: Option<String>,
+    matches: Vec<usize>,
+    searching: bool,
+    /// The mode to return to once the viewer is closed
+    return_mode: Mode,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for JsonViewer {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::ExploreJson) => {
+                self.focused = true;
+                return Ok(None);
+            }
+            Action::ChangeMode(mode) => {
+                if !self.focused {
+                    self.return_mode = mode;
+                }
+                self.focused = false;
+                return Ok(None);
+            }
+            Action::ViewJson(json) => {
+                return Ok(Some(match JsonTree::parse(&json) {
+                    Ok(tree) => {
+                        self.tree = tree;
+                        self.collapsed.clear();
+                        self.search = None;
+                        self.matches.clear();
+                        self.searching = false;
+                        self.list_state = ListState::default().with_selected(Some(0));
+                        Action::ChangeMode(Mode::ExploreJson)
+                    }
+                    Err(e) => Action::Error(format!("Failed to read the JSON value: {e}")),
+                }));
+            }
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        // Key inputs for typing the search are in `handle_key_event`
+        if self.searching {
+            match action {
+                Action::MakeSelection => {
+                    self.searching = false;
+                    self.select_match(0);
+                }
+                Action::Clear => {
+                    self.searching = false;
+                    self.search = None;
+                    self.matches.clear();
+                }
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        let rows = self.tree.rows(&self.collapsed);
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|idx| rows.get(idx))
+            .map(JsonRow::node);
+        match action {
+            // protect against excess navigation
+            Action::NavDown
+                if self
+                    .list_state
+                    .selected()
+                    .is_some_and(|idx| idx + 1 < rows.len()) =>
+            {
+                self.list_state.select_next();
+            }
+            Action::NavUp => self.list_state.select_previous(),
+            Action::MakeSelection => {
+                if let Some(node) = selected
+                    && self.tree.has_children(node)
+                {
+                    if !self.collapsed.remove(&node) {
+                        self.collapsed.insert(node);
+                    }
+                    self.select_node(node);
+                }
+            }
+            Action::NavLeft => {
+                if let Some(node) = selected {
+                    if self.tree.has_children(node) && self.collapsed.insert(node) {
+                        self.select_node(node);
+                    } else if let Some(parent) = self.tree.parent(node) {
+                        // Already collapsed, move to the parent instead
+                        self.select_node(parent);
+                    }
+                }
+            }
+            Action::NavRight => {
+                if let Some(node) = selected {
+                    self.collapsed.remove(&node);
+                }
+            }
+            Action::Search => {
+                self.searching = true;
+                self.search = Some(String::new());
+            }
+            Action::SearchNext => {
+                let next = selected
+                    .and_then(|node| self.matches.iter().position(|m| *m > node))
+                    .unwrap_or_default();
+                self.select_match(next);
+            }
+            Action::Yank => {
+                if let Some(value) = selected.and_then(|node| self.tree.value(node))
+                    && let Ok(mut clipboard) = Clipboard::new()
+                {
+                    // Strings are copied without quotes
+                    let text = match value {
+                        serde_json::Value::String(text) => text.clone(),
+                        value => serde_json::to_string_pretty(value)?,
+                    };
+                    clipboard.set_text(text)?;
+                }
+            }
+            Action::YankPath => {
+                if let Some(node) = selected
+                    && let Ok(mut clipboard) = Clipboard::new()
+                {
+                    clipboard.set_text(self.tree.nodes[node].path.clone())?;
+                }
+            }
+            Action::Clear => return Ok(Some(Action::ChangeMode(self.return_mode))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+    ) -> color_eyre::Result<Option<Action>> {
+        if self.focused
+            && self.searching
+            && let Some(search) = &mut self.search
+        {
+            match key.code {
+                KeyCode::Char(c) => search.push(c),
+                KeyCode::Backspace => {
+                    search.pop();
+                }
+                _ => return Ok(None),
+            }
+            self.matches = if search.is_empty() {
+                vec![]
+            } else {
+                self.tree.search(search)
+            };
+        }
+        Ok(None)
+    }
+
+    fn draw(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+    ) -> color_eyre::Result<()> {
+        if !self.focused {
+            return Ok(());
+        }
+
+        let vertical = Layout::vertical([Constraint::Percentage(80)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+
+        let rows = self.tree.rows(&self.collapsed);
+        let path = self
+            .list_state
+            .selected()
+            .and_then(|idx| rows.get(idx))
+            .map(|row| self.tree.nodes[row.node()].path.as_str())
+            .unwrap_or("$");
+        let title_bottom = match &self.search {
+            Some(search) => format!("/{search}  {} matches", self.matches.len()),
+            None => String::from(
+                "enter: toggle  /: search paths  n: next match  y: yank value  Y: yank path  esc: close",
+            ),
+        };
+        let block = Block::bordered()
+            .title(format!("JSON {path}"))
+            .title_bottom(title_bottom)
+            .style(Color::Cyan)
+            .border_type(BorderType::Thick);
+
+        let list = List::new(rows.iter().map(|row| self.row_line(*row)))
+            .highlight_style(Modifier::REVERSED)
+            .block(block);
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.list_state.clone());
+        Ok(())
+    }
+}
+
+impl JsonViewer {
+    /// Selects the line of a node, expanding its ancestors so that it is shown.
+    fn select_node(&mut self, node: usize) {
+        let mut ancestor = self.tree.parent(node);
+        while let Some(idx) = ancestor {
+            self.collapsed.remove(&idx);
+            ancestor = self.tree.parent(idx);
+        }
+        let rows = self.tree.rows(&self.collapsed);
+        let idx = rows.iter().position(|row| *row == JsonRow::Node(node));
+        self.list_state.select(idx);
+    }
+
+    fn select_match(&mut self, idx: usize) {
+        if let Some(node) = self.matches.get(idx).copied() {
+            self.select_node(node);
+        }
+    }
+
+    fn row_line(&self, row: JsonRow) -> Line<'_> {
+        let node = &self.tree.nodes[row.node()];
+        let indent = "  ".repeat(node.depth);
+        let comma = if node.last { "" } else { "," };
+        let (open, close) = match node.kind {
+            NodeKind::Array(_) => ("[", "]"),
+            _ => ("{", "}"),
+        };
+        if let JsonRow::Close(_) = row {
+            return Line::from(format!("{indent}  {close}{comma}")).white();
+        }
+
+        let marker = if !self.tree.has_children(row.node()) {
+            " "
+        } else if self.collapsed.contains(&row.node()) {
+            "▸"
+        } else {
+            "▾"
+        };
+        let mut spans = vec![Span::from(format!("{indent}{marker} "))];
+        if let Some(key) = &node.key {
+            spans.push(Span::from(serde_json::Value::from(key.as_str()).to_string()).cyan());
+            spans.push(Span::from(": ").white());
+        }
+        match &node.kind {
+            NodeKind::Object(0) | NodeKind::Array(0) => {
+                spans.push(Span::from(format!("{open}{close}{comma}")).white());
+            }
+            NodeKind::Object(len) | NodeKind::Array(len)
+                if self.collapsed.contains(&row.node()) =>
+            {
+                let items = match node.kind {
+                    NodeKind::Array(_) if *len == 1 => "item",
+                    NodeKind::Array(_) => "items",
+                    _ if *len == 1 => "key",
+                    _ => "keys",
+                };
+                spans.push(Span::from(format!("{open}…{close}{comma}")).white());
+                spans.push(Span::from(format!("  {len} {items}")).dark_gray());
+            }
+            NodeKind::Object(_) | NodeKind::Array(_) => spans.push(Span::from(open).white()),
+            NodeKind::Scalar(value) => {
+                let color = match value {
+                    serde_json::Value::String(_) => Color::Green,
+                    serde_json::Value::Number(_) => Color::Yellow,
+                    serde_json::Value::Bool(_) => Color::Magenta,
+                    _ => Color::DarkGray,
+                };
+                spans.push(Span::from(value.to_string()).fg(color));
+                spans.push(Span::from(comma).white());
+            }
+        }
+        // Nodes matching the search are underlined
+        if self.matches.contains(&row.node()) {
+            Line::from(spans).underlined()
+        } else {
+            Line::from(spans)
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// What a node of a JSON value holds
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    /// An object with its number of keys
+    Object(usize),
+    /// An array with its number of elements
+    Array(usize),
+    Scalar(Value),
+}
+
+/// A node of a JSON value. Nodes are stored in depth-first order, so the descendants of a node
+/// directly follow it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonNode {
+    pub depth: usize,
+    /// Key of the node in its parent object, none for array elements and the root
+    pub key: Option<String>,
+    /// JSONPath of the node, e.g. `$.items[0].name`
+    pub path: String,
+    /// JSON pointer of the node, to look its value up in the root
+    pointer: String,
+    pub kind: NodeKind,
+    /// Whether the node is the last child of its parent, which is not followed by a comma
+    pub last: bool,
+}
+
+/// A line of the pretty-printed value: a node, or the closing bracket of an expanded object or
+/// array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonRow {
+    Node(usize),
+    Close(usize),
+}
+
+impl JsonRow {
+    pub fn node(&self) -> usize {
+        match self {
+            JsonRow::Node(idx) | JsonRow::Close(idx) => *idx,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonTree {
+    root: Value,
+    pub nodes: Vec<JsonNode>,
+}
+
+impl JsonTree {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let root: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut tree = JsonTree {
+            root: Value::Null,
+            nodes: vec![],
+        };
+        tree.add_node(&root, 0, None, String::from("$"), String::new(), true);
+        tree.root = root;
+        Ok(tree)
+    }
+
+    fn add_node(
+        &mut self,
+        value: &Value,
+        depth: usize,
+        key: Option<String>,
+        path: String,
+        pointer: String,
+        last: bool,
+    ) {
+        let kind = match value {
+            Value::Object(map) => NodeKind::Object(map.len()),
+            Value::Array(values) => NodeKind::Array(values.len()),
+            scalar => NodeKind::Scalar(scalar.clone()),
+        };
+        self.nodes.push(JsonNode {
+            depth,
+            key,
+            path: path.clone(),
+            pointer: pointer.clone(),
+            kind,
+            last,
+        });
+        match value {
+            Value::Object(map) => {
+                for (idx, (key, child)) in map.iter().enumerate() {
+                    self.add_node(
+                        child,
+                        depth + 1,
+                        Some(key.clone()),
+                        format!("{path}{}", path_key(key)),
+                        format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1")),
+                        idx + 1 == map.len(),
+                    );
+                }
+            }
+            Value::Array(values) => {
+                for (idx, child) in values.iter().enumerate() {
+                    self.add_node(
+                        child,
+                        depth + 1,
+                        None,
+                        format!("{path}[{idx}]"),
+                        format!("{pointer}/{idx}"),
+                        idx + 1 == values.len(),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The value of a node
+    pub fn value(&self, idx: usize) -> Option<&Value> {
+        self.root.pointer(&self.nodes.get(idx)?.pointer)
+    }
+
+    /// Whether a node is an object or array with something in it
+    pub fn has_children(&self, idx: usize) -> bool {
+        matches!(
+            self.nodes[idx].kind,
+            NodeKind::Object(len) | NodeKind::Array(len) if len > 0
+        )
+    }
+
+    /// Index of the parent of a node, none for the root
+    pub fn parent(&self, idx: usize) -> Option<usize> {
+        let depth = self.nodes[idx].depth;
+        (0..idx).rev().find(|i| self.nodes[*i].depth < depth)
+    }
+
+    /// The lines shown, leaving out the descendants of collapsed nodes. Expanded objects and
+    /// arrays are closed on their own line.
+    pub fn rows(&self, collapsed: &HashSet<usize>) -> Vec<JsonRow> {
+        let mut rows = vec![];
+        // Expanded containers waiting for their closing line
+        let mut open: Vec<usize> = vec![];
+        let mut hidden_below: Option<usize> = None;
+        for (idx, node) in self.nodes.iter().enumerate() {
+            match hidden_below {
+                Some(depth) if node.depth > depth => continue,
+                _ => hidden_below = None,
+            }
+            while let Some(container) = open.last()
+                && self.nodes[*container].depth >= node.depth
+            {
+                rows.push(JsonRow::Close(*container));
+                open.pop();
+            }
+            rows.push(JsonRow::Node(idx));
+            if collapsed.contains(&idx) {
+                hidden_below = Some(node.depth);
+            } else if self.has_children(idx) {
+                open.push(idx);
+            }
+        }
+        rows.extend(open.into_iter().rev().map(JsonRow::Close));
+        rows
+    }
+
+    /// Nodes whose path contains the search, ignoring case
+    pub fn search(&self, search: &str) -> Vec<usize> {
+        let search = search.to_lowercase();
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.path.to_lowercase().contains(&search))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
+
+/// The part of a JSONPath selecting a key, in dot notation when the key is an identifier.
+fn path_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if identifier {
+        format!(".{key}")
+    } else {
+        format!("['{}']", key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str =
+        r#"{"id": 1, "tags": ["a", "b"], "owner": {"first name": "Ann"}, "empty": []}"#;
+
+    #[test]
+    fn test_parse() {
+        let tree = JsonTree::parse(JSON).unwrap();
+        let paths: Vec<&str> = tree.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "$",
+                "$.id",
+                "$.tags",
+                "$.tags[0]",
+                "$.tags[1]",
+                "$.owner",
+                "$.owner['first name']",
+                "$.empty",
+            ]
+        );
+        assert_eq!(tree.nodes[2].kind, NodeKind::Array(2));
+        assert!(tree.nodes[4].last);
+        assert!(!tree.nodes[5].last);
+        assert_eq!(tree.value(6), Some(&Value::from("Ann")));
+        assert_eq!(tree.parent(6), Some(5));
+        assert_eq!(tree.parent(0), None);
+        assert!(!tree.has_children(7));
+        assert!(JsonTree::parse("{").is_err());
+    }
+
+    #[test]
+    fn test_rows() {
+        let tree = JsonTree::parse(JSON).unwrap();
+        let rows = tree.rows(&HashSet::from([2]));
+        assert_eq!(
+            rows,
+            vec![
+                JsonRow::Node(0),
+                JsonRow::Node(1),
+                JsonRow::Node(2),
+                JsonRow::Node(5),
+                JsonRow::Node(6),
+                JsonRow::Close(5),
+                JsonRow::Node(7),
+                JsonRow::Close(0),
+            ]
+        );
+        assert_eq!(tree.rows(&HashSet::from([0])), vec![JsonRow::Node(0)]);
+    }
+
+    #[test]
+    fn test_search_and_path_keys() {
+        let tree = JsonTree::parse(JSON).unwrap();
+        assert_eq!(tree.search("TAGS["), vec![3, 4]);
+        assert_eq!(path_key("it's"), r"['it\'s']");
+        assert_eq!(path_key("_a1"), "._a1");
+        assert_eq!(path_key("1a"), "['1a']");
+    }
+
+    #[test]
+    fn test_numbers_keep_their_text() {
+        let tree = JsonTree::parse(r#"[1.10, 123456789012345678901, 1e3]"#).unwrap();
+        let numbers: Vec<String> = (1..4)
+            .map(|idx| tree.value(idx).unwrap().to_string())
+            .collect();
+        assert_eq!(numbers, ["1.10", "123456789012345678901", "1e3"]);
+    }
+}
@@ -0,0 +1,312 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Block, BorderType, Borders, Cell, Row, Table, TableState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::Component,
+    config::Config,
+    database::{
+        connection::QueryResult,
+        system_query::{self, SystemQuery},
+        value::Value,
+    },
+};
+
+/// How often the overview is fetched again while it is shown
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Transactions counted by the server at a point in time, to compute the rate between two samples
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    transactions: i64,
+    /// Seconds since the epoch, on the server clock
+    sampled_at: f64,
+}
+
+/// Health of the database shown after connecting: size, connections, cache hit ratio, transaction
+/// rate, replication and the largest tables.
+#[derive(Default)]
+pub struct Overview {
+    database: String,
+    /// Label, value and color of each statistic
+    stats: Vec<(&'static str, String, Color)>,
+    previous: Option<Sample>,
+    /// Largest tables, with their total size and estimated rows
+    tables: Vec<(system_query::Table, String, String)>,
+    state: TableState,
+    last_refresh: Option<Instant>,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for Overview {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::Overview) => {
+                self.focused = true;
+                self.refresh()?;
+                return Ok(None);
+            }
+            Action::ChangeMode(_) => self.focused = false,
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        match action {
+            Action::Tick
+                if self
+                    .last_refresh
+                    .is_none_or(|last| last.elapsed() >= REFRESH_INTERVAL) =>
+            {
+                self.refresh()?;
+            }
+            // protect against excess navigation
+            Action::NavDown
+                if self
+                    .state
+                    .selected()
+                    .is_some_and(|idx| idx + 1 < self.tables.len()) =>
+            {
+                self.state.select_next();
+            }
+            Action::NavUp => self.state.select_previous(),
+            Action::MakeSelection => {
+                if let Some((table, _, _)) = self.state.selected().and_then(|i| self.tables.get(i))
+                {
+                    return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                        QueryTag::TableStructure(table.clone()),
+                    )?)));
+                }
+            }
+            Action::Clear => return Ok(Some(Action::ChangeMode(Mode::ExploreTables))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        match event {
+            // Rates are measured from scratch on a new connection
+            AppEvent::DbConnectionEstablished(_) => {
+                self.previous = None;
+                self.stats.clear();
+                self.tables.clear();
+            }
+            AppEvent::QueryResult(result, QueryTag::Overview) => self.read_stats(&result),
+            AppEvent::QueryResult(result, QueryTag::LargestTables) => {
+                self.tables = result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        let mut values = row.iter().map(|v| v.text().unwrap_or_default());
+                        let mut next = || values.next().unwrap_or_default();
+                        let table = system_query::Table {
+                            schema: next(),
+                            name: next(),
+                        };
+                        (table, next(), next())
+                    })
+                    .collect();
+                let idx = self.state.selected().unwrap_or_default();
+                self.state.select(
+                    (!self.tables.is_empty())
+                        .then_some(idx.min(self.tables.len().saturating_sub(1))),
+                );
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let block = Block::bordered()
+            .title(format!("Overview of {} [alt+7]", self.database))
+            .title_bottom("enter: table structure  esc: tables")
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(if self.focused {
+                Color::Cyan
+            } else {
+                Color::Blue
+            }))
+            .border_type(if self.focused {
+                BorderType::Thick
+            } else {
+                BorderType::Plain
+            });
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [stats_area, tables_area] = Layout::vertical([
+            Constraint::Length(self.stats.len() as u16 + 1),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+
+        let label_width = self.stats.iter().map(|(label, _, _)| label.len()).max();
+        let stats = Table::new(
+            self.stats.iter().map(|(label, value, color)| {
+                Row::new([
+                    Cell::from(*label).bold(),
+                    Cell::from(value.as_str()).fg(*color),
+                ])
+            }),
+            [
+                Constraint::Length(label_width.unwrap_or_default() as u16),
+                Constraint::Fill(1),
+            ],
+        )
+        .column_spacing(2);
+        frame.render_widget(stats, stats_area);
+
+        let header =
+            Row::new(["Schema", "Table", "Size", "Estimated rows"]).style(Style::new().bold());
+        let tables = Table::new(
+            self.tables.iter().map(|(table, size, rows)| {
+                Row::new([
+                    table.schema.as_str(),
+                    table.name.as_str(),
+                    size.as_str(),
+                    rows.as_str(),
+                ])
+            }),
+            [
+                Constraint::Length(16),
+                Constraint::Fill(1),
+                Constraint::Length(12),
+                Constraint::Length(16),
+            ],
+        )
+        .header(header)
+        .style(Color::White)
+        .row_highlight_style(Style::new().on_dark_gray().bold())
+        .highlight_symbol("▷ ")
+        .block(
+            Block::new()
+                .borders(Borders::TOP)
+                .title("Largest tables")
+                .fg(Color::Cyan),
+        );
+        frame.render_stateful_widget(tables, tables_area, &mut self.state);
+        Ok(())
+    }
+}
+
+impl Overview {
+    fn refresh(&mut self) -> color_eyre::Result<()> {
+        self.last_refresh = Some(Instant::now());
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::ExecuteQuery(SystemQuery::query_for(
+                QueryTag::Overview,
+            )?))?;
+            tx.send(Action::ExecuteQuery(SystemQuery::query_for(
+                QueryTag::LargestTables,
+            )?))?;
+        }
+        Ok(())
+    }
+
+    /// Reads the single row of the overview query, whose columns are looked up by name.
+    fn read_stats(&mut self, result: &QueryResult) {
+        let row = result.rows.first();
+        let value = |name: &str| {
+            let idx = result.columns.iter().position(|c| c == name)?;
+            row?.get(idx).and_then(Value::text)
+        };
+        let number = |name: &str| value(name).and_then(|v| v.parse::<f64>().ok());
+
+        self.database = value("database").unwrap_or_default();
+        let mut stats = vec![(
+            "Server version",
+            value("server_version").unwrap_or_default(),
+            Color::White,
+        )];
+        stats.push((
+            "Database size",
+            value("database_size").unwrap_or_default(),
+            Color::White,
+        ));
+
+        let (connections, max) = (number("connections"), number("max_connections"));
+        if let (Some(connections), Some(max)) = (connections, max) {
+            let used = connections / max.max(1.0);
+            let color = if used >= 0.9 {
+                Color::Red
+            } else if used >= 0.75 {
+                Color::Yellow
+            } else {
+                Color::White
+            };
+            stats.push(("Connections", format!("{connections} / {max}"), color));
+        }
+
+        match number("cache_hit_ratio") {
+            Some(ratio) => {
+                let color = if ratio < 0.9 {
+                    Color::Red
+                } else if ratio < 0.99 {
+                    Color::Yellow
+                } else {
+                    Color::White
+                };
+                stats.push(("Cache hit ratio", format!("{:.2}%", ratio * 100.0), color));
+            }
+            None => stats.push(("Cache hit ratio", String::from("-"), Color::White)),
+        }
+
+        // The rate is measured between two refreshes
+        let sample = value("transactions")
+            .and_then(|v| v.parse().ok())
+            .zip(number("sampled_at"))
+            .map(|(transactions, sampled_at)| Sample {
+                transactions,
+                sampled_at,
+            });
+        let rate = match (self.previous, sample) {
+            (Some(previous), Some(sample)) if sample.sampled_at > previous.sampled_at => {
+                let transactions = (sample.transactions - previous.transactions).max(0) as f64;
+                format!(
+                    "{:.1}/s",
+                    transactions / (sample.sampled_at - previous.sampled_at)
+                )
+            }
+            _ => String::from("measuring..."),
+        };
+        self.previous = sample.or(self.previous);
+        stats.push(("Transactions", rate, Color::White));
+
+        let replication = if value("in_recovery").as_deref() == Some("true") {
+            match number("replay_delay") {
+                Some(delay) => format!("standby, replayed up to {delay:.1}s ago"),
+                None => String::from("standby"),
+            }
+        } else {
+            match value("replicas") {
+                Some(replicas) => format!("primary, streaming to {replicas}"),
+                None => String::from("primary, no replicas"),
+            }
+        };
+        stats.push(("Replication", replication, Color::White));
+        self.stats = stats;
+    }
+}
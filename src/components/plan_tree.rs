@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListState, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::Component,
+    config::Config,
+    database::value::Value,
+};
+
+mod plan;
+
+use plan::{EXPENSIVE_SHARE, MISESTIMATE_FACTOR, Plan};
+
+/// Collapsible tree of the nodes of a query plan, from `EXPLAIN` or `EXPLAIN ANALYZE`.
+#[derive(Default)]
+pub struct PlanTree {
+    plan: Plan,
+    /// Nodes whose children are hidden
+    collapsed: HashSet<usize>,
+    list_state: ListState,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for PlanTree {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::ExplorePlan) => self.focused = true,
+            Action::ChangeMode(_) => self.focused = false,
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        let rows = self.visible_nodes();
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|idx| rows.get(idx).copied());
+        match action {
+            // protect against excess navigation
+            Action::NavDown
+                if self
+                    .list_state
+                    .selected()
+                    .is_some_and(|idx| idx + 1 < rows.len()) =>
+            {
+                self.list_state.select_next();
+            }
+            Action::NavUp => self.list_state.select_previous(),
+            Action::MakeSelection => {
+                if let Some(node) = selected
+                    && !self.plan.children(node).is_empty()
+                    && !self.collapsed.remove(&node)
+                {
+                    self.collapsed.insert(node);
+                }
+            }
+            Action::NavLeft => {
+                if let Some(node) = selected {
+                    if !self.plan.children(node).is_empty() && self.collapsed.insert(node) {
+                        return Ok(None);
+                    }
+                    // Already collapsed, move to the parent instead
+                    let depth = self.plan.nodes[node].depth;
+                    if let Some(parent) = rows
+                        .iter()
+                        .rposition(|idx| *idx < node && self.plan.nodes[*idx].depth < depth)
+                    {
+                        self.list_state.select(Some(parent));
+                    }
+                }
+            }
+            Action::NavRight => {
+                if let Some(node) = selected {
+                    self.collapsed.remove(&node);
+                }
+            }
+            Action::Clear => return Ok(Some(Action::ChangeMode(Mode::EditQuery))),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        if let AppEvent::QueryResult(result, QueryTag::Explain { .. }) = event {
+            // The plan is a single JSON value
+            let json = result
+                .rows
+                .first()
+                .and_then(|row| row.first().and_then(Value::text))
+                .unwrap_or_default();
+            return Ok(Some(match Plan::parse(&json) {
+                Ok(plan) => {
+                    self.plan = plan;
+                    self.collapsed.clear();
+                    self.list_state = ListState::default().with_selected(Some(0));
+                    Action::ChangeMode(Mode::ExplorePlan)
+                }
+                Err(e) => Action::Error(format!("Failed to read the plan: {e}")),
+            }));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let mut title = String::from(if self.plan.analyzed() {
+            "Plan (analyzed)"
+        } else {
+            "Plan"
+        });
+        if let Some(time) = self.plan.planning_time {
+            title.push_str(&format!(" planning {time:.3} ms"));
+        }
+        if let Some(time) = self.plan.execution_time {
+            title.push_str(&format!(", execution {time:.3} ms"));
+        }
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom("enter: toggle  h/l: collapse/expand  esc: back to editor")
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(if self.focused {
+                Color::Cyan
+            } else {
+                Color::Blue
+            }))
+            .border_type(if self.focused {
+                BorderType::Thick
+            } else {
+                BorderType::Plain
+            });
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let rows = self.visible_nodes();
+        let list = List::new(rows.iter().map(|idx| self.node_line(*idx)))
+            .highlight_style(Modifier::REVERSED);
+
+        // Other properties of the selected node are listed below the tree.
+        let details = self
+            .list_state
+            .selected()
+            .and_then(|idx| rows.get(idx))
+            .map(|idx| {
+                self.plan.nodes[*idx]
+                    .details
+                    .iter()
+                    .map(|(key, value)| {
+                        Line::from(vec![
+                            Span::from(format!("{key}: ")).bold(),
+                            Span::from(value.as_str()),
+                        ])
+                    })
+                    .collect::<Vec<Line>>()
+            })
+            .unwrap_or_default();
+        let height = (details.len() as u16 + 1).min(inner.height / 3);
+        let [tree_area, details_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(height)]).areas(inner);
+
+        frame.render_stateful_widget(list, tree_area, &mut self.list_state.clone());
+        frame.render_widget(
+            Paragraph::new(details)
+                .wrap(Wrap { trim: false })
+                .block(Block::new().borders(Borders::TOP).title("Details"))
+                .fg(Color::White),
+            details_area,
+        );
+        Ok(())
+    }
+}
+
+impl PlanTree {
+    /// Indexes of the nodes shown, leaving out the descendants of collapsed nodes.
+    fn visible_nodes(&self) -> Vec<usize> {
+        let mut rows = vec![];
+        let mut hidden_below: Option<usize> = None;
+        for (idx, node) in self.plan.nodes.iter().enumerate() {
+            match hidden_below {
+                Some(depth) if node.depth > depth => continue,
+                _ => hidden_below = None,
+            }
+            rows.push(idx);
+            if self.collapsed.contains(&idx) {
+                hidden_below = Some(node.depth);
+            }
+        }
+        rows
+    }
+
+    fn node_line(&self, idx: usize) -> Line<'_> {
+        let node = &self.plan.nodes[idx];
+        let leaf = self
+            .plan
+            .nodes
+            .get(idx + 1)
+            .is_none_or(|next| next.depth <= node.depth);
+        let marker = if leaf {
+            "·"
+        } else if self.collapsed.contains(&idx) {
+            "▸"
+        } else {
+            "▾"
+        };
+        let share = node.share;
+        let cost_color = if share >= 0.5 {
+            Color::Red
+        } else if share >= EXPENSIVE_SHARE {
+            Color::Yellow
+        } else {
+            Color::White
+        };
+
+        let mut spans = vec![
+            Span::from(format!("{}{marker} ", "  ".repeat(node.depth))),
+            Span::from(node.node_type.clone()).fg(cost_color).bold(),
+        ];
+        if let Some(target) = &node.target {
+            spans.push(Span::from(format!(" {target}")).fg(Color::Cyan));
+        }
+        spans.push(
+            Span::from(format!(
+                "  cost={:.2}..{:.2}",
+                node.startup_cost, node.total_cost
+            ))
+            .dark_gray(),
+        );
+        match node.actual_rows {
+            Some(actual) => {
+                spans.push(Span::from(format!("  rows={}→{actual}", node.plan_rows)));
+                if let Some(factor) = node.misestimate().filter(|f| *f >= MISESTIMATE_FACTOR) {
+                    spans.push(Span::from(format!(" (×{factor:.0})")).fg(Color::Magenta));
+                }
+            }
+            None => spans.push(Span::from(format!("  rows={}", node.plan_rows))),
+        }
+        if let Some(time) = node.actual_time {
+            spans.push(Span::from(format!("  time={time:.3} ms")));
+        }
+        if let Some(loops) = node.actual_loops {
+            spans.push(Span::from(format!("  loops={loops}")));
+        }
+        if let (Some(hit), Some(read)) = (node.shared_hit, node.shared_read) {
+            spans.push(Span::from(format!("  buffers hit={hit} read={read}")).dark_gray());
+        }
+        spans.push(Span::from(format!("  {:.0}%", share * 100.0)).fg(cost_color));
+        Line::from(spans)
+    }
+}
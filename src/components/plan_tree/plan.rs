@@ -0,0 +1,315 @@
+use serde_json::{Map, Value};
+
+/// Share of the total time or cost above which a node is highlighted as expensive
+pub const EXPENSIVE_SHARE: f64 = 0.1;
+/// Factor between estimated and actual rows above which a node is highlighted as misestimated
+pub const MISESTIMATE_FACTOR: f64 = 10.0;
+
+/// Keys shown in the line of a node rather than in its details
+const SUMMARY_KEYS: [&str; 17] = [
+    "Node Type",
+    "Plans",
+    "Startup Cost",
+    "Total Cost",
+    "Plan Rows",
+    "Plan Width",
+    "Actual Startup Time",
+    "Actual Total Time",
+    "Actual Rows",
+    "Actual Loops",
+    "Shared Hit Blocks",
+    "Shared Read Blocks",
+    "Relation Name",
+    "Alias",
+    "Index Name",
+    "Parallel Aware",
+    "Async Capable",
+];
+
+/// A node of a query plan. Nodes are stored in depth-first order, so the descendants of a node
+/// directly follow it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanNode {
+    pub depth: usize,
+    /// Node type, with the join type and scan direction when they apply, e.g. `Hash Left Join`
+    pub node_type: String,
+    /// Relation, alias or index the node reads from, e.g. `using books_pkey on books b`
+    pub target: Option<String>,
+    pub startup_cost: f64,
+    pub total_cost: f64,
+    pub plan_rows: f64,
+    /// Rows returned by each loop, when the query was analyzed
+    pub actual_rows: Option<f64>,
+    pub actual_loops: Option<f64>,
+    /// Time of each loop in milliseconds, including children
+    pub actual_time: Option<f64>,
+    /// Shared buffers hit and read, including children
+    pub shared_hit: Option<i64>,
+    pub shared_read: Option<i64>,
+    /// Other properties of the node, e.g. its conditions and sort keys
+    pub details: Vec<(String, String)>,
+    /// Time, or cost when not analyzed, spent in the node itself rather than its children
+    pub exclusive: f64,
+    /// Share of the whole plan's time or cost spent in the node itself
+    pub share: f64,
+}
+
+impl PlanNode {
+    /// Total time spent in all loops, in milliseconds
+    pub fn total_time(&self) -> Option<f64> {
+        Some(self.actual_time? * self.actual_loops.unwrap_or(1.0))
+    }
+
+    /// How many times the estimated rows were off from the actual rows, when it was analyzed. Never
+    /// executed nodes are left out.
+    pub fn misestimate(&self) -> Option<f64> {
+        if self.actual_loops == Some(0.0) {
+            return None;
+        }
+        let actual = self.actual_rows?.max(1.0);
+        let estimated = self.plan_rows.max(1.0);
+        Some((actual / estimated).max(estimated / actual))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    pub nodes: Vec<PlanNode>,
+    pub planning_time: Option<f64>,
+    pub execution_time: Option<f64>,
+}
+
+impl Plan {
+    /// Parses the output of `EXPLAIN (FORMAT JSON)`.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let explained = value
+            .as_array()
+            .and_then(|statements| statements.first())
+            .and_then(Value::as_object)
+            .ok_or("Expected the JSON output of EXPLAIN")?;
+        let root = explained
+            .get("Plan")
+            .and_then(Value::as_object)
+            .ok_or("The plan has no root node")?;
+        let mut plan = Plan {
+            planning_time: explained.get("Planning Time").and_then(Value::as_f64),
+            execution_time: explained.get("Execution Time").and_then(Value::as_f64),
+            ..Default::default()
+        };
+        plan.add_node(root, 0);
+        plan.add_shares();
+        Ok(plan)
+    }
+
+    fn add_node(&mut self, node: &Map<String, Value>, depth: usize) {
+        let text = |key: &str| node.get(key).and_then(Value::as_str).map(String::from);
+        let number = |key: &str| node.get(key).and_then(Value::as_f64);
+
+        let mut node_type = text("Node Type").unwrap_or_default();
+        if let Some(join) = text("Join Type").filter(|j| j != "Inner") {
+            node_type = node_type.replacen(" Join", &format!(" {join} Join"), 1);
+            node_type = node_type.replacen("Nested Loop", &format!("Nested Loop {join}"), 1);
+        }
+        if text("Scan Direction").as_deref() == Some("Backward") {
+            node_type = format!("{node_type} Backward");
+        }
+        let relation = match (text("Relation Name"), text("Alias")) {
+            (Some(relation), Some(alias)) if relation != alias => {
+                Some(format!("{relation} {alias}"))
+            }
+            (relation, alias) => relation.or(alias),
+        };
+        let target = match (text("Index Name"), relation) {
+            (Some(index), Some(relation)) => Some(format!("using {index} on {relation}")),
+            (Some(index), None) => Some(format!("on {index}")),
+            (None, Some(relation)) => Some(format!("on {relation}")),
+            (None, None) => None,
+        };
+
+        let details = node
+            .iter()
+            .filter(|(key, value)| {
+                let summary = SUMMARY_KEYS.contains(&key.as_str())
+                    || matches!(
+                        key.as_str(),
+                        "Join Type" | "Scan Direction" | "Parent Relationship"
+                    );
+                // Leave out the buffer and I/O counters that are zero
+                let zero_counter = (key.ends_with("Blocks") || key.starts_with("I/O"))
+                    && value.as_f64() == Some(0.0);
+                !summary && !zero_counter
+            })
+            .map(|(key, value)| (key.clone(), display_value(value)))
+            .collect();
+
+        self.nodes.push(PlanNode {
+            depth,
+            node_type,
+            target,
+            startup_cost: number("Startup Cost").unwrap_or_default(),
+            total_cost: number("Total Cost").unwrap_or_default(),
+            plan_rows: number("Plan Rows").unwrap_or_default(),
+            actual_rows: number("Actual Rows"),
+            actual_loops: number("Actual Loops"),
+            actual_time: number("Actual Total Time"),
+            shared_hit: node.get("Shared Hit Blocks").and_then(Value::as_i64),
+            shared_read: node.get("Shared Read Blocks").and_then(Value::as_i64),
+            details,
+            exclusive: 0.0,
+            share: 0.0,
+        });
+
+        for child in node
+            .get("Plans")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object)
+        {
+            self.add_node(child, depth + 1);
+        }
+    }
+
+    /// Whether the plan has actual times and rows from `EXPLAIN ANALYZE`
+    pub fn analyzed(&self) -> bool {
+        self.nodes.first().is_some_and(|n| n.actual_time.is_some())
+    }
+
+    /// Indexes of the direct children of a node
+    pub fn children(&self, idx: usize) -> Vec<usize> {
+        let Some(node) = self.nodes.get(idx) else {
+            return vec![];
+        };
+        self.nodes[idx + 1..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, n)| n.depth > node.depth)
+            .filter(|(_, n)| n.depth == node.depth + 1)
+            .map(|(offset, _)| idx + 1 + offset)
+            .collect()
+    }
+
+    /// Works out the time or cost of each node spent in the node itself, and its share of the
+    /// whole plan's, in one pass as they are drawn for every line.
+    fn add_shares(&mut self) {
+        let inclusive = |n: &PlanNode| n.total_time().unwrap_or(n.total_cost);
+        // The last node seen at each depth, which is the parent of the next node a level deeper
+        let mut ancestors: Vec<usize> = vec![];
+        for idx in 0..self.nodes.len() {
+            let depth = self.nodes[idx].depth;
+            ancestors.truncate(depth);
+            self.nodes[idx].exclusive += inclusive(&self.nodes[idx]);
+            if let Some(parent) = depth.checked_sub(1).and_then(|d| ancestors.get(d)) {
+                self.nodes[*parent].exclusive -= inclusive(&self.nodes[idx]);
+            }
+            ancestors.push(idx);
+        }
+        for node in self.nodes.iter_mut() {
+            node.exclusive = node.exclusive.max(0.0);
+        }
+        let total: f64 = self.nodes.iter().map(|n| n.exclusive).sum();
+        if total > 0.0 {
+            for node in self.nodes.iter_mut() {
+                node.share = node.exclusive / total;
+            }
+        }
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"[{
+        "Plan": {
+            "Node Type": "Hash Join", "Join Type": "Left", "Startup Cost": 12.5, "Total Cost": 39.5,
+            "Plan Rows": 50, "Actual Total Time": 2.0, "Actual Rows": 1000, "Actual Loops": 1,
+            "Hash Cond": "(a.id = b.author_id)", "Shared Hit Blocks": 2, "Temp Read Blocks": 0,
+            "Plans": [
+                {"Node Type": "Seq Scan", "Relation Name": "books", "Alias": "b",
+                 "Startup Cost": 0, "Total Cost": 22, "Plan Rows": 1200,
+                 "Actual Total Time": 0.5, "Actual Rows": 1000, "Actual Loops": 1},
+                {"Node Type": "Hash", "Startup Cost": 11.88, "Total Cost": 11.88, "Plan Rows": 50,
+                 "Actual Total Time": 0.25, "Actual Rows": 2, "Actual Loops": 2,
+                 "Plans": [
+                    {"Node Type": "Index Scan", "Index Name": "authors_pkey",
+                     "Relation Name": "authors", "Alias": "authors", "Startup Cost": 0,
+                     "Total Cost": 11.88, "Plan Rows": 50, "Actual Total Time": 0.1,
+                     "Actual Rows": 2, "Actual Loops": 2}
+                 ]}
+            ]
+        },
+        "Planning Time": 0.1,
+        "Execution Time": 2.1
+    }]"#;
+
+    #[test]
+    fn test_parse() {
+        let plan = Plan::parse(PLAN).unwrap();
+        assert!(plan.analyzed());
+        assert_eq!(plan.execution_time, Some(2.1));
+        let types: Vec<(usize, &str)> = plan
+            .nodes
+            .iter()
+            .map(|n| (n.depth, n.node_type.as_str()))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                (0, "Hash Left Join"),
+                (1, "Seq Scan"),
+                (1, "Hash"),
+                (2, "Index Scan")
+            ]
+        );
+        assert_eq!(plan.nodes[1].target.as_deref(), Some("on books b"));
+        assert_eq!(
+            plan.nodes[3].target.as_deref(),
+            Some("using authors_pkey on authors")
+        );
+        assert_eq!(
+            plan.nodes[0].details,
+            vec![(
+                String::from("Hash Cond"),
+                String::from("(a.id = b.author_id)")
+            )]
+        );
+        assert_eq!(plan.children(0), vec![1, 2]);
+        assert_eq!(plan.children(2), vec![3]);
+    }
+
+    #[test]
+    fn test_exclusive_time_and_misestimate() {
+        let plan = Plan::parse(PLAN).unwrap();
+        // 2.0 ms minus 0.5 ms and 2 loops of 0.25 ms
+        assert_eq!(plan.nodes[0].exclusive, 1.0);
+        assert_eq!(plan.nodes[2].exclusive, 0.3);
+        assert_eq!(plan.nodes[0].misestimate(), Some(20.0));
+        assert_eq!(plan.nodes[3].misestimate(), Some(25.0));
+    }
+
+    #[test]
+    fn test_not_analyzed() {
+        let plan = Plan::parse(
+            r#"[{"Plan": {"Node Type": "Seq Scan", "Total Cost": 22, "Plan Rows": 1200}}]"#,
+        )
+        .unwrap();
+        assert!(!plan.analyzed());
+        assert_eq!(plan.nodes[0].misestimate(), None);
+        assert_eq!(plan.nodes[0].share, 1.0);
+        assert!(Plan::parse("{}").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Style, Stylize},
+    widgets::{Block, BorderType, Clear, Paragraph},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use tui_textarea::TextArea;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::QueryTag,
+    components::Component,
+    config::Config,
+    database::system_query::{CellUpdate, Table},
+};
+
+/// What a prompt is collecting input for. It is handed back with the submitted values so the
+/// component that opened the prompt can recognize its answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromptPurpose {
+    /// Name a new saved query with the given contents
+    SaveSnippet(String),
+    /// Confirm replacing the saved query of the given name with the contents
+    ReplaceSnippet { name: String, contents: String },
+    /// Values for the named parameters of a user query, in `$n` order, run with the given tag
+    BindParameters {
+        tag: QueryTag,
+        query: String,
+        names: Vec<String>,
+    },
+    /// Path of a file to open in the editor
+    OpenFile,
+    /// Path to save the editor buffer to
+    SaveFile,
+    /// Confirm closing an editor buffer with unsaved changes
+    CloseBuffer,
+    /// New name for the active editor buffer
+    RenameBuffer,
+    /// Confirm refreshing a materialized view
+    RefreshMaterializedView(Table),
+    /// Path to export the ER diagram to
+    ExportDiagram,
+    /// Confirm running a statement that may modify data to analyze its plan
+    ExplainAnalyze(String),
+    /// Confirm cancelling the query of the backend with the given process ID
+    CancelBackend(i32),
+    /// Confirm terminating the backend with the given process ID
+    TerminateBackend(i32),
+    /// Confirm writing an edited value back to its table
+    UpdateCell(CellUpdate),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptRequest {
+    pub purpose: PromptPurpose,
+    pub title: String,
+    /// Label and initial value of each input field
+    pub fields: Vec<(String, String)>,
+}
+
+/// Popup collecting text input on behalf of other components.
+#[derive(Default)]
+pub struct Prompt<'a> {
+    request: Option<PromptRequest>,
+    inputs: Vec<TextArea<'a>>,
+    /// Index of the input receiving key events
+    selected: usize,
+    /// The mode to return to once the prompt is closed
+    return_mode: Mode,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for Prompt<'_> {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::Prompt) => self.focused = true,
+            Action::ChangeMode(mode) => {
+                self.focused = false;
+                self.return_mode = mode;
+            }
+            Action::Prompt(request) => {
+                self.inputs = request
+                    .fields
+                    .iter()
+                    .map(|(_, initial)| {
+                        let mut input = TextArea::from([initial.clone()]);
+                        input.move_cursor(tui_textarea::CursorMove::End);
+                        input
+                    })
+                    .collect();
+                self.selected = 0;
+                self.request = Some(request);
+                return Ok(Some(Action::ChangeMode(Mode::Prompt)));
+            }
+            Action::NavDown if self.is_open() && !self.inputs.is_empty() => {
+                self.selected = (self.selected + 1) % self.inputs.len();
+            }
+            Action::NavUp if self.is_open() && !self.inputs.is_empty() => {
+                self.selected = (self.selected + self.inputs.len() - 1) % self.inputs.len();
+            }
+            Action::MakeSelection if self.is_open() => {
+                if let Some(request) = self.request.take()
+                    && let Some(tx) = &self.command_tx
+                {
+                    let values = self.inputs.iter().map(|i| i.lines().join("")).collect();
+                    tx.send(Action::PromptSubmitted(request.purpose, values))?;
+                }
+                self.inputs.clear();
+                return Ok(Some(Action::ChangeMode(self.return_mode)));
+            }
+            Action::Clear if self.is_open() => {
+                self.request = None;
+                self.inputs.clear();
+                return Ok(Some(Action::ChangeMode(self.return_mode)));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+        if !self.is_open() || self.config.keybindings.is_bound(Mode::Prompt, &key) {
+            return Ok(None);
+        }
+        if let Some(input) = self.inputs.get_mut(self.selected) {
+            input.input_without_shortcuts(key);
+        }
+        Ok(None)
+    }
+
+    fn draw(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+    ) -> color_eyre::Result<()> {
+        let Some(request) = &self.request else {
+            return Ok(());
+        };
+
+        // Each field takes a bordered line, plus the outer border and a line of help text.
+        let height = request.fields.len() as u16 * 3 + 3;
+        let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+
+        let block = Block::bordered()
+            .title(request.title.as_str())
+            .title_bottom("enter: submit  esc: cancel  tab: next field")
+            .style(Color::Cyan)
+            .border_type(BorderType::Thick);
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let rows = Layout::vertical(request.fields.iter().map(|_| Constraint::Length(3)))
+            .split(inner.inner(ratatui::layout::Margin::new(1, 0)));
+        for (idx, ((label, _), input)) in request
+            .fields
+            .iter()
+            .zip(self.inputs.iter_mut())
+            .enumerate()
+        {
+            let selected = idx == self.selected;
+            input.set_cursor_style(if selected {
+                Style::default().reversed()
+            } else {
+                Style::default()
+            });
+            input.set_cursor_line_style(Style::default());
+            input.set_block(
+                Block::bordered()
+                    .title(label.clone())
+                    .style(Style::new().fg(if selected {
+                        Color::Cyan
+                    } else {
+                        Color::DarkGray
+                    })),
+            );
+            if let Some(row) = rows.get(idx) {
+                frame.render_widget(&*input, *row);
+            }
+        }
+        if request.fields.is_empty() {
+            frame.render_widget(Paragraph::new("Are you sure?").centered(), inner);
+        }
+        Ok(())
+    }
+}
+
+impl Prompt<'_> {
+    fn is_open(&self) -> bool {
+        self.focused && self.request.is_some()
+    }
+}
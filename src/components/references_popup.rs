@@ -0,0 +1,149 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Modifier, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, List, ListState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::Component,
+    config::Config,
+    database::{
+        system_query::{Reference, SystemQuery},
+        value::Value,
+    },
+};
+
+/// Popup listing the rows of other tables that reference the selected result row.
+#[derive(Default)]
+pub struct ReferencesPopup {
+    /// Each reference with its number of rows
+    items: Vec<(Reference, String)>,
+    list_state: ListState,
+    /// The mode to return to once the popup is closed
+    return_mode: Mode,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for ReferencesPopup {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::ExploreReferences) => self.focused = true,
+            Action::ChangeMode(mode) => {
+                self.focused = false;
+                self.return_mode = mode;
+                self.items.clear();
+            }
+            // protect against excess navigation
+            Action::NavDown
+                if self.focused
+                    && self
+                        .list_state
+                        .selected()
+                        .is_some_and(|selected| selected + 1 < self.items.len()) =>
+            {
+                self.list_state.select_next();
+            }
+            Action::NavUp if self.focused => self.list_state.select_previous(),
+            Action::MakeSelection if self.focused => {
+                if let Some(index) = self.list_state.selected()
+                    && let Some((reference, _)) = self.items.get(index)
+                    && let Some(tx) = &self.command_tx
+                {
+                    tx.send(Action::ExecuteQuery(SystemQuery::query_for(
+                        QueryTag::FollowReference(reference.clone()),
+                    )?))?;
+                }
+                return Ok(Some(Action::ChangeMode(self.return_mode)));
+            }
+            Action::Clear if self.focused => {
+                return Ok(Some(Action::ChangeMode(self.return_mode)));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        if let AppEvent::QueryResult(result, QueryTag::ReferenceCounts(references)) = event {
+            // Rows hold the index of each reference and its count
+            let count = |idx: usize| {
+                result
+                    .rows
+                    .iter()
+                    .find(|row| row.first().and_then(Value::text) == Some(idx.to_string()))
+                    .and_then(|row| row.get(1).and_then(Value::text))
+                    .unwrap_or_default()
+            };
+            self.items = references
+                .into_iter()
+                .enumerate()
+                .map(|(idx, reference)| (reference, count(idx)))
+                .collect();
+            self.list_state = ListState::default().with_selected(Some(0));
+            return Ok(Some(Action::ChangeMode(Mode::ExploreReferences)));
+        }
+        Ok(None)
+    }
+
+    fn draw(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+    ) -> color_eyre::Result<()> {
+        if !self.focused || self.items.is_empty() {
+            return Ok(());
+        }
+
+        let vertical =
+            Layout::vertical([Constraint::Length(self.items.len() as u16 + 2)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+
+        let block = Block::bordered()
+            .title("Referenced by")
+            .title_bottom("enter: show rows  esc: close")
+            .style(Color::Cyan)
+            .border_type(BorderType::Thick);
+        let list = List::new(self.items.iter().map(|(reference, count)| {
+            let columns: Vec<&str> = reference
+                .columns
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            Line::from(vec![
+                Span::from(format!(
+                    "{}.{}",
+                    reference.table.schema, reference.table.name
+                ))
+                .white(),
+                Span::from(format!(" ({})", columns.join(", "))).dark_gray(),
+                Span::from(format!("  {count} rows")),
+            ])
+        }))
+        .highlight_style(Modifier::REVERSED)
+        .highlight_symbol("▹ ")
+        .block(block);
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.list_state.clone());
+        Ok(())
+    }
+}
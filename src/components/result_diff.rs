@@ -0,0 +1,337 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Block, BorderType, Cell, Row, Table, TableState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::{Component, results_table::columns},
+    config::Config,
+    database::{connection::QueryResult, value::Value},
+};
+
+mod diff;
+
+use diff::{DiffRow, RowStatus};
+
+/// Widest a column of the comparison is shown
+const MAX_COLUMN_WIDTH: u16 = 30;
+
+/// Which of the compared results a side of the comparison shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    /// The pinned results
+    Before,
+    /// The results run after pinning
+    After,
+}
+
+/// One side of the comparison of pinned results with the results run after them. Both sides are
+/// separate components that follow the same actions, which keeps their rows aligned, and only the
+/// compared side answers with actions.
+pub struct ResultDiff {
+    side: DiffSide,
+    pinned: Option<QueryResult>,
+    compared: Option<QueryResult>,
+    /// Columns in both results, which rows can be matched by
+    keys: Vec<String>,
+    /// Index of the key column rows are matched by
+    key: usize,
+    rows: Vec<DiffRow>,
+    /// Width of each column of this side's results, fitted when they are compared
+    widths: Vec<u16>,
+    state: TableState,
+    /// Column paging offset
+    column_offset: usize,
+    focused: bool,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl ResultDiff {
+    pub fn new(side: DiffSide) -> Self {
+        Self {
+            side,
+            pinned: None,
+            compared: None,
+            keys: vec![],
+            key: 0,
+            rows: vec![],
+            widths: vec![],
+            state: TableState::default(),
+            column_offset: 0,
+            focused: false,
+            command_tx: None,
+            config: Config::default(),
+        }
+    }
+}
+
+impl Component for ResultDiff {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::ChangeMode(Mode::CompareResults) => self.focused = true,
+            Action::ChangeMode(_) => self.focused = false,
+            // Pinning again unpins, so that comparing can be stopped from the results
+            Action::ResultPinned(_) if self.pinned.is_some() => {
+                self.unpin();
+                return Ok(self.answer(Action::Info(String::from("Unpinned the results"))));
+            }
+            Action::ResultPinned(result) => {
+                let rows = result.rows.len();
+                self.pinned = Some(result);
+                self.compared = None;
+                self.rows.clear();
+                self.widths.clear();
+                return Ok(self.answer(Action::Info(format!(
+                    "Pinned {rows} rows, the next results are compared to them"
+                ))));
+            }
+            _ => {}
+        }
+        if !self.focused {
+            return Ok(None);
+        }
+
+        match action {
+            // protect against excess navigation
+            Action::NavDown
+                if self
+                    .state
+                    .selected()
+                    .is_some_and(|selected| selected + 1 < self.rows.len()) =>
+            {
+                self.state.select_next();
+            }
+            Action::NavUp => self.state.select_previous(),
+            Action::PageLeft => self.column_offset = self.column_offset.saturating_sub(1),
+            Action::PageRight if self.column_offset + 1 < self.column_count() => {
+                self.column_offset += 1;
+            }
+            Action::NextTab if !self.keys.is_empty() => {
+                self.key = (self.key + 1) % self.keys.len();
+                self.compare();
+            }
+            Action::Clear => {
+                self.unpin();
+                return Ok(self.answer(Action::ChangeMode(Mode::ExploreResults)));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        if let AppEvent::QueryResult(result, QueryTag::User | QueryTag::InitialTable(_)) = event
+            && let Some(pinned) = &self.pinned
+        {
+            let keys = diff::key_columns(pinned, &result);
+            if keys.is_empty() {
+                // Or every later result would fail to compare, with no way to unpin them
+                self.unpin();
+                return Ok(self.answer(Action::Error(String::from(
+                    "The results have no column in common with the pinned ones to match rows by, \
+                     so they were unpinned",
+                ))));
+            }
+            // Keep matching by the same column, else by an id
+            let previous = self.keys.get(self.key);
+            self.key = keys
+                .iter()
+                .position(|k| Some(k) == previous)
+                .or_else(|| keys.iter().position(|k| k == "id"))
+                .unwrap_or_default();
+            self.keys = keys;
+            self.compared = Some(result);
+            self.column_offset = 0;
+            self.compare();
+            return Ok(self.answer(Action::ChangeMode(Mode::CompareResults)));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let result = self.result();
+        let column_space = 1;
+        let shown: Vec<usize> = (self.column_offset..result.columns.len()).collect();
+        let widths = &self.widths[self.column_offset.min(self.widths.len())..];
+
+        let header = Row::new(
+            std::iter::once(Cell::from("")).chain(
+                shown
+                    .iter()
+                    .map(|c| Cell::from(result.columns[*c].as_str())),
+            ),
+        )
+        .style(Style::new().bold().bg(Color::Rgb(18, 18, 18)))
+        .bottom_margin(1);
+
+        let rows = self.rows.iter().map(|diff_row| {
+            let row_idx = match self.side {
+                DiffSide::Before => diff_row.before,
+                DiffSide::After => diff_row.after,
+            };
+            let Some(row) = row_idx.and_then(|idx| result.rows.get(idx)) else {
+                // The row is only on the other side
+                return Row::new([Cell::from("")]).style(Style::new().bg(Color::Rgb(24, 24, 24)));
+            };
+            let (marker, color) = match diff_row.status {
+                RowStatus::Unchanged => (" ", Color::Reset),
+                RowStatus::Changed => ("~", Color::Reset),
+                RowStatus::Added => ("+", Color::Rgb(0, 48, 0)),
+                RowStatus::Removed => ("-", Color::Rgb(64, 0, 0)),
+            };
+            let cells = shown.iter().zip(widths).map(|(column, width)| {
+                let cell = match row.get(*column).and_then(Value::text) {
+                    Some(text) => {
+                        Cell::from(columns::truncate(&text, *width as usize).into_owned())
+                    }
+                    None => Cell::from("NULL").dark_gray(),
+                };
+                if diff_row.changed.contains(&result.columns[*column]) {
+                    cell.style(Style::new().bold().black().on_yellow())
+                } else {
+                    cell
+                }
+            });
+            Row::new(std::iter::once(Cell::from(marker).yellow()).chain(cells))
+                .style(Style::new().bg(color))
+        });
+
+        let table = Table::default()
+            .rows(rows)
+            .widths(
+                std::iter::once(Constraint::Length(1))
+                    .chain(widths.iter().copied().map(Constraint::Length)),
+            )
+            .header(header)
+            .block(self.make_block())
+            .column_spacing(column_space)
+            .style(Color::Blue)
+            .row_highlight_style(Style::new().on_dark_gray().bold());
+
+        // The rows borrow the results, so the state is put back after rendering
+        let mut state = self.state.clone();
+        frame.render_stateful_widget(table, area, &mut state);
+        self.state = state;
+        Ok(())
+    }
+}
+
+impl ResultDiff {
+    /// The action answered to the app, only by the compared side so that it is sent once
+    fn answer(&self, action: Action) -> Option<Action> {
+        (self.side == DiffSide::After).then_some(action)
+    }
+
+    /// The results shown on this side
+    fn result(&self) -> &QueryResult {
+        static EMPTY: QueryResult = QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            origins: Vec::new(),
+        };
+        let result = match self.side {
+            DiffSide::Before => &self.pinned,
+            DiffSide::After => &self.compared,
+        };
+        result.as_ref().unwrap_or(&EMPTY)
+    }
+
+    /// Columns of the widest results, which both sides page through alike
+    fn column_count(&self) -> usize {
+        [&self.pinned, &self.compared]
+            .iter()
+            .filter_map(|result| result.as_ref())
+            .map(|result| result.columns.len())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn unpin(&mut self) {
+        self.pinned = None;
+        self.compared = None;
+        self.rows.clear();
+        self.widths.clear();
+    }
+
+    /// Matches the rows of the pinned and compared results by the key column
+    fn compare(&mut self) {
+        if let (Some(pinned), Some(compared), Some(key)) =
+            (&self.pinned, &self.compared, self.keys.get(self.key))
+        {
+            self.rows = diff::diff(pinned, compared, key);
+        }
+        let result = self.result();
+        self.widths = (0..result.columns.len())
+            .map(|column| {
+                let cells: Vec<String> = result
+                    .rows
+                    .iter()
+                    .take(columns::FIT_SAMPLE_ROWS)
+                    .map(|row| {
+                        row.get(column)
+                            .and_then(Value::text)
+                            .unwrap_or(String::from("NULL"))
+                    })
+                    .collect();
+                columns::fit_width(
+                    &result.columns[column],
+                    cells.iter().map(String::as_str),
+                    MAX_COLUMN_WIDTH,
+                )
+            })
+            .collect();
+        self.state = TableState::default().with_selected(Some(0));
+    }
+
+    fn make_block<'a>(&self) -> Block<'a> {
+        let count = |status: RowStatus| self.rows.iter().filter(|r| r.status == status).count();
+        let (title, title_bottom) = match self.side {
+            DiffSide::Before => (
+                format!("pinned ({} rows)", self.result().rows.len()),
+                format!(
+                    "{} changed  {} added  {} removed",
+                    count(RowStatus::Changed),
+                    count(RowStatus::Added),
+                    count(RowStatus::Removed)
+                ),
+            ),
+            DiffSide::After => (
+                format!("compared ({} rows)", self.result().rows.len()),
+                format!(
+                    "key: {}  tab: next key  esc: unpin",
+                    self.keys.get(self.key).map(String::as_str).unwrap_or("-")
+                ),
+            ),
+        };
+        Block::bordered()
+            .title(title)
+            .title_bottom(title_bottom)
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(if self.focused {
+                Color::Cyan
+            } else {
+                Color::Blue
+            }))
+            .border_type(if self.focused {
+                BorderType::Thick
+            } else {
+                BorderType::Plain
+            })
+    }
+}
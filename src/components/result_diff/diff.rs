@@ -0,0 +1,200 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::database::{connection::QueryResult, value::Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowStatus {
+    Unchanged,
+    Changed,
+    Added,
+    Removed,
+}
+
+/// A line of the comparison: a row of the pinned results, of the compared ones, or of both when
+/// their keys match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRow {
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+    pub status: RowStatus,
+    /// Names of the columns whose values differ between the two rows
+    pub changed: Vec<String>,
+}
+
+/// Columns in both results, which rows can be matched by
+pub fn key_columns(before: &QueryResult, after: &QueryResult) -> Vec<String> {
+    let mut keys: Vec<String> = vec![];
+    for column in before.columns.iter() {
+        if after.columns.contains(column) && !keys.contains(column) {
+            keys.push(column.clone());
+        }
+    }
+    keys
+}
+
+/// Matches the rows of both results by the value of the key column. The rows are in the order of
+/// the pinned results, removed ones included, followed by the added rows. Rows sharing a key are
+/// matched in order, and rows with a null key are never matched.
+pub fn diff(before: &QueryResult, after: &QueryResult, key: &str) -> Vec<DiffRow> {
+    let key_column = |result: &QueryResult| result.columns.iter().position(|c| c == key);
+    let (Some(before_key), Some(after_key)) = (key_column(before), key_column(after)) else {
+        return vec![];
+    };
+
+    // Compared rows by key, in order, waiting to be matched
+    let mut unmatched: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (idx, row) in after.rows.iter().enumerate() {
+        if let Some(key) = row.get(after_key).and_then(Value::text) {
+            unmatched.entry(key).or_default().push_back(idx);
+        }
+    }
+
+    let mut matched = vec![false; after.rows.len()];
+    let mut rows = vec![];
+    for (idx, row) in before.rows.iter().enumerate() {
+        let after_idx = row
+            .get(before_key)
+            .and_then(Value::text)
+            .and_then(|key| unmatched.get_mut(&key)?.pop_front());
+        rows.push(match after_idx {
+            Some(after_idx) => {
+                matched[after_idx] = true;
+                let changed = changed_columns(before, idx, after, after_idx);
+                DiffRow {
+                    before: Some(idx),
+                    after: Some(after_idx),
+                    status: if changed.is_empty() {
+                        RowStatus::Unchanged
+                    } else {
+                        RowStatus::Changed
+                    },
+                    changed,
+                }
+            }
+            None => DiffRow {
+                before: Some(idx),
+                after: None,
+                status: RowStatus::Removed,
+                changed: vec![],
+            },
+        });
+    }
+    rows.extend(
+        (0..after.rows.len())
+            .filter(|idx| !matched[*idx])
+            .map(|idx| DiffRow {
+                before: None,
+                after: Some(idx),
+                status: RowStatus::Added,
+                changed: vec![],
+            }),
+    );
+    rows
+}
+
+/// Names of the columns in both results whose values differ between the two rows
+fn changed_columns(
+    before: &QueryResult,
+    before_row: usize,
+    after: &QueryResult,
+    after_row: usize,
+) -> Vec<String> {
+    before
+        .columns
+        .iter()
+        .enumerate()
+        .filter_map(|(before_column, name)| {
+            let after_column = after.columns.iter().position(|c| c == name)?;
+            let old = before.rows[before_row]
+                .get(before_column)
+                .and_then(Value::text);
+            let new = after.rows[after_row]
+                .get(after_column)
+                .and_then(Value::text);
+            (old != new).then(|| name.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(columns: &[&str], rows: &[&[Option<&str>]]) -> QueryResult {
+        QueryResult {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|v| v.map_or(Value::Null, |v| Value::Text(v.to_string())))
+                        .collect()
+                })
+                .collect(),
+            origins: vec![None; columns.len()],
+        }
+    }
+
+    #[test]
+    fn test_key_columns() {
+        let before = result(&["id", "name", "id"], &[]);
+        let after = result(&["name", "id", "price"], &[]);
+        assert_eq!(key_columns(&before, &after), vec!["id", "name"]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = result(
+            &["id", "name", "price"],
+            &[
+                &[Some("1"), Some("apple"), Some("1.00")],
+                &[Some("2"), Some("pear"), Some("2.00")],
+                &[Some("3"), Some("plum"), None],
+                &[None, Some("fig"), None],
+            ],
+        );
+        let after = result(
+            &["name", "id"],
+            &[
+                &[Some("plum"), Some("3")],
+                &[Some("apple"), Some("1")],
+                &[Some("kiwi"), Some("4")],
+                &[Some("pears"), Some("2")],
+            ],
+        );
+        let rows = diff(&before, &after, "id");
+        let summary: Vec<(Option<usize>, Option<usize>, RowStatus)> =
+            rows.iter().map(|r| (r.before, r.after, r.status)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some(0), Some(1), RowStatus::Unchanged),
+                (Some(1), Some(3), RowStatus::Changed),
+                (Some(2), Some(0), RowStatus::Unchanged),
+                (Some(3), None, RowStatus::Removed),
+                (None, Some(2), RowStatus::Added),
+            ]
+        );
+        // Only columns in both results are compared
+        assert_eq!(rows[1].changed, vec!["name"]);
+        assert!(diff(&before, &after, "price").is_empty());
+    }
+
+    #[test]
+    fn test_diff_duplicate_keys() {
+        let before = result(
+            &["k", "v"],
+            &[&[Some("a"), Some("1")], &[Some("a"), Some("2")]],
+        );
+        let after = result(
+            &["k", "v"],
+            &[&[Some("a"), Some("1")], &[Some("a"), Some("3")]],
+        );
+        let rows = diff(&before, &after, "k");
+        assert_eq!(rows[0].status, RowStatus::Unchanged);
+        assert_eq!(
+            (rows[1].after, rows[1].status),
+            (Some(1), RowStatus::Changed)
+        );
+    }
+}
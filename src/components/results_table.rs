@@ -1,8 +1,12 @@
+use std::{collections::HashMap, fmt::Write};
+
 use arboard::Clipboard;
+use chrono::{FixedOffset, Local};
 use ratatui::{
     layout::{Alignment, Constraint},
     prelude::Rect,
     style::{Color, Style, Stylize},
+    text::Text,
     widgets::{Block, BorderType, Cell, Row, Table, TableState},
 };
 use unicode_width::UnicodeWidthStr;
@@ -11,25 +15,156 @@ use crate::{
     action::Action,
     app::Mode,
     app_event::{AppEvent, QueryTag},
-    components::Component,
+    components::{
+        Component,
+        column_picker::ColumnChoice,
+        prompt::{PromptPurpose, PromptRequest},
+        results_table::keys::{ForeignKey, TableKey, parse_foreign_keys},
+    },
     config::Config,
+    database::{
+        connection::{ColumnOrigin, QueryResult},
+        system_query::{self, Reference, SystemQuery},
+        value::Value,
+    },
+    external_editor::{EditPurpose, EditRequest},
 };
 use tokio::sync::mpsc::UnboundedSender;
 
+pub mod columns;
+pub mod keys;
+
+/// Widest a column is fitted to its contents, unless configured otherwise
+const DEFAULT_MAX_COLUMN_WIDTH: u16 = 40;
+
+/// Time zone timestamps with time zones are shown in
+#[derive(Debug, Clone, Copy)]
+enum TimeZone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl TimeZone {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "utc" => Some(TimeZone::Utc),
+            "local" => Some(TimeZone::Local),
+            _ => name.parse().ok().map(TimeZone::Fixed),
+        }
+    }
+}
+
+/// How timestamps are shown, from the `results` config. Without any, they are shown as Postgres
+/// prints them.
+#[derive(Debug, Default)]
+struct TimestampFormat {
+    time_zone: Option<TimeZone>,
+    format: Option<String>,
+}
+
+impl TimestampFormat {
+    fn format(&self, value: &Value) -> Option<String> {
+        if self.time_zone.is_none() && self.format.is_none() {
+            return None;
+        }
+        let Value::Timestamp { zoned, .. } = value else {
+            return None;
+        };
+        let instant = value.timestamp()?;
+        let default = if *zoned {
+            "%Y-%m-%d %H:%M:%S%.f%:z"
+        } else {
+            "%Y-%m-%d %H:%M:%S%.f"
+        };
+        let format = self.format.as_deref().unwrap_or(default);
+
+        // Invalid formats, or offsets of timestamps without time zones, fail to write
+        let mut text = String::new();
+        let written = if !*zoned {
+            write!(text, "{}", instant.naive_utc().format(format))
+        } else {
+            match self.time_zone.unwrap_or(TimeZone::Utc) {
+                TimeZone::Utc => write!(text, "{}", instant.format(format)),
+                TimeZone::Local => write!(text, "{}", instant.with_timezone(&Local).format(format)),
+                TimeZone::Fixed(offset) => {
+                    write!(text, "{}", instant.with_timezone(&offset).format(format))
+                }
+            }
+        };
+        written.ok().map(|_| text)
+    }
+}
+
+/// A value edited in the external editor, waiting for the primary key of its table and then for
+/// the update of its row.
+#[derive(Debug)]
+struct PendingEdit {
+    row: usize,
+    column: usize,
+    value: String,
+}
+
+/// A result left by following a reference, to return to.
+#[derive(Debug)]
+struct PreviousResult {
+    result: QueryResult,
+    foreign_keys: Option<Vec<ForeignKey>>,
+    referencing_keys: Option<Vec<ForeignKey>>,
+    state: TableState,
+    column_offset: usize,
+    widths: Vec<u16>,
+    frozen: usize,
+    column_choices: Vec<ColumnChoice>,
+    table: Option<system_query::Table>,
+}
+
 #[derive(Debug)]
 pub struct ResultsTable {
     /// Column names
     columns: Vec<String>,
     /// Result rows
-    rows: Vec<Vec<Option<String>>>,
+    rows: Vec<Vec<Value>>,
     /// Widths to render for each column
     widths: Vec<u16>,
+    /// Widths fitting the contents of each column, to reset resized columns to
+    fitted_widths: Vec<u16>,
+    /// Columns in the order they are shown in, with the hidden ones
+    column_choices: Vec<ColumnChoice>,
+    /// The result columns shown, in order. Frozen columns, the paging offset and the column of
+    /// expanded records count positions in it.
+    layout: Vec<usize>,
+    /// Number of leading columns kept in view while paging columns
+    frozen: usize,
+    /// Whether each row is shown as a record of column and value lines, like psql's `\x`
+    expanded: bool,
+    /// The column selected in the expanded record
+    expanded_field: usize,
     /// Table state determining selections, etc.
     state: TableState,
     /// Whether this table is in focus
     focused: bool,
-    /// Column paging offset (scrolling columns left to right)
+    /// Column paging offset (scrolling columns left to right), the first column shown after the
+    /// frozen ones
     column_offset: usize,
+    /// The table column each result column was read from
+    origins: Vec<Option<ColumnOrigin>>,
+    /// Foreign keys of the tables in the results, fetched when first followed
+    foreign_keys: Option<Vec<ForeignKey>>,
+    /// Whether to follow the selected reference once the foreign keys are fetched
+    follow_pending: bool,
+    /// Foreign keys referencing the tables in the results, fetched when first shown
+    referencing_keys: Option<Vec<ForeignKey>>,
+    /// Whether to show what references the selected row once the referencing keys are fetched
+    references_pending: bool,
+    /// Results to go back to, from following references
+    back_stack: Vec<PreviousResult>,
+    /// The table whose rows are shown, when opened from the table list
+    table: Option<system_query::Table>,
+    /// Column choices by name of the tables opened from the table list, kept for the next time
+    table_columns: HashMap<system_query::Table, Vec<(String, bool)>>,
+    pending_edit: Option<PendingEdit>,
+    timestamp_format: TimestampFormat,
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
 }
@@ -40,9 +175,25 @@ impl Default for ResultsTable {
             columns: Default::default(),
             rows: Default::default(),
             widths: Default::default(),
+            fitted_widths: Default::default(),
+            column_choices: Default::default(),
+            layout: Default::default(),
+            frozen: 0,
+            expanded: false,
+            expanded_field: 0,
             state: Default::default(),
             focused: false,
             column_offset: 0,
+            origins: Default::default(),
+            foreign_keys: None,
+            follow_pending: false,
+            referencing_keys: None,
+            references_pending: false,
+            back_stack: Default::default(),
+            table: None,
+            table_columns: Default::default(),
+            pending_edit: None,
+            timestamp_format: Default::default(),
             command_tx: Default::default(),
             config: Default::default(),
         };
@@ -53,59 +204,25 @@ impl Default for ResultsTable {
 
 impl Component for ResultsTable {
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
-        match action {
-            Action::NavDown if self.focused => self.state.select_next(),
-            Action::NavUp if self.focused => self.state.select_previous(),
-            Action::NavLeft if self.focused => self.state.select_previous_column(),
-            Action::NavRight if self.focused => self.state.select_next_column(),
-            Action::ChangeMode(Mode::ExploreResults) => self.focused = true,
-            Action::ChangeMode(_) => self.focused = false,
-            Action::Clear if self.focused => {
-                if let Some(selection) = self.state.selected_cell() {
-                    // Clear the cell selection, but retain the row selection
-                    self.state.select_cell(None);
-                    self.state.select(Some(selection.0));
-                } else if self.state.selected().is_some() {
-                    self.state.select(None);
-                }
-            }
-            Action::Yank => {
-                if let Ok(clipboard) = Clipboard::new() {
-                    let mut clip = clipboard;
-                    if let Some((idx, col)) = self.state.selected_cell()
-                        && let Some(row) = self.rows.get(idx)
-                        && let Some(val) = row.get(col)
-                    {
-                        clip.set_text(val.clone().unwrap_or(String::from("NULL")))? // copy cell value
-                    } else if let Some(idx) = self.state.selected()
-                        && let Some(row) = self.rows.get(idx)
-                    {
-                        let row_str: String = row
-                            .iter()
-                            .map(|v| v.clone().unwrap_or(String::from("NULL")))
-                            .collect::<Vec<String>>()
-                            .join(" ");
-                        clip.set_text(row_str)?
-                    }
-                }
-            }
-            Action::MakeSelection if self.focused => {
-                if let Some(selection) = self.cell_selection() {
-                    return Ok(Some(Action::SelectCell(selection)));
-                }
-                if let Some(row_selection) = self.row_selection() {
-                    return Ok(Some(Action::SelectRow(self.columns.clone(), row_selection)));
-                }
-            }
-            Action::PageLeft if self.focused && self.column_offset > 0 => {
-                self.column_offset -= 1;
+        if self.focused && self.expanded {
+            match action {
+                Action::NavDown => self.next_field(),
+                Action::NavUp => self.previous_field(),
+                Action::NavRight => self.select_record(self.record() + 1),
+                Action::NavLeft => self.select_record(self.record().saturating_sub(1)),
+                Action::Clear | Action::ToggleExpanded => self.toggle_expanded(),
+                // Columns are not shown side by side
+                Action::PageLeft
+                | Action::PageRight
+                | Action::GrowColumn
+                | Action::ShrinkColumn
+                | Action::FitColumn
+                | Action::FreezeColumns => {}
+                _ => return self.update_table(action),
             }
-            Action::PageRight if self.focused && self.column_offset < self.columns.len() - 1 => {
-                self.column_offset += 1;
-            }
-            _ => {}
+            return Ok(None);
         }
-        Ok(None)
+        self.update_table(action)
     }
 
     fn handle_app_events(
@@ -113,9 +230,85 @@ impl Component for ResultsTable {
         event: crate::app_event::AppEvent,
     ) -> color_eyre::Result<Option<Action>> {
         match event {
-            AppEvent::QueryResult(result, QueryTag::User)
-            | AppEvent::QueryResult(result, QueryTag::InitialTable(_)) => {
-                self.set_data(result.columns, result.rows);
+            AppEvent::QueryResult(result, QueryTag::User) => {
+                self.back_stack.clear();
+                self.set_result(result);
+            }
+            AppEvent::QueryResult(result, QueryTag::InitialTable(table)) => {
+                self.back_stack.clear();
+                self.set_result(result);
+                if let Some(choices) = self.table_columns.get(&table) {
+                    self.set_columns(columns::remembered_columns(&self.columns, choices));
+                }
+                self.table = Some(table);
+            }
+            AppEvent::QueryResult(result, QueryTag::FollowReference(_)) => {
+                let previous = PreviousResult {
+                    result: QueryResult {
+                        columns: std::mem::take(&mut self.columns),
+                        rows: std::mem::take(&mut self.rows),
+                        origins: std::mem::take(&mut self.origins),
+                    },
+                    foreign_keys: self.foreign_keys.take(),
+                    referencing_keys: self.referencing_keys.take(),
+                    state: self.state.clone(),
+                    column_offset: self.column_offset,
+                    widths: self.widths.clone(),
+                    frozen: self.frozen,
+                    column_choices: self.column_choices.clone(),
+                    table: self.table.take(),
+                };
+                self.back_stack.push(previous);
+                self.set_result(result);
+            }
+            // Ignore foreign keys fetched for previous results
+            AppEvent::QueryResult(result, QueryTag::ForeignKeys(relation_ids))
+                if relation_ids == self.relation_ids() =>
+            {
+                self.foreign_keys = Some(parse_foreign_keys(result));
+                if self.follow_pending {
+                    self.follow_pending = false;
+                    return self.follow_reference();
+                }
+            }
+            AppEvent::QueryResult(result, QueryTag::ReferencingForeignKeys(relation_ids))
+                if relation_ids == self.relation_ids() =>
+            {
+                self.referencing_keys = Some(parse_foreign_keys(result));
+                if self.references_pending {
+                    self.references_pending = false;
+                    return self.show_references();
+                }
+            }
+            AppEvent::QueryResult(result, QueryTag::TableKey(relation_id)) => {
+                return Ok(self.update_prompt(result, relation_id));
+            }
+            AppEvent::QueryResult(result, QueryTag::UpdateCell(update)) => {
+                let Some(edit) = self.pending_edit.take() else {
+                    return Ok(None);
+                };
+                // The update returns the new value, as stored
+                let Some(value) = result
+                    .rows
+                    .into_iter()
+                    .next()
+                    .and_then(|r| r.into_iter().next())
+                else {
+                    return Ok(Some(Action::Error(String::from(
+                        "No row was updated, it may have been deleted",
+                    ))));
+                };
+                if let Some(cell) = self
+                    .rows
+                    .get_mut(edit.row)
+                    .and_then(|r| r.get_mut(edit.column))
+                {
+                    *cell = value;
+                }
+                return Ok(Some(Action::Info(format!(
+                    "Updated {} of {}",
+                    update.column.0, update.row.table.name
+                ))));
             }
             _ => {}
         }
@@ -128,33 +321,71 @@ impl Component for ResultsTable {
     }
 
     fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        let results = &config.results;
+        let time_zone = results.time_zone.as_deref().map(|name| {
+            TimeZone::parse(name).unwrap_or_else(|| {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Action::Error(format!(
+                        "Unknown time zone {name}, showing timestamps in UTC"
+                    )));
+                }
+                TimeZone::Utc
+            })
+        });
+        self.timestamp_format = TimestampFormat {
+            time_zone,
+            format: results.timestamp_format.clone(),
+        };
         self.config = config;
         Ok(())
     }
 
     fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
-        // Clip the number of displayed columns based on calculated widths and visible space
-        let mut visible_cols = 0;
-        let mut visible_width = 0;
+        if self.expanded {
+            return self.draw_expanded(frame, area);
+        }
+
+        // Clip the displayed columns based on their widths and the visible space
         let column_space = 2;
-        for width in self.widths[self.column_offset..].iter() {
-            // stop counting when columns will overflow, with additional buffer
-            if visible_width >= area.width {
+        let mut visible = columns::visible_columns(
+            &self
+                .layout
+                .iter()
+                .map(|column| self.widths[*column])
+                .collect::<Vec<u16>>(),
+            self.frozen,
+            self.column_offset,
+            column_space,
+            area.width,
+        );
+        // The last column is cut off to the space left for it, within the borders and the
+        // highlight symbol
+        let mut remaining = area.width.saturating_sub(3);
+        let mut widths: Vec<u16> = vec![];
+        for column in visible.iter() {
+            if remaining == 0 {
                 break;
             }
-            visible_width += width + column_space * 2;
-            visible_cols += 1;
+            let width = self.widths[self.layout[*column]].min(remaining);
+            remaining = remaining.saturating_sub(width + column_space);
+            widths.push(width);
         }
-        let col_range = self.column_offset..(visible_cols + self.column_offset);
+        visible.truncate(widths.len());
 
-        let column_names = self.columns[col_range.clone()]
-            .iter()
-            .map(|c| Cell::from(c.as_str()));
         let header_bg_color = if self.columns.is_empty() {
             Color::Reset
         } else {
             Color::Rgb(18, 18, 18)
         };
+        let column_names = visible.iter().zip(&widths).map(|(column, width)| {
+            let name = columns::truncate(&self.columns[self.layout[*column]], *width as usize);
+            // Frozen columns stand out from the ones paged through
+            if *column < self.frozen {
+                Cell::from(name.into_owned()).fg(Color::Cyan)
+            } else {
+                Cell::from(name.into_owned())
+            }
+        });
         let header = Row::new(column_names)
             .style(Style::new().bold().bg(header_bg_color))
             .bottom_margin(1);
@@ -166,23 +397,16 @@ impl Component for ResultsTable {
             } else {
                 Color::Reset
             };
-            Row::new(r[col_range.clone()].iter().map(|val| {
-                if let Some(row_val) = val {
-                    if row_val.is_empty() {
-                        Cell::from("EMPTY").fg(Color::Rgb(44, 44, 44))
-                    } else {
-                        Cell::from(row_val.as_str())
-                    }
-                } else {
-                    Cell::from("NULL").fg(Color::Rgb(38, 38, 38))
-                }
-            }))
+            Row::new(
+                visible
+                    .iter()
+                    .zip(&widths)
+                    .map(|(column, width)| self.value_cell(&r[self.layout[*column]], *width, true)),
+            )
             .style(Style::default().bg(color))
         });
 
-        let widths = self.widths[col_range.clone()]
-            .iter()
-            .map(|len| Constraint::Length(*len));
+        let widths = widths.iter().copied().map(Constraint::Length);
 
         let table = Table::default()
             .rows(table_rows)
@@ -201,26 +425,470 @@ impl Component for ResultsTable {
 }
 
 impl ResultsTable {
-    fn set_data(&mut self, new_cols: Vec<String>, new_rows: Vec<Vec<Option<String>>>) {
+    /// Handles actions shared by both views and those of the table view
+    fn update_table(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::NavDown if self.focused => self.state.select_next(),
+            Action::NavUp if self.focused => self.state.select_previous(),
+            Action::NavLeft if self.focused => self.state.select_previous_column(),
+            Action::NavRight if self.focused => self.state.select_next_column(),
+            Action::ChangeMode(Mode::ExploreResults) => self.focused = true,
+            Action::ChangeMode(_) => self.focused = false,
+            Action::Clear if self.focused => {
+                if let Some(selection) = self.state.selected_cell() {
+                    // Clear the cell selection, but retain the row selection
+                    self.state.select_cell(None);
+                    self.state.select(Some(selection.0));
+                } else if self.state.selected().is_some() {
+                    self.state.select(None);
+                }
+            }
+            Action::Yank if self.focused => {
+                if let Ok(clipboard) = Clipboard::new() {
+                    let mut clip = clipboard;
+                    if let Some(val) = self.selected_value() {
+                        clip.set_text(val.text().unwrap_or(String::from("NULL")))? // copy cell value
+                    } else if let Some(idx) = self.state.selected()
+                        && let Some(row) = self.rows.get(idx)
+                    {
+                        let row_str: String = row
+                            .iter()
+                            .map(|v| v.text().unwrap_or(String::from("NULL")))
+                            .collect::<Vec<String>>()
+                            .join(" ");
+                        clip.set_text(row_str)?
+                    }
+                }
+            }
+            Action::MakeSelection if self.focused => {
+                if let Some(Value::Json(json)) = self.selected_value() {
+                    return Ok(Some(Action::ViewJson(json.clone())));
+                }
+                if let Some(value) = self.selected_value() {
+                    return Ok(Some(Action::SelectCell(value.clone())));
+                }
+                if let Some(row_selection) = self.row_selection() {
+                    return Ok(Some(Action::SelectRow(self.columns.clone(), row_selection)));
+                }
+            }
+            Action::FollowReference if self.focused => return self.follow_reference(),
+            Action::EditCell if self.focused => {
+                let Some((row, column)) = self.selected_cell() else {
+                    return Ok(Some(Action::Info(String::from("Select a cell to edit it"))));
+                };
+                let value = self.rows.get(row).and_then(|r| r.get(column));
+                let extension = match value {
+                    Some(Value::Json(_)) => "json",
+                    _ => "txt",
+                };
+                return Ok(Some(Action::OpenInEditor(EditRequest {
+                    purpose: EditPurpose::Cell { row, column },
+                    contents: value.and_then(Value::text).unwrap_or_default(),
+                    extension: String::from(extension),
+                })));
+            }
+            Action::EditorClosed(EditPurpose::Cell { row, column }, value) => {
+                return self.edited(row, column, value);
+            }
+            Action::PromptSubmitted(PromptPurpose::UpdateCell(update), _) => {
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::UpdateCell(Box::new(update)),
+                )?)));
+            }
+            Action::ShowReferences if self.focused => return self.show_references(),
+            Action::Back if self.focused => {
+                if let Some(previous) = self.back_stack.pop() {
+                    self.set_result(previous.result);
+                    self.set_columns(previous.column_choices);
+                    self.foreign_keys = previous.foreign_keys;
+                    self.referencing_keys = previous.referencing_keys;
+                    self.state = previous.state;
+                    self.column_offset = previous.column_offset;
+                    self.widths = previous.widths;
+                    self.frozen = previous.frozen;
+                    self.table = previous.table;
+                }
+            }
+            Action::DisplayResult(result) => {
+                self.back_stack.clear();
+                self.set_result(result);
+            }
+            Action::PageLeft if self.focused && self.column_offset > self.frozen => {
+                self.column_offset -= 1;
+            }
+            Action::PageRight if self.focused && self.column_offset + 1 < self.layout.len() => {
+                self.column_offset += 1;
+            }
+            Action::GrowColumn | Action::ShrinkColumn | Action::FitColumn if self.focused => {
+                let Some((_, column)) = self.selected_cell() else {
+                    return Ok(Some(Action::Info(String::from(
+                        "Select a cell to resize its column",
+                    ))));
+                };
+                if let Some(width) = self.widths.get_mut(column) {
+                    *width = match action {
+                        Action::GrowColumn => width.saturating_add(columns::WIDTH_STEP),
+                        Action::ShrinkColumn => width
+                            .saturating_sub(columns::WIDTH_STEP)
+                            .max(columns::MIN_WIDTH),
+                        _ => self.fitted_widths[column],
+                    };
+                }
+            }
+            Action::FreezeColumns if self.focused => self.toggle_frozen(),
+            Action::ToggleExpanded if self.focused => self.toggle_expanded(),
+            Action::PinResult if self.focused => {
+                return Ok(Some(Action::ResultPinned(QueryResult {
+                    columns: self.columns.clone(),
+                    rows: self.rows.clone(),
+                    origins: self.origins.clone(),
+                })));
+            }
+            Action::PickColumns if self.focused && !self.columns.is_empty() => {
+                return Ok(Some(Action::ShowColumnPicker(self.column_choices.clone())));
+            }
+            Action::ColumnsPicked(choices) => {
+                if let Some(table) = &self.table {
+                    self.table_columns.insert(
+                        table.clone(),
+                        choices
+                            .iter()
+                            .map(|choice| (choice.name.clone(), choice.shown))
+                            .collect(),
+                    );
+                }
+                self.set_columns(choices);
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn set_result(&mut self, result: QueryResult) {
+        self.origins = result.origins;
+        self.foreign_keys = None;
+        self.follow_pending = false;
+        self.referencing_keys = None;
+        self.references_pending = false;
+        self.pending_edit = None;
+        self.set_data(result.columns, result.rows);
+    }
+
+    /// OIDs of the tables the results were read from
+    fn relation_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .origins
+            .iter()
+            .flatten()
+            .map(|origin| origin.relation_id)
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Queries the rows referenced by the foreign key of the selected cell, fetching the foreign
+    /// keys of the tables in the results first if needed.
+    fn follow_reference(&mut self) -> color_eyre::Result<Option<Action>> {
+        let Some((row_idx, col_idx)) = self.selected_cell() else {
+            return Ok(Some(Action::Info(String::from(
+                "Select a cell to follow its reference",
+            ))));
+        };
+        let column = self.columns.get(col_idx).cloned().unwrap_or_default();
+        let Some(origin) = self.origins.get(col_idx).copied().flatten() else {
+            return Ok(Some(Action::Info(format!(
+                "{column} is not read from a table"
+            ))));
+        };
+        let Some(foreign_keys) = &self.foreign_keys else {
+            self.follow_pending = true;
+            return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                QueryTag::ForeignKeys(self.relation_ids()),
+            )?)));
+        };
+        let Some(foreign_key) = foreign_keys.iter().find(|fk| fk.contains(origin)) else {
+            return Ok(Some(Action::Info(format!("{column} is not a foreign key"))));
+        };
+
+        match self.reference(foreign_key, row_idx) {
+            Ok(reference) => Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                QueryTag::FollowReference(reference),
+            )?))),
+            Err(message) => Ok(Some(Action::Info(message))),
+        }
+    }
+
+    /// Fetches the primary key of the table a value edited in the external editor was read from,
+    /// to update its row.
+    fn edited(
+        &mut self,
+        row: usize,
+        column: usize,
+        value: String,
+    ) -> color_eyre::Result<Option<Action>> {
+        let original = self.rows.get(row).and_then(|r| r.get(column));
+        if original.and_then(Value::text).unwrap_or_default() == value {
+            return Ok(Some(Action::Info(String::from(
+                "The value was not changed",
+            ))));
+        }
+        let name = self.columns.get(column).cloned().unwrap_or_default();
+        let Some(origin) = self.origins.get(column).copied().flatten() else {
+            return Ok(Some(Action::Info(format!(
+                "{name} is not read from a table, so it can't be updated"
+            ))));
+        };
+        self.pending_edit = Some(PendingEdit { row, column, value });
+        Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+            QueryTag::TableKey(origin.relation_id),
+        )?)))
+    }
+
+    /// Asks to update the row of the edited value, when the results hold its primary key.
+    fn update_prompt(&mut self, result: QueryResult, relation_id: u32) -> Option<Action> {
+        let edit = self.pending_edit.as_ref()?;
+        let origin = self.origins.get(edit.column).copied().flatten()?;
+        if origin.relation_id != relation_id {
+            return None;
+        }
+        let table_key = TableKey::parse(result, origin.attnum)?;
+        let row = self
+            .rows
+            .get(edit.row)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let update = match table_key.update(relation_id, &self.origins, row, edit.value.clone()) {
+            Ok(update) => update,
+            Err(message) => {
+                self.pending_edit = None;
+                return Some(Action::Info(message));
+            }
+        };
+
+        let conditions: Vec<String> = update
+            .row
+            .columns
+            .iter()
+            .zip(update.row.values.iter())
+            .map(|((name, _), value)| format!("{name} = {value}"))
+            .collect();
+        let title = format!(
+            "Update {} of {} where {}?",
+            update.column.0,
+            update.row.table.name,
+            conditions.join(", ")
+        );
+        Some(Action::Prompt(PromptRequest {
+            title,
+            purpose: PromptPurpose::UpdateCell(update),
+            fields: vec![],
+        }))
+    }
+
+    /// Counts the rows of other tables referencing the selected row, fetching the foreign keys
+    /// referencing the tables in the results first if needed.
+    fn show_references(&mut self) -> color_eyre::Result<Option<Action>> {
+        let Some(row_idx) = self.state.selected() else {
+            return Ok(Some(Action::Info(String::from(
+                "Select a row to show what references it",
+            ))));
+        };
+        let Some(referencing_keys) = &self.referencing_keys else {
+            if self.relation_ids().is_empty() {
+                return Ok(Some(Action::Info(String::from(
+                    "The results are not read from a table",
+                ))));
+            }
+            self.references_pending = true;
+            return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                QueryTag::ReferencingForeignKeys(self.relation_ids()),
+            )?)));
+        };
+        // Keys whose columns are missing from the results or NULL can't reference the row.
+        let references: Vec<Reference> = referencing_keys
+            .iter()
+            .filter_map(|key| self.reference(key, row_idx).ok())
+            .collect();
+        if references.is_empty() {
+            return Ok(Some(Action::Info(String::from(
+                "No foreign keys reference this row",
+            ))));
+        }
+        Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+            QueryTag::ReferenceCounts(references),
+        )?)))
+    }
+
+    /// The rows of a key's other table matching the key's values in a row of the results.
+    fn reference(&self, key: &ForeignKey, row_idx: usize) -> Result<Reference, String> {
+        let row = self
+            .rows
+            .get(row_idx)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        key.reference(&self.origins, row)
+    }
+
+    fn set_data(&mut self, new_cols: Vec<String>, new_rows: Vec<Vec<Value>>) {
         self.columns = new_cols;
         self.rows = new_rows;
         self.state = TableState::default();
-        self.widths = self.calc_widths();
+        self.fitted_widths = self.calc_widths();
+        self.widths = self.fitted_widths.clone();
         self.column_offset = 0;
+        self.frozen = 0;
+        self.expanded_field = 0;
+        self.table = None;
+        self.set_columns(
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(index, name)| ColumnChoice {
+                    index,
+                    name: name.clone(),
+                    shown: true,
+                })
+                .collect(),
+        );
+    }
+
+    /// Shows the chosen columns in their order. Columns are paged back to the first ones not
+    /// frozen.
+    fn set_columns(&mut self, choices: Vec<ColumnChoice>) {
+        self.layout = choices
+            .iter()
+            .filter(|choice| choice.shown && choice.index < self.columns.len())
+            .map(|choice| choice.index)
+            .collect();
+        self.column_choices = choices;
+        self.frozen = self.frozen.min(self.layout.len());
+        self.column_offset = self.frozen;
+        self.expanded_field = self.expanded_field.min(self.layout.len().saturating_sub(1));
+        if self.state.selected_cell().is_some() {
+            self.state.select_column(Some(0));
+        }
+    }
+
+    /// The position in the layout of a column shown, counting the frozen columns and the paging
+    /// offset
+    fn position(&self, shown: usize) -> usize {
+        if shown < self.frozen {
+            shown
+        } else {
+            shown - self.frozen + self.column_offset
+        }
+    }
+
+    /// Freezes the columns up to the selected one, or unfreezes them when they already are. Without
+    /// a selected cell, the first column is toggled.
+    fn toggle_frozen(&mut self) {
+        let selected = self
+            .selected_position()
+            .map(|(_, column)| column)
+            .filter(|column| *column < self.layout.len());
+        let frozen = match selected {
+            Some(column) if self.frozen != column + 1 => column + 1,
+            None if self.frozen == 0 && !self.layout.is_empty() => 1,
+            _ => 0,
+        };
+        if frozen > 0 {
+            self.column_offset = self.column_offset.max(frozen);
+        } else if let Some(column) = selected {
+            // Page back to the unfrozen column when it would leave the view
+            self.column_offset = self.column_offset.min(column);
+        } else {
+            self.column_offset = 0;
+        }
+        self.frozen = frozen;
+        // Keep the same cell selected where it is shown now
+        if let Some(column) = selected {
+            self.state.select_column(self.shown_column(column));
+        }
+    }
+
+    /// Where the column at a position of the layout is shown among the frozen and paged columns,
+    /// none when it is paged out of view
+    fn shown_column(&self, column: usize) -> Option<usize> {
+        if column < self.frozen {
+            Some(column)
+        } else if column >= self.column_offset {
+            Some(self.frozen + column - self.column_offset)
+        } else {
+            None
+        }
+    }
+
+    /// Switches between the table and expanded records, keeping the selected cell
+    fn toggle_expanded(&mut self) {
+        if self.expanded {
+            self.expanded = false;
+            self.state
+                .select_column(self.shown_column(self.expanded_field));
+        } else {
+            self.expanded_field = self.selected_position().map(|(_, c)| c).unwrap_or_default();
+            self.expanded = true;
+            self.select_record(self.record());
+        }
+    }
+
+    /// The selected record in the expanded view, the first one when no row is selected
+    fn record(&self) -> usize {
+        self.state.selected().unwrap_or_default()
+    }
+
+    fn select_record(&mut self, record: usize) {
+        if !self.rows.is_empty() {
+            self.state.select(Some(record.min(self.rows.len() - 1)));
+        }
+    }
+
+    /// Selects the next column of the expanded record, going on to the first column of the next
+    /// record after the last one
+    fn next_field(&mut self) {
+        if self.expanded_field + 1 < self.layout.len() {
+            self.expanded_field += 1;
+        } else if self.record() + 1 < self.rows.len() {
+            self.expanded_field = 0;
+            self.select_record(self.record() + 1);
+        }
+    }
+
+    fn previous_field(&mut self) {
+        if self.expanded_field > 0 {
+            self.expanded_field -= 1;
+        } else if self.record() > 0 {
+            self.expanded_field = self.layout.len().saturating_sub(1);
+            self.select_record(self.record() - 1);
+        }
     }
 
     fn make_block<'a>(&self) -> Block<'a> {
-        let left_arrow = if self.column_offset > 0 { "<<" } else { "" };
-        let right_arrow = if !self.columns.is_empty() && self.column_offset < self.columns.len() - 1
-        {
+        let left_arrow = if self.column_offset > self.frozen {
+            "<<"
+        } else {
+            ""
+        };
+        let right_arrow = if self.column_offset + 1 < self.layout.len() {
             ">>"
         } else {
             ""
         };
 
+        let title = match self.back_stack.len() {
+            0 => String::from("results [alt+3]"),
+            depth => format!("results [alt+3] ({depth} back)"),
+        };
+
         Block::bordered()
-            .title("results [alt+3]")
-            .title_bottom(format!("{}   {}", left_arrow, right_arrow))
+            .title(title)
+            .title_bottom(match self.frozen {
+                _ if self.expanded => {
+                    format!("record {}/{}", self.record() + 1, self.rows.len())
+                }
+                0 => format!("{}   {}", left_arrow, right_arrow),
+                frozen => format!("{}  {} frozen  {}", left_arrow, frozen, right_arrow),
+            })
             .style(Style::new().fg(if self.focused {
                 Color::Cyan
             } else {
@@ -234,28 +902,237 @@ impl ResultsTable {
             })
     }
 
-    fn cell_selection(&self) -> Option<String> {
-        if let Some((row_idx, col_idx)) = self.state.selected_cell()
-            && let Some(row) = self.rows.get(row_idx)
-            && let Some(cell) = row.get(col_idx)
-        {
-            return cell.clone();
+    /// Row and layout position of the selected cell. In expanded records, a column is always
+    /// selected.
+    fn selected_position(&self) -> Option<(usize, usize)> {
+        if self.expanded {
+            return (!self.rows.is_empty()).then(|| (self.record(), self.expanded_field));
         }
-        None
+        let (row_idx, col_idx) = self.state.selected_cell()?;
+        Some((row_idx, self.position(col_idx)))
+    }
+
+    /// Row and result column of the selected cell
+    fn selected_cell(&self) -> Option<(usize, usize)> {
+        let (row_idx, position) = self.selected_position()?;
+        Some((row_idx, *self.layout.get(position)?))
+    }
+
+    fn selected_value(&self) -> Option<&Value> {
+        let (row_idx, col_idx) = self.selected_cell()?;
+        self.rows.get(row_idx)?.get(col_idx)
     }
 
     fn row_selection(&self) -> Option<Vec<Option<String>>> {
         if let Some(index) = self.state.selected() {
-            return self.rows.get(index).cloned();
+            return self
+                .rows
+                .get(index)
+                .map(|row| row.iter().map(Value::text).collect());
         }
         None
     }
 
-    fn calc_widths(&self) -> Vec<u16> {
-        let max = 120u16;
-        self.columns
+    /// Draws each row as a record of column and value lines, from the selected record on. The
+    /// record scrolls once its selected column is below the view.
+    fn draw_expanded(&self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let column_space = 2;
+        let record_header = |idx: usize| format!("-[ RECORD {} ]", idx + 1);
+        let name_width = self
+            .layout
+            .iter()
+            .map(|column| self.columns[*column].width())
+            .chain(std::iter::once(record_header(self.rows.len()).width() + 1))
+            .max()
+            .unwrap_or_default()
+            .min(area.width as usize / 2) as u16;
+        // Within the borders and the highlight symbol
+        let value_width = area.width.saturating_sub(3 + name_width + column_space);
+        let height = area.height.saturating_sub(2) as usize;
+
+        let selected_line = self.expanded_field + 1;
+        let skip = (selected_line + 1).saturating_sub(height);
+        let lines = self
+            .rows
             .iter()
-            .map(|c| std::cmp::min(max, (c.width()) as u16))
+            .enumerate()
+            .skip(self.record())
+            .flat_map(|(idx, row)| {
+                let header = Row::new([
+                    Cell::from(format!(
+                        "{:-<width$}",
+                        record_header(idx),
+                        width = name_width as usize
+                    )),
+                    Cell::from("-".repeat(value_width as usize)),
+                ])
+                .fg(Color::DarkGray);
+                let fields = self.layout.iter().map(|column| {
+                    Row::new([
+                        Cell::from(
+                            columns::truncate(&self.columns[*column], name_width as usize)
+                                .into_owned(),
+                        )
+                        .bold(),
+                        self.value_cell(&row[*column], value_width, false),
+                    ])
+                });
+                std::iter::once(header).chain(fields)
+            })
+            .skip(skip)
+            .take(height);
+
+        let table = Table::default()
+            .rows(lines)
+            .widths([
+                Constraint::Length(name_width),
+                Constraint::Length(value_width),
+            ])
+            .block(self.make_block())
+            .column_spacing(column_space)
+            .style(Color::Blue)
+            .row_highlight_style(Style::new().on_dark_gray().bold())
+            .highlight_symbol("▷");
+
+        let mut state = TableState::default();
+        if !self.rows.is_empty() {
+            state.select(Some(selected_line - skip));
+        }
+        frame.render_stateful_widget(table, area, &mut state);
+        Ok(())
+    }
+
+    /// A value as shown in a cell of the given width, with nulls, empty text and booleans set
+    /// apart
+    fn value_cell(&self, value: &Value, width: u16, align_numbers: bool) -> Cell<'static> {
+        let text = self
+            .cell_text(value)
+            .map(|text| columns::truncate(&text, width as usize).into_owned());
+        match (value, text) {
+            (_, None) => Cell::from("NULL").fg(Color::Rgb(38, 38, 38)),
+            (_, Some(text)) if text.is_empty() => Cell::from("EMPTY").fg(Color::Rgb(44, 44, 44)),
+            (Value::Bool(true), Some(text)) => Cell::from(text).fg(Color::Green),
+            (Value::Bool(false), Some(text)) => Cell::from(text).fg(Color::Red),
+            (value, Some(text)) if align_numbers && value.is_number() => {
+                Cell::from(Text::from(text).alignment(Alignment::Right))
+            }
+            (_, Some(text)) => Cell::from(text),
+        }
+    }
+
+    /// The text shown for a value, none for null
+    fn cell_text(&self, value: &Value) -> Option<String> {
+        self.timestamp_format.format(value).or_else(|| value.text())
+    }
+
+    /// Widths fitting the header and the values of each column, as shown
+    fn calc_widths(&self) -> Vec<u16> {
+        let max = self
+            .config
+            .results
+            .max_column_width
+            .unwrap_or(DEFAULT_MAX_COLUMN_WIDTH);
+        (0..self.columns.len())
+            .map(|column| {
+                let cells: Vec<String> = self
+                    .rows
+                    .iter()
+                    .take(columns::FIT_SAMPLE_ROWS)
+                    .map(
+                        |row| match row.get(column).and_then(|v| self.cell_text(v)) {
+                            None => String::from("NULL"),
+                            Some(text) if text.is_empty() => String::from("EMPTY"),
+                            Some(text) => text,
+                        },
+                    )
+                    .collect();
+                columns::fit_width(&self.columns[column], cells.iter().map(String::as_str), max)
+            })
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+
+    /// Results with the key and a column of table 20, and an expression
+    fn table() -> ResultsTable {
+        let mut table = ResultsTable::default();
+        let origin = |attnum| {
+            Some(ColumnOrigin {
+                relation_id: 20,
+                attnum,
+            })
+        };
+        table.set_result(QueryResult {
+            columns: vec![
+                String::from("id"),
+                String::from("note"),
+                String::from("total"),
+            ],
+            rows: vec![vec![Value::Int(5), text("old"), Value::Int(3)]],
+            origins: vec![origin(1), origin(2), None],
+        });
+        table
+    }
+
+    #[test]
+    fn test_edited() {
+        let mut table = table();
+        assert_eq!(
+            table.edited(0, 1, String::from("old")).unwrap(),
+            Some(Action::Info(String::from("The value was not changed")))
+        );
+        assert_eq!(
+            table.edited(0, 2, String::from("4")).unwrap(),
+            Some(Action::Info(String::from(
+                "total is not read from a table, so it can't be updated"
+            )))
+        );
+        assert!(table.pending_edit.is_none());
+        assert_eq!(
+            table.edited(0, 1, String::from("new")).unwrap(),
+            Some(Action::ExecuteQuery(
+                SystemQuery::query_for(QueryTag::TableKey(20)).unwrap()
+            ))
+        );
+
+        let key = QueryResult {
+            columns: vec![],
+            rows: vec![
+                ["public", "notes", "1", "id", "integer"].map(text).to_vec(),
+                ["public", "notes", "2", "note", "text"].map(text).to_vec(),
+            ],
+            origins: vec![],
+        };
+        let mut with_key = key.clone();
+        with_key.rows[0].push(Value::Bool(true));
+        with_key.rows[1].push(Value::Bool(false));
+        // Keys of other tables are not for the edit
+        assert_eq!(table.update_prompt(with_key.clone(), 21), None);
+        let Some(Action::Prompt(request)) = table.update_prompt(with_key, 20) else {
+            panic!("expected a prompt");
+        };
+        assert_eq!(request.title, "Update note of notes where id = 5?");
+
+        // Without a primary key the edit is dropped
+        let mut without_key = key;
+        for row in without_key.rows.iter_mut() {
+            row.push(Value::Bool(false));
+        }
+        assert_eq!(
+            table.update_prompt(without_key, 20),
+            Some(Action::Info(String::from(
+                "notes has no primary key, so the row can't be updated"
+            )))
+        );
+        assert!(table.pending_edit.is_none());
+    }
+}
@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::components::column_picker::ColumnChoice;
+
+/// Narrowest a column can be shrunk to
+pub const MIN_WIDTH: u16 = 3;
+/// How much a column grows or shrinks at a time
+pub const WIDTH_STEP: u16 = 4;
+/// Rows looked at when fitting a column to its contents
+pub const FIT_SAMPLE_ROWS: usize = 500;
+
+/// Width fitting the header and the first line of each cell, up to the maximum. Cells are given as
+/// shown, with `NULL` and `EMPTY` for null and empty values.
+pub fn fit_width<'a>(header: &str, cells: impl Iterator<Item = &'a str>, max: u16) -> u16 {
+    let widest = cells
+        .take(FIT_SAMPLE_ROWS)
+        .map(|cell| cell.lines().next().unwrap_or_default().width())
+        .chain(std::iter::once(header.width()))
+        .max()
+        .unwrap_or_default();
+    (widest.min(max as usize) as u16).max(MIN_WIDTH)
+}
+
+/// The first line of the text, ending in an ellipsis when it does not fit the width or more lines
+/// follow.
+pub fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    let first_line = text.lines().next().unwrap_or_default();
+    if first_line.len() == text.len() && text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    for c in first_line.chars() {
+        let char_width = c.width().unwrap_or_default();
+        // Leave room for the ellipsis
+        if truncated_width + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += char_width;
+    }
+    truncated.push('…');
+    Cow::Owned(truncated)
+}
+
+/// The columns shown in the available width: the frozen ones, then the others from the offset.
+/// The last column shown may be cut off.
+pub fn visible_columns(
+    widths: &[u16],
+    frozen: usize,
+    offset: usize,
+    spacing: u16,
+    available: u16,
+) -> Vec<usize> {
+    let mut columns = vec![];
+    let mut used = 0u16;
+    for column in (0..frozen.min(widths.len())).chain(offset.max(frozen)..widths.len()) {
+        if used >= available {
+            break;
+        }
+        used = used.saturating_add(widths[column] + spacing);
+        columns.push(column);
+    }
+    columns
+}
+
+/// Choices for the columns of new results, following the choices remembered for their table.
+/// Columns that are new to the table are shown at the end.
+pub fn remembered_columns(columns: &[String], remembered: &[(String, bool)]) -> Vec<ColumnChoice> {
+    let mut choices: Vec<ColumnChoice> = vec![];
+    for (name, shown) in remembered {
+        // Columns may share a name, each is matched once
+        if let Some(index) = (0..columns.len())
+            .find(|idx| columns[*idx] == *name && !choices.iter().any(|c| c.index == *idx))
+        {
+            choices.push(ColumnChoice {
+                index,
+                name: name.clone(),
+                shown: *shown,
+            });
+        }
+    }
+    for (index, name) in columns.iter().enumerate() {
+        if !choices.iter().any(|choice| choice.index == index) {
+            choices.push(ColumnChoice {
+                index,
+                name: name.clone(),
+                shown: true,
+            });
+        }
+    }
+    // Keep a column shown when all remembered ones are gone
+    if !choices.iter().any(|choice| choice.shown)
+        && let Some(first) = choices.first_mut()
+    {
+        first.shown = true;
+    }
+    choices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_width() {
+        let cells = ["a", "a longer value", "two\nlines that are long"];
+        assert_eq!(fit_width("name", cells.into_iter(), 40), 14);
+        assert_eq!(fit_width("name", cells.into_iter(), 10), 10);
+        assert_eq!(fit_width("a", std::iter::empty(), 40), MIN_WIDTH);
+        assert_eq!(fit_width("a_long_header", ["x"].into_iter(), 40), 13);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate("a longer value", 6), "a lon…");
+        assert_eq!(truncate("two\nlines", 10), "two…");
+        // Wide characters take two columns
+        assert_eq!(truncate("日本語テキスト", 6), "日本…");
+    }
+
+    #[test]
+    fn test_visible_columns() {
+        let widths = [4, 10, 10, 10, 10];
+        assert_eq!(visible_columns(&widths, 0, 0, 2, 30), vec![0, 1, 2]);
+        assert_eq!(visible_columns(&widths, 0, 2, 2, 30), vec![2, 3, 4]);
+        // Frozen columns stay in front of the scrolled ones
+        assert_eq!(visible_columns(&widths, 1, 3, 2, 30), vec![0, 3, 4]);
+        assert_eq!(visible_columns(&widths, 2, 0, 2, 30), vec![0, 1, 2]);
+        assert!(visible_columns(&[], 0, 0, 2, 30).is_empty());
+    }
+
+    #[test]
+    fn test_remembered_columns() {
+        let columns: Vec<String> = ["id", "name", "id", "created_at"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let remembered = vec![
+            (String::from("name"), true),
+            (String::from("id"), true),
+            (String::from("created_at"), false),
+            (String::from("dropped"), true),
+        ];
+        let choices: Vec<(usize, bool)> = remembered_columns(&columns, &remembered)
+            .iter()
+            .map(|c| (c.index, c.shown))
+            .collect();
+        // The second id is new to the table
+        assert_eq!(choices, vec![(1, true), (0, true), (3, false), (2, true)]);
+        let hidden = vec![(String::from("name"), false)];
+        assert!(remembered_columns(&columns[1..2], &hidden)[0].shown);
+    }
+}
@@ -0,0 +1,395 @@
+use crate::database::{
+    connection::{ColumnOrigin, QueryResult},
+    system_query::{CellUpdate, Reference, Table},
+    value::Value,
+};
+
+/// A foreign key between a table in the results and another table, which is the referenced
+/// table for keys of the results' tables and the referencing table for keys to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    /// OID of the constraint, as tables can have keys of the same name
+    pub id: u32,
+    /// OID of the table in the results
+    pub relation_id: u32,
+    pub name: String,
+    /// The other table
+    pub table: Table,
+    /// Each column of the key in the results' table, with the name and type of the matching
+    /// column in the other table
+    pub columns: Vec<(i16, String, String)>,
+}
+
+impl ForeignKey {
+    /// Whether the column is part of the key
+    pub fn contains(&self, origin: ColumnOrigin) -> bool {
+        self.relation_id == origin.relation_id
+            && self
+                .columns
+                .iter()
+                .any(|(attnum, ..)| *attnum == origin.attnum)
+    }
+
+    /// The rows of the other table matching the key's values in a row of the results. Keys
+    /// spanning several columns need all of them in the results.
+    pub fn reference(
+        &self,
+        origins: &[Option<ColumnOrigin>],
+        row: &[Value],
+    ) -> Result<Reference, String> {
+        let mut values = vec![];
+        for (attnum, other_column, _) in self.columns.iter() {
+            let key_column = ColumnOrigin {
+                relation_id: self.relation_id,
+                attnum: *attnum,
+            };
+            let Some(idx) = origins.iter().position(|o| *o == Some(key_column)) else {
+                return Err(format!(
+                    "The results need every column of {} to follow it",
+                    self.name
+                ));
+            };
+            match row.get(idx).and_then(Value::text) {
+                Some(value) => values.push(value),
+                None => return Err(format!("The reference to {other_column} is NULL")),
+            }
+        }
+        Ok(Reference {
+            table: self.table.clone(),
+            columns: self
+                .columns
+                .iter()
+                .map(|(_, name, typ)| (name.clone(), typ.clone()))
+                .collect(),
+            values,
+        })
+    }
+}
+
+/// Groups the rows of the `ForeignKeys` and `ReferencingForeignKeys` queries, one per key column,
+/// into foreign keys.
+pub fn parse_foreign_keys(result: QueryResult) -> Vec<ForeignKey> {
+    let mut foreign_keys: Vec<ForeignKey> = vec![];
+    for row in result.rows {
+        let value = |idx: usize| row.get(idx).and_then(Value::text).unwrap_or_default();
+        let id = value(0).parse().unwrap_or_default();
+        let column = (value(3).parse().unwrap_or_default(), value(6), value(7));
+        match foreign_keys.last_mut() {
+            Some(fk) if fk.id == id => fk.columns.push(column),
+            _ => foreign_keys.push(ForeignKey {
+                id,
+                relation_id: value(1).parse().unwrap_or_default(),
+                name: value(2),
+                table: Table {
+                    schema: value(4),
+                    name: value(5),
+                },
+                columns: vec![column],
+            }),
+        }
+    }
+    foreign_keys
+}
+
+/// The primary key of a table with one of its columns, from the `TableKey` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableKey {
+    pub table: Table,
+    /// Name and type of the column
+    pub column: (String, String),
+    /// Number, name and type of each column of the primary key, empty when there is none
+    pub key: Vec<(i16, String, String)>,
+}
+
+impl TableKey {
+    /// Reads the rows of the `TableKey` query, one per column of the table, keeping the column of
+    /// the given number. `None` when the table no longer has the column.
+    pub fn parse(result: QueryResult, attnum: i16) -> Option<Self> {
+        let mut table = Table {
+            schema: String::new(),
+            name: String::new(),
+        };
+        let mut column = None;
+        let mut key = vec![];
+        for row in result.rows {
+            let value = |idx: usize| row.get(idx).and_then(Value::text).unwrap_or_default();
+            table = Table {
+                schema: value(0),
+                name: value(1),
+            };
+            let number: i16 = value(2).parse().unwrap_or_default();
+            if number == attnum {
+                column = Some((value(3), value(4)));
+            }
+            if value(5) == "true" {
+                key.push((number, value(3), value(4)));
+            }
+        }
+        Some(Self {
+            table,
+            column: column?,
+            key,
+        })
+    }
+
+    /// Sets the column to the value in the row of the table identified by the primary key's
+    /// values in a row of the results, which need every column of the key.
+    pub fn update(
+        self,
+        relation_id: u32,
+        origins: &[Option<ColumnOrigin>],
+        row: &[Value],
+        value: String,
+    ) -> Result<CellUpdate, String> {
+        if self.key.is_empty() {
+            return Err(format!(
+                "{} has no primary key, so the row can't be updated",
+                self.table.name
+            ));
+        }
+        let values: Option<Vec<String>> = self
+            .key
+            .iter()
+            .map(|(attnum, ..)| {
+                let key_column = ColumnOrigin {
+                    relation_id,
+                    attnum: *attnum,
+                };
+                let idx = origins.iter().position(|o| *o == Some(key_column))?;
+                row.get(idx)?.text()
+            })
+            .collect();
+        let Some(values) = values else {
+            return Err(format!(
+                "The results need the primary key of {} to update it",
+                self.table.name
+            ));
+        };
+        Ok(CellUpdate {
+            row: Reference {
+                table: self.table,
+                columns: self
+                    .key
+                    .into_iter()
+                    .map(|(_, name, typ)| (name, typ))
+                    .collect(),
+                values,
+            },
+            column: self.column,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+
+    fn origin(relation_id: u32, attnum: i16) -> Option<ColumnOrigin> {
+        Some(ColumnOrigin {
+            relation_id,
+            attnum,
+        })
+    }
+
+    /// A key of table 10 on its columns 2 and 3, referencing `public.orders`
+    fn composite_key() -> ForeignKey {
+        ForeignKey {
+            id: 100,
+            relation_id: 10,
+            name: String::from("items_order_fkey"),
+            table: Table {
+                schema: String::from("public"),
+                name: String::from("orders"),
+            },
+            columns: vec![
+                (2, String::from("region"), String::from("text")),
+                (3, String::from("id"), String::from("integer")),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_foreign_keys() {
+        let row =
+            |id: &str, relation: &str, name: &str, attnum: &str, table: &str, column: &str| {
+                [
+                    id, relation, name, attnum, "public", table, column, "integer",
+                ]
+                .map(text)
+                .to_vec()
+            };
+        let result = QueryResult {
+            columns: vec![],
+            rows: vec![
+                row("100", "10", "items_order_fkey", "2", "orders", "region"),
+                row("100", "10", "items_order_fkey", "3", "orders", "id"),
+                row("101", "10", "items_product_fkey", "4", "products", "id"),
+                // Keys of different tables can share a name
+                row("102", "11", "items_order_fkey", "2", "orders", "id"),
+            ],
+            origins: vec![],
+        };
+        let keys = parse_foreign_keys(result);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].id, 100);
+        assert_eq!(keys[0].relation_id, 10);
+        assert_eq!(keys[0].table.name, "orders");
+        assert_eq!(
+            keys[0].columns,
+            vec![
+                (2, String::from("region"), String::from("integer")),
+                (3, String::from("id"), String::from("integer")),
+            ]
+        );
+        assert_eq!(keys[1].name, "items_product_fkey");
+        assert_eq!(keys[1].columns.len(), 1);
+        assert_eq!((keys[2].id, keys[2].relation_id), (102, 11));
+        assert!(parse_foreign_keys(QueryResult::default()).is_empty());
+    }
+
+    #[test]
+    fn test_contains() {
+        let key = composite_key();
+        assert!(key.contains(origin(10, 2).unwrap()));
+        assert!(key.contains(origin(10, 3).unwrap()));
+        assert!(!key.contains(origin(10, 1).unwrap()));
+        assert!(!key.contains(origin(11, 2).unwrap()));
+    }
+
+    #[test]
+    fn test_reference() {
+        let key = composite_key();
+        // The key's columns can be in any order, between others
+        let origins = [origin(10, 3), None, origin(10, 2), origin(10, 1)];
+        let row = [Value::Int(7), text("x"), text("eu"), text("y")];
+        assert_eq!(
+            key.reference(&origins, &row),
+            Ok(Reference {
+                table: key.table.clone(),
+                columns: vec![
+                    (String::from("region"), String::from("text")),
+                    (String::from("id"), String::from("integer")),
+                ],
+                values: vec![String::from("eu"), String::from("7")],
+            })
+        );
+
+        // Part of a composite key is not enough
+        assert_eq!(
+            key.reference(&origins[1..], &row[1..]),
+            Err(String::from(
+                "The results need every column of items_order_fkey to follow it"
+            ))
+        );
+        // Columns of the same number in other tables don't count
+        assert!(
+            key.reference(&[origin(11, 2), origin(11, 3)], &row)
+                .is_err()
+        );
+
+        let row = [Value::Int(7), text("x"), Value::Null, text("y")];
+        assert_eq!(
+            key.reference(&origins, &row),
+            Err(String::from("The reference to region is NULL"))
+        );
+    }
+
+    /// The `TableKey` rows of `public.items`, keyed by its columns 1 and 2
+    fn items_key() -> QueryResult {
+        let row = |attnum: &str, name: &str, typ: &str, primary: bool| {
+            vec![
+                text("public"),
+                text("items"),
+                text(attnum),
+                text(name),
+                text(typ),
+                Value::Bool(primary),
+            ]
+        };
+        QueryResult {
+            columns: vec![],
+            rows: vec![
+                row("1", "order_id", "integer", true),
+                row("2", "line", "smallint", true),
+                row("3", "note", "text", false),
+            ],
+            origins: vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_table_key() {
+        let key = TableKey::parse(items_key(), 3).unwrap();
+        assert_eq!(key.table.name, "items");
+        assert_eq!(key.column, (String::from("note"), String::from("text")));
+        assert_eq!(
+            key.key,
+            vec![
+                (1, String::from("order_id"), String::from("integer")),
+                (2, String::from("line"), String::from("smallint")),
+            ]
+        );
+        // The column was dropped
+        assert_eq!(TableKey::parse(items_key(), 4), None);
+    }
+
+    #[test]
+    fn test_update() {
+        let key = TableKey::parse(items_key(), 3).unwrap();
+        let origins = [origin(20, 3), origin(20, 2), origin(20, 1)];
+        let row = [text("old"), Value::Int(2), Value::Int(5)];
+        let update = key
+            .clone()
+            .update(20, &origins, &row, String::from("new"))
+            .unwrap();
+        assert_eq!(
+            update.row.columns,
+            vec![
+                (String::from("order_id"), String::from("integer")),
+                (String::from("line"), String::from("smallint")),
+            ]
+        );
+        assert_eq!(
+            update.row.values,
+            vec![String::from("5"), String::from("2")]
+        );
+        assert_eq!(update.column.0, "note");
+        assert_eq!(update.value, "new");
+
+        // Every column of a composite key is needed, with a value
+        let missing = "The results need the primary key of items to update it";
+        assert_eq!(
+            key.clone()
+                .update(20, &origins[..2], &row[..2], String::new()),
+            Err(String::from(missing))
+        );
+        // The key columns must be read from the edited table
+        assert_eq!(
+            key.clone().update(21, &origins, &row, String::new()),
+            Err(String::from(missing))
+        );
+        let row = [text("old"), Value::Int(2), Value::Null];
+        assert_eq!(
+            key.update(20, &origins, &row, String::new()),
+            Err(String::from(missing))
+        );
+
+        let mut no_key = items_key();
+        for row in no_key.rows.iter_mut() {
+            row[5] = Value::Bool(false);
+        }
+        assert_eq!(
+            TableKey::parse(no_key, 3)
+                .unwrap()
+                .update(20, &origins, &row, String::new()),
+            Err(String::from(
+                "items has no primary key, so the row can't be updated"
+            ))
+        );
+    }
+}
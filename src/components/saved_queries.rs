@@ -0,0 +1,266 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use arboard::Clipboard;
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, List, ListState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    app_event::QueryTag,
+    components::{
+        Component,
+        prompt::{PromptPurpose, PromptRequest},
+        text_editor::is_valid_name,
+    },
+    config::Config,
+    database::system_query::Query,
+};
+
+/// A named query stored as a `.sql` file in the `queries` folder of the config directory.
+#[derive(Debug, Clone)]
+struct SavedQuery {
+    name: String,
+    path: PathBuf,
+    /// Queries in a subfolder named after a connection are only listed for that connection.
+    scoped: bool,
+}
+
+/// Picker for saved queries, listed alongside the tables.
+pub struct SavedQueries {
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+    list_state: ListState,
+    focused: bool,
+    /// Name of the open connection, used to find connection-scoped queries
+    connection: Option<String>,
+    items: Vec<SavedQuery>,
+}
+
+impl Default for SavedQueries {
+    fn default() -> Self {
+        Self {
+            command_tx: Default::default(),
+            config: Default::default(),
+            list_state: ListState::default().with_selected(Some(0)),
+            focused: false,
+            connection: None,
+            items: Default::default(),
+        }
+    }
+}
+
+impl Component for SavedQueries {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        self.reload();
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        // Does not require focus:
+        match action {
+            Action::ChangeMode(Mode::ExploreSavedQueries) => self.focused = true,
+            Action::ChangeMode(_) => self.focused = false,
+            Action::OpenDbConnection(ref name) => {
+                self.connection = Some(name.clone());
+                self.reload();
+            }
+            Action::PromptSubmitted(PromptPurpose::SaveSnippet(ref contents), ref values) => {
+                let name = values.first().map(|v| v.trim()).unwrap_or_default();
+                return Ok(Some(match self.save(name, contents, false) {
+                    Ok(()) => Action::Info(format!("Saved query '{name}'")),
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                        Action::Prompt(PromptRequest {
+                            purpose: PromptPurpose::ReplaceSnippet {
+                                name: name.to_string(),
+                                contents: contents.clone(),
+                            },
+                            title: format!("A query named '{name}' is saved already. Replace it?"),
+                            fields: vec![],
+                        })
+                    }
+                    Err(e) => Action::Error(format!("Failed to save query: {e}")),
+                }));
+            }
+            Action::PromptSubmitted(
+                PromptPurpose::ReplaceSnippet {
+                    ref name,
+                    ref contents,
+                },
+                _,
+            ) => {
+                return Ok(Some(match self.save(name, contents, true) {
+                    Ok(()) => Action::Info(format!("Saved query '{name}'")),
+                    Err(e) => Action::Error(format!("Failed to save query: {e}")),
+                }));
+            }
+            _ => {}
+        }
+
+        if !self.focused {
+            return Ok(None);
+        }
+
+        match action {
+            Action::NavDown => {
+                // protect against excess navigation
+                if let Some(selected) = self.list_state.selected()
+                    && !self.items.is_empty()
+                    && selected >= self.items.len() - 1
+                {
+                    return Ok(None);
+                }
+                self.list_state.select_next();
+            }
+            Action::NavUp => self.list_state.select_previous(),
+            Action::MakeSelection => {
+                if let Some(contents) = self.selection_contents()? {
+                    return Ok(Some(Action::InsertQuery(contents)));
+                }
+            }
+            Action::RunSelection => {
+                if let Some(contents) = self.selection_contents()? {
+                    return Ok(Some(Action::ExecuteQuery(Query {
+                        tag: QueryTag::User,
+                        query: contents,
+                        binds: None,
+                    })));
+                }
+            }
+            Action::Yank => {
+                if let Ok(clipboard) = Clipboard::new()
+                    && let Some(contents) = self.selection_contents()?
+                {
+                    let mut clip = clipboard;
+                    clip.set_text(contents)?
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(
+        &mut self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+    ) -> color_eyre::Result<()> {
+        let block = Block::bordered()
+            .title("saved queries [alt+5]")
+            .style(Style::new().fg(if self.focused {
+                Color::Cyan
+            } else {
+                Color::Blue
+            }))
+            .title_alignment(Alignment::Center)
+            .border_type(if self.focused {
+                BorderType::Thick
+            } else {
+                BorderType::Plain
+            });
+
+        let list = List::new(self.items.iter().map(|item| {
+            if item.scoped {
+                Line::from(item.name.as_str().cyan())
+            } else {
+                Line::from(vec![
+                    Span::from(item.name.as_str()).cyan(),
+                    Span::from(" (global)").dark_gray(),
+                ])
+            }
+        }))
+        .highlight_style(Modifier::REVERSED)
+        .highlight_symbol("▹ ")
+        .block(block);
+
+        frame.render_stateful_widget(list, area, &mut self.list_state.clone());
+        Ok(())
+    }
+}
+
+impl SavedQueries {
+    fn queries_dir(&self) -> PathBuf {
+        self.config.config.config_dir.join("queries")
+    }
+
+    /// Re-read the saved queries from disk. Queries scoped to the open connection are listed
+    /// before the global ones.
+    fn reload(&mut self) {
+        let mut items = vec![];
+        if let Some(connection) = &self.connection {
+            items.extend(Self::read_dir(self.queries_dir().join(connection), true));
+        }
+        items.extend(Self::read_dir(self.queries_dir(), false));
+        self.items = items;
+    }
+
+    fn read_dir(dir: PathBuf, scoped: bool) -> Vec<SavedQuery> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return vec![];
+        };
+        let mut items: Vec<SavedQuery> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "sql"))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                Some(SavedQuery { name, path, scoped })
+            })
+            .collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        items
+    }
+
+    /// Writes a new saved query, scoped to the open connection if there is one. A query of the
+    /// same name is only replaced when asked to, else saving fails as it already exists.
+    fn save(&mut self, name: &str, contents: &str, replace: bool) -> io::Result<()> {
+        if !is_valid_name(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid name '{name}'"),
+            ));
+        }
+        let dir = match &self.connection {
+            Some(connection) => self.queries_dir().join(connection),
+            None => self.queries_dir(),
+        };
+        fs::create_dir_all(&dir)?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .create_new(!replace)
+            .open(dir.join(format!("{name}.sql")))?;
+        file.write_all(contents.as_bytes())?;
+        self.reload();
+        let position = self
+            .items
+            .iter()
+            .position(|item| item.name == name && item.scoped == self.connection.is_some());
+        self.list_state.select(position.or(Some(0)));
+        Ok(())
+    }
+
+    fn selection_contents(&self) -> color_eyre::Result<Option<String>> {
+        if let Some(index) = self.list_state.selected()
+            && let Some(item) = self.items.get(index)
+        {
+            return Ok(Some(fs::read_to_string(&item.path)?));
+        }
+        Ok(None)
+    }
+}
@@ -17,6 +17,7 @@ use crate::{
     app_event::{AppEvent, QueryTag},
     components::Component,
     config::Config,
+    database::{system_query::SystemQuery, value::Value},
 };
 
 pub struct SchemaList<'a> {
@@ -97,6 +98,14 @@ impl<'a> Component for SchemaList<'a> {
                     }
                     return Ok(None);
                 }
+                Action::ShowDiagram => {
+                    if let Some(selection) = self.selection() {
+                        return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                            QueryTag::SchemaDiagram(selection),
+                        )?)));
+                    }
+                    return Ok(None);
+                }
                 Action::Yank => {
                     if let Ok(clipboard) = Clipboard::new()
                         && let Some(selection) = self.selection()
@@ -153,12 +162,7 @@ impl<'a> Component for SchemaList<'a> {
                 self.items = result
                     .rows
                     .iter()
-                    .map(|r| {
-                        r.first()
-                            .cloned()
-                            .unwrap_or(None)
-                            .unwrap_or(String::default())
-                    })
+                    .map(|r| r.first().and_then(Value::text).unwrap_or_default())
                     .collect::<HashSet<String>>()
                     .into_iter()
                     .collect();
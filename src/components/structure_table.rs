@@ -1,10 +1,13 @@
+use std::collections::HashMap;
+
 use arboard::Clipboard;
 use ratatui::{
-    layout::{Alignment, Constraint},
+    layout::{Alignment, Constraint, Layout},
     prelude::Rect,
     style::{Color, Style, Stylize},
-    widgets::{Block, BorderType, Cell, Row, Table, TableState},
+    widgets::{Block, BorderType, Cell, Row, Table, TableState, Tabs},
 };
+use strum::{Display, IntoEnumIterator};
 
 use crate::{
     action::Action,
@@ -12,9 +15,49 @@ use crate::{
     app_event::{AppEvent, QueryTag},
     components::Component,
     config::Config,
+    database::{
+        connection::QueryResult,
+        system_query::{self, SystemQuery},
+        value::Value,
+    },
 };
 use tokio::sync::mpsc::UnboundedSender;
 
+/// Sub-tabs of the structure pane, each backed by its own system query.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+enum StructureTab {
+    #[default]
+    Columns,
+    Indexes,
+    Constraints,
+    Triggers,
+    Partitions,
+}
+
+impl StructureTab {
+    fn tag(self, table: system_query::Table) -> QueryTag {
+        match self {
+            StructureTab::Columns => QueryTag::TableStructure(table),
+            StructureTab::Indexes => QueryTag::TableIndexes(table),
+            StructureTab::Constraints => QueryTag::TableConstraints(table),
+            StructureTab::Triggers => QueryTag::TableTriggers(table),
+            StructureTab::Partitions => QueryTag::TablePartitions(table),
+        }
+    }
+
+    fn from_tag(tag: QueryTag) -> Option<(Self, system_query::Table)> {
+        match tag {
+            QueryTag::TableStructure(table) => Some((StructureTab::Columns, table)),
+            QueryTag::TableIndexes(table) => Some((StructureTab::Indexes, table)),
+            QueryTag::TableConstraints(table) => Some((StructureTab::Constraints, table)),
+            QueryTag::TableTriggers(table) => Some((StructureTab::Triggers, table)),
+            QueryTag::TablePartitions(table) => Some((StructureTab::Partitions, table)),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct StructureTable {
     /// Column names
@@ -25,8 +68,12 @@ pub struct StructureTable {
     widths: Vec<Constraint>,
     /// Table state determining selections, etc.
     state: TableState,
-    /// Name of table being displayed, if any
-    table_name: Option<String>,
+    /// Table being displayed, if any
+    table: Option<system_query::Table>,
+    /// The sub-tab being displayed
+    tab: StructureTab,
+    /// Query results of each sub-tab for the displayed table
+    results: HashMap<StructureTab, QueryResult>,
     /// Whether this table is in focus
     focused: bool,
     command_tx: Option<UnboundedSender<Action>>,
@@ -38,9 +85,11 @@ impl Default for StructureTable {
         let mut def = Self {
             columns: Default::default(),
             rows: Default::default(),
-            widths: Default::default(), // TODO: calculate column widths
+            widths: Default::default(),
             state: Default::default(),
-            table_name: Default::default(),
+            table: Default::default(),
+            tab: Default::default(),
+            results: Default::default(),
             focused: false,
             command_tx: Default::default(),
             config: Default::default(),
@@ -57,6 +106,16 @@ impl Component for StructureTable {
             Action::NavUp if self.focused => self.state.select_previous(),
             Action::NavLeft if self.focused => self.state.select_previous_column(),
             Action::NavRight if self.focused => self.state.select_next_column(),
+            Action::NextTab if self.focused => {
+                let tabs: Vec<StructureTab> = StructureTab::iter().collect();
+                let idx = tabs.iter().position(|t| *t == self.tab).unwrap_or_default();
+                self.select_tab(tabs[(idx + 1) % tabs.len()]);
+            }
+            Action::PreviousTab if self.focused => {
+                let tabs: Vec<StructureTab> = StructureTab::iter().collect();
+                let idx = tabs.iter().position(|t| *t == self.tab).unwrap_or_default();
+                self.select_tab(tabs[(idx + tabs.len() - 1) % tabs.len()]);
+            }
             Action::ChangeMode(Mode::ExploreStructure) => self.focused = true,
             Action::ChangeMode(_) => self.focused = false,
             Action::Yank => {
@@ -88,13 +147,38 @@ impl Component for StructureTable {
         &mut self,
         event: crate::app_event::AppEvent,
     ) -> color_eyre::Result<Option<Action>> {
-        if let AppEvent::QueryResult(result, QueryTag::TableStructure(table)) = event {
-            self.table_name = Some(table.name);
-            self.set_data(result.columns, result.rows);
+        let AppEvent::QueryResult(result, tag) = event else {
+            return Ok(None);
+        };
+        let Some((tab, table)) = StructureTab::from_tag(tag) else {
+            return Ok(None);
+        };
+
+        if tab == StructureTab::Columns {
+            // A table was opened: the other tabs are fetched in the background.
+            if let Some(tx) = &self.command_tx {
+                for other in StructureTab::iter().filter(|t| *t != StructureTab::Columns) {
+                    tx.send(Action::ExecuteQuery(SystemQuery::query_for(
+                        other.tag(table.clone()),
+                    )?))?;
+                }
+            }
+            self.table = Some(table);
+            self.results.clear();
+            self.results.insert(tab, result);
+            self.select_tab(tab);
             // TODO: this also changes focused view which is jarring.
             // Need a way to swap the visible table to this one without refocusing.
             return Ok(Some(Action::ChangeMode(Mode::ExploreStructure)));
         }
+
+        // Ignore late results for a previously displayed table
+        if self.table.as_ref() == Some(&table) {
+            self.results.insert(tab, result);
+            if tab == self.tab {
+                self.select_tab(tab);
+            }
+        }
         Ok(None)
     }
 
@@ -134,9 +218,28 @@ impl Component for StructureTable {
             .style(Style::default().bg(color))
         });
 
+        let block = self.make_block();
+        let [tabs_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(block.inner(area));
+        frame.render_widget(block, area);
+
+        let tabs = Tabs::new(
+            StructureTab::iter().map(|tab| match self.results.get(&tab) {
+                Some(result) => format!("{tab} ({})", result.rows.len()),
+                None => tab.to_string(),
+            }),
+        )
+        .select(
+            StructureTab::iter()
+                .position(|t| t == self.tab)
+                .unwrap_or_default(),
+        )
+        .style(Color::DarkGray)
+        .highlight_style(Style::new().cyan().reversed());
+        frame.render_widget(tabs, tabs_area);
+
         let table = Table::new(table_rows, &self.widths)
             .header(header)
-            .block(self.make_block())
             .column_spacing(1)
             .style(Color::Blue)
             .row_highlight_style(Style::new().on_dark_gray().bold())
@@ -144,24 +247,52 @@ impl Component for StructureTable {
             .cell_highlight_style(Style::new().reversed().yellow())
             .highlight_symbol("▷ ");
 
-        frame.render_stateful_widget(table, area, &mut self.state);
+        frame.render_stateful_widget(table, table_area, &mut self.state);
         Ok(())
     }
 }
 
 impl StructureTable {
     fn set_data(&mut self, new_cols: Vec<String>, new_rows: Vec<Vec<Option<String>>>) {
+        // Share the width between columns by the length of their longest value, so that long
+        // definitions get most of it.
+        self.widths = new_cols
+            .iter()
+            .enumerate()
+            .map(|(idx, col)| {
+                let longest = new_rows
+                    .iter()
+                    .filter_map(|row| row.get(idx).cloned().flatten())
+                    .map(|val| val.chars().count())
+                    .chain([col.chars().count(), 4])
+                    .max()
+                    .unwrap_or_default();
+                Constraint::Fill(longest.min(80) as u16)
+            })
+            .collect();
         self.columns = new_cols;
         self.rows = new_rows;
         self.state = TableState::default();
     }
 
+    fn select_tab(&mut self, tab: StructureTab) {
+        self.tab = tab;
+        let result = self.results.get(&tab).cloned().unwrap_or_default();
+        let rows = result
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Value::text).collect())
+            .collect();
+        self.set_data(result.columns, rows);
+    }
+
     fn make_block<'a>(&self) -> Block<'a> {
         Block::bordered()
             .title(format!(
                 "{} [alt+4]",
-                self.table_name
-                    .clone()
+                self.table
+                    .as_ref()
+                    .map(|table| table.name.clone())
                     .unwrap_or("Select a table and press 's'".to_string())
             ))
             .style(Style::new().fg(if self.focused {
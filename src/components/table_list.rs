@@ -1,11 +1,14 @@
+use std::collections::HashSet;
+
 use arboard::Clipboard;
 use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Alignment, Constraint, Layout},
-    style::{Color, Modifier, Style},
-    text::Text,
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
     widgets::{Block, BorderType, List, ListState},
 };
+use strum::IntoEnumIterator;
 use tokio::sync::mpsc::UnboundedSender;
 use tui_textarea::TextArea;
 
@@ -13,18 +16,143 @@ use crate::{
     action::Action,
     app::Mode,
     app_event::{AppEvent, QueryTag},
-    components::Component,
+    components::{
+        Component,
+        prompt::{PromptPurpose, PromptRequest},
+    },
     config::Config,
-    database::system_query::{SystemQuery, Table},
+    database::{
+        system_query::{Function, SystemQuery, Table},
+        value::Value,
+    },
 };
 
+/// The kinds of database objects, in the order their groups are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumIter)]
+enum ObjectKind {
+    Table,
+    View,
+    MaterializedView,
+    Function,
+    Sequence,
+    Type,
+}
+
+impl ObjectKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(ObjectKind::Table),
+            "view" => Some(ObjectKind::View),
+            "materialized view" => Some(ObjectKind::MaterializedView),
+            "function" => Some(ObjectKind::Function),
+            "sequence" => Some(ObjectKind::Sequence),
+            "type" => Some(ObjectKind::Type),
+            _ => None,
+        }
+    }
+
+    fn group_name(&self) -> &'static str {
+        match self {
+            ObjectKind::Table => "Tables",
+            ObjectKind::View => "Views",
+            ObjectKind::MaterializedView => "Materialized Views",
+            ObjectKind::Function => "Functions",
+            ObjectKind::Sequence => "Sequences",
+            ObjectKind::Type => "Types",
+        }
+    }
+
+    /// Whether the object can be selected from like a table
+    fn is_relation(&self) -> bool {
+        matches!(
+            self,
+            ObjectKind::Table
+                | ObjectKind::View
+                | ObjectKind::MaterializedView
+                | ObjectKind::Sequence
+        )
+    }
+}
+
+/// Order of the objects within each group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SortOrder {
+    #[default]
+    Name,
+    /// Largest total size first
+    Size,
+    /// Largest estimated row count first
+    Rows,
+}
+
+impl SortOrder {
+    fn next(self) -> Self {
+        match self {
+            SortOrder::Name => SortOrder::Size,
+            SortOrder::Size => SortOrder::Rows,
+            SortOrder::Rows => SortOrder::Name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DbObject {
+    schema: String,
+    name: String,
+    kind: ObjectKind,
+    /// Input argument types of a function
+    arguments: String,
+    /// Total size in bytes of a table or materialized view, with indexes and TOAST
+    size: Option<i64>,
+    estimated_rows: Option<i64>,
+}
+
+impl DbObject {
+    fn display_name(&self) -> String {
+        match self.kind {
+            ObjectKind::Function => format!("{}({})", self.name, self.arguments),
+            _ => self.name.clone(),
+        }
+    }
+
+    fn table(&self) -> Table {
+        Table {
+            schema: self.schema.clone(),
+            name: self.name.clone(),
+        }
+    }
+
+    /// Query showing the object's definition
+    fn definition_tag(&self) -> QueryTag {
+        match self.kind {
+            ObjectKind::Function => QueryTag::FunctionDefinition(Function {
+                schema: self.schema.clone(),
+                name: self.name.clone(),
+                arguments: self.arguments.clone(),
+            }),
+            ObjectKind::Type => QueryTag::TypeDefinition(self.table()),
+            _ => QueryTag::TableDdl(self.table()),
+        }
+    }
+}
+
+/// A line of the object tree.
+enum TreeRow<'a> {
+    /// A group header with the number of objects in it
+    Group(ObjectKind, usize),
+    Object(&'a DbObject),
+}
+
+/// Tree of the objects in the selected schema, grouped by kind.
 pub struct TableList {
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
     list_state: ListState,
     focused: Option<FocusTarget>,
-    /// schema, table
-    items: Vec<(String, String)>,
+    items: Vec<DbObject>,
+    /// Groups whose objects are hidden
+    collapsed: HashSet<ObjectKind>,
+    sort: SortOrder,
     search: Option<String>,
     selected_schema: String,
 }
@@ -42,6 +170,8 @@ impl Default for TableList {
             list_state: ListState::default().with_selected(Some(0)),
             focused: None,
             items: Default::default(),
+            collapsed: Default::default(),
+            sort: Default::default(),
             search: None,
             selected_schema: "public".to_string(),
         }
@@ -69,6 +199,11 @@ impl Component for TableList {
                 self.list_state = ListState::default().with_selected(Some(0));
                 return Ok(Some(Action::ChangeMode(Mode::ExploreTables)));
             }
+            Action::PromptSubmitted(PromptPurpose::RefreshMaterializedView(view), _) => {
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::RefreshMaterializedView(view),
+                )?)));
+            }
             _ => {}
         }
 
@@ -87,8 +222,8 @@ impl Component for TableList {
                 Action::NavDown => {
                     // protect against excess navigation
                     if let Some(selected) = self.list_state.selected()
-                        && self.display_items().count() > 0
-                        && selected >= self.display_items().count() - 1
+                        && !self.display_rows().is_empty()
+                        && selected >= self.display_rows().len() - 1
                     {
                         return Ok(None);
                     }
@@ -96,33 +231,75 @@ impl Component for TableList {
                 }
                 Action::NavUp => self.list_state.select_previous(),
                 Action::MakeSelection => {
-                    if let Some(selection) = self.selection() {
+                    let Some(index) = self.list_state.selected() else {
+                        return Ok(None);
+                    };
+                    match self.display_rows().get(index) {
+                        Some(TreeRow::Group(kind, _)) => {
+                            let kind = *kind;
+                            if !self.collapsed.remove(&kind) {
+                                self.collapsed.insert(kind);
+                            }
+                        }
+                        Some(TreeRow::Object(object)) if object.kind.is_relation() => {
+                            return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                                QueryTag::InitialTable(object.table()),
+                            )?)));
+                        }
+                        Some(TreeRow::Object(object)) => {
+                            return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                                object.definition_tag(),
+                            )?)));
+                        }
+                        None => {}
+                    }
+                    return Ok(None);
+                }
+                Action::ViewStructure => {
+                    if let Some(object) = self.selection()
+                        && matches!(object.kind, ObjectKind::Table | ObjectKind::View)
+                    {
                         return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
-                            QueryTag::InitialTable(Table {
-                                schema: selection.0,
-                                name: selection.1,
-                            }),
+                            QueryTag::TableStructure(object.table()),
                         )?)));
                     }
                     return Ok(None);
                 }
-                Action::ViewStructure => {
-                    if let Some(selection) = self.selection() {
+                Action::ShowDdl => {
+                    if let Some(object) = self.selection() {
                         return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
-                            QueryTag::TableStructure(Table {
-                                schema: selection.0,
-                                name: selection.1,
-                            }),
+                            object.definition_tag(),
                         )?)));
                     }
                     return Ok(None);
                 }
+                Action::ShowDiagram => {
+                    return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                        QueryTag::SchemaDiagram(self.selected_schema.clone()),
+                    )?)));
+                }
+                Action::RefreshMaterializedView => {
+                    if let Some(object) = self.selection()
+                        && object.kind == ObjectKind::MaterializedView
+                    {
+                        return Ok(Some(Action::Prompt(PromptRequest {
+                            title: format!("Refresh materialized view {}?", object.name),
+                            purpose: PromptPurpose::RefreshMaterializedView(object.table()),
+                            fields: vec![],
+                        })));
+                    }
+                    return Ok(None);
+                }
+                Action::CycleSort => {
+                    self.sort = self.sort.next();
+                    self.list_state.select_first();
+                }
                 Action::Yank => {
                     if let Ok(clipboard) = Clipboard::new()
-                        && let Some(selection) = self.selection()
+                        && let Some(object) = self.selection()
                     {
                         let mut clip = clipboard;
-                        clip.set_text(selection.1)?
+                        clip.set_text(object.display_name())?
                     }
                 }
                 Action::Search => {
@@ -181,34 +358,38 @@ impl Component for TableList {
             // Listen for when the query is returned
             AppEvent::QueryResult(result, QueryTag::ListTables) => {
                 // If there is no "public" schema, then go with the first found.
-                if !result.rows.iter().any(|r| {
-                    r.first()
-                        .map(|r| r.as_deref() == Some("public"))
-                        .unwrap_or(false)
-                }) {
+                if !result
+                    .rows
+                    .iter()
+                    .any(|r| r.first().and_then(Value::text).as_deref() == Some("public"))
+                {
                     let it = result
                         .rows
                         .first()
-                        .map(|r| r.first().cloned())
-                        .unwrap_or(None);
-                    self.selected_schema = it.unwrap_or(None).unwrap_or("unknown".to_string());
+                        .and_then(|r| r.first().and_then(Value::text));
+                    self.selected_schema = it.unwrap_or("unknown".to_string());
                 }
 
                 self.items = result
                     .rows
                     .iter()
-                    .map(|r| {
-                        (
-                            r.first()
-                                .cloned()
-                                .unwrap_or(None)
-                                .unwrap_or("unknown".into()),
-                            r.get(1).cloned().unwrap_or(None).unwrap_or("???".into()),
-                        )
+                    .filter_map(|r| {
+                        let value = |idx: usize| r.get(idx).and_then(Value::text);
+                        Some(DbObject {
+                            schema: value(0).unwrap_or("unknown".into()),
+                            name: value(1).unwrap_or("???".into()),
+                            kind: ObjectKind::from_name(&value(2)?)?,
+                            arguments: value(3).unwrap_or_default(),
+                            size: value(4).and_then(|v| v.parse().ok()),
+                            estimated_rows: value(5).and_then(|v| v.parse().ok()),
+                        })
                     })
                     .collect();
                 Ok(None)
             }
+            AppEvent::QueryResult(_, QueryTag::RefreshMaterializedView(view)) => Ok(Some(
+                Action::Info(format!("Refreshed materialized view {}", view.name)),
+            )),
             _ => Ok(None),
         }
     }
@@ -221,8 +402,13 @@ impl Component for TableList {
         let has_focus = self.focused.is_some();
         let search_focused = matches!(self.focused, Some(FocusTarget::Search));
 
+        let sorted_by = match self.sort {
+            SortOrder::Name => "",
+            SortOrder::Size => " by size",
+            SortOrder::Rows => " by rows",
+        };
         let block = Block::bordered()
-            .title(format!("{} [alt+1]", self.selected_schema))
+            .title(format!("{}{sorted_by} [alt+1]", self.selected_schema))
             .style(Style::new().fg(if has_focus { Color::Cyan } else { Color::Blue }))
             .title_alignment(Alignment::Center)
             .border_type(if has_focus {
@@ -231,16 +417,35 @@ impl Component for TableList {
                 BorderType::Plain
             });
 
-        let list = List::new(self.display_items().map(|(_, table)| {
-            Text::styled(
-                table,
-                if search_focused {
-                    Color::DarkGray
+        let item_color = if search_focused {
+            Color::DarkGray
+        } else {
+            Color::Cyan
+        };
+        let list = List::new(self.display_rows().iter().map(|row| match row {
+            TreeRow::Group(kind, count) => {
+                let marker = if self.collapsed.contains(kind) {
+                    "▸"
                 } else {
-                    Color::Cyan
-                },
-            )
+                    "▾"
+                };
+                Line::from(format!("{marker} {} ({count})", kind.group_name())).bold()
+            }
+            TreeRow::Object(object) => {
+                // The value objects are sorted by is shown after their name
+                let value = match self.sort {
+                    SortOrder::Name => None,
+                    SortOrder::Size => object.size.map(format_size),
+                    SortOrder::Rows => object.estimated_rows.map(|rows| format!("~{rows} rows")),
+                };
+                let mut line = Line::from(format!("  {}", object.display_name()));
+                if let Some(value) = value {
+                    line.push_span(format!("  {value}").dark_gray());
+                }
+                line
+            }
         }))
+        .style(item_color)
         .highlight_style(Modifier::REVERSED)
         .highlight_symbol("▹ ")
         .block(block);
@@ -271,23 +476,58 @@ impl Component for TableList {
 }
 
 impl TableList {
-    fn display_items(&self) -> impl Iterator<Item = &(String, String)> {
-        self.items.iter().filter(move |(schema, name)| {
-            (self.search.is_none() || name.contains(&self.search_content()))
-                && schema == &self.selected_schema
-        })
+    /// The visible lines of the tree: each group with objects matching the search, followed by
+    /// those objects unless the group is collapsed.
+    fn display_rows(&self) -> Vec<TreeRow<'_>> {
+        let mut rows = vec![];
+        for kind in ObjectKind::iter() {
+            let mut objects: Vec<&DbObject> = self
+                .items
+                .iter()
+                .filter(|object| {
+                    object.kind == kind
+                        && object.schema == self.selected_schema
+                        && (self.search.is_none() || object.name.contains(&self.search_content()))
+                })
+                .collect();
+            // Items come sorted by name, which breaks ties.
+            match self.sort {
+                SortOrder::Name => {}
+                SortOrder::Size => objects.sort_by_key(|o| std::cmp::Reverse(o.size)),
+                SortOrder::Rows => objects.sort_by_key(|o| std::cmp::Reverse(o.estimated_rows)),
+            }
+            if objects.is_empty() {
+                continue;
+            }
+            rows.push(TreeRow::Group(kind, objects.len()));
+            if !self.collapsed.contains(&kind) {
+                rows.extend(objects.into_iter().map(TreeRow::Object));
+            }
+        }
+        rows
     }
 
     fn search_content(&self) -> String {
         self.search.clone().unwrap_or_default()
     }
 
-    fn selection(&self) -> Option<(String, String)> {
-        if let Some(index) = self.list_state.selected()
-            && let Some((schema, table)) = self.display_items().nth(index)
-        {
-            return Some((schema.clone(), table.clone()));
+    fn selection(&self) -> Option<DbObject> {
+        let index = self.list_state.selected()?;
+        match self.display_rows().get(index) {
+            Some(TreeRow::Object(object)) => Some((*object).clone()),
+            _ => None,
         }
-        None
     }
 }
+
+/// Formats a size in bytes like `pg_size_pretty`, e.g. `8192 bytes` or `48 kB`.
+fn format_size(bytes: i64) -> String {
+    let units = ["bytes", "kB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size.abs() >= 10240.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{} {}", size.round(), units[unit])
+}
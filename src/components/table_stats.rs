@@ -0,0 +1,99 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Block, BorderType, Cell, Row, Table},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    app_event::{AppEvent, QueryTag},
+    components::Component,
+    config::Config,
+    database::{
+        system_query::{self, SystemQuery},
+        value::Value,
+    },
+};
+
+/// Panel shown next to the structure of a table, with its size, row estimate and maintenance
+/// statistics.
+#[derive(Default)]
+pub struct TableStats {
+    /// Table the statistics are shown for
+    table: Option<system_query::Table>,
+    /// Name and value of each statistic
+    stats: Vec<(String, Option<String>)>,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+}
+
+impl Component for TableStats {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        match event {
+            // Statistics are fetched whenever the structure of a table is opened
+            AppEvent::QueryResult(_, QueryTag::TableStructure(table)) => {
+                self.table = Some(table.clone());
+                self.stats.clear();
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::TableStats(table),
+                )?)));
+            }
+            // Ignore late results for a previously displayed table
+            AppEvent::QueryResult(result, QueryTag::TableStats(table))
+                if self.table.as_ref() == Some(&table) =>
+            {
+                let values = result.rows.into_iter().next().unwrap_or_default();
+                self.stats = result
+                    .columns
+                    .into_iter()
+                    .zip(
+                        values
+                            .iter()
+                            .map(Value::text)
+                            .chain(std::iter::repeat(None)),
+                    )
+                    .collect();
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame, area: Rect) -> color_eyre::Result<()> {
+        let block = Block::bordered()
+            .title("Statistics")
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(Color::Blue))
+            .border_type(BorderType::Plain);
+
+        let rows = self.stats.iter().map(|(name, value)| {
+            Row::new([
+                Cell::from(name.as_str()).bold(),
+                Cell::from(value.as_deref().unwrap_or("-")).fg(Color::White),
+            ])
+        });
+        let label_width = self.stats.iter().map(|(n, _)| n.len()).max();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(label_width.unwrap_or_default() as u16),
+                Constraint::Fill(1),
+            ],
+        )
+        .column_spacing(2)
+        .block(block);
+        frame.render_widget(table, area);
+        Ok(())
+    }
+}
@@ -1,45 +1,77 @@
+mod vim;
+
+use std::{fs, path::PathBuf};
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::Alignment,
-    style::{Color, Style},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, BorderType},
 };
 use tokio::sync::mpsc::UnboundedSender;
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
 
 use crate::{
-    action::Action, app::Mode, app_event::QueryTag, components::Component, config::Config,
-    database::system_query::Query,
+    action::Action,
+    app::Mode,
+    app_event::{AppEvent, QueryTag},
+    components::{
+        Component,
+        prompt::{PromptPurpose, PromptRequest},
+    },
+    config::Config,
+    database::{
+        connection::QueryResult,
+        system_query::{Query, SystemQuery},
+        value::Value,
+    },
+    external_editor::{EditPurpose, EditRequest},
 };
+use vim::{Vim, VimMode};
 
-/// Text editor for SQL statements.
-pub struct TextEditor<'a> {
+/// An editor tab. Each buffer keeps its own text, cursor, selection and last result.
+struct Buffer<'a> {
+    name: String,
     internal: TextArea<'a>,
-    command_tx: Option<UnboundedSender<Action>>,
-    config: Config,
-    focused: bool,
+    /// File the buffer was opened from or saved to
+    file: Option<PathBuf>,
+    /// Buffer contents as of the last file open or save, for tracking unsaved changes
+    saved_contents: String,
+    /// Result of the last query run from this buffer
+    last_result: Option<QueryResult>,
 }
 
-impl<'a> Default for TextEditor<'a> {
-    fn default() -> Self {
-        let mut internal = TextArea::default();
-
-        let style = Style::default().bg(Color::DarkGray).fg(Color::LightBlue);
-        internal.set_line_number_style(style);
-        internal.set_selection_style(Style::default().bg(Color::DarkGray));
-
+impl<'a> Buffer<'a> {
+    fn new(name: String, contents: &str) -> Self {
         Self {
-            internal,
-            command_tx: Default::default(),
-            config: Default::default(),
-            focused: false,
+            name,
+            internal: text_area(contents),
+            file: None,
+            saved_contents: String::default(),
+            last_result: None,
         }
     }
-}
 
-impl<'a> TextEditor<'a> {
-    pub fn new() -> Self {
-        Self::default()
+    fn contents(&self) -> String {
+        self.internal.lines().join("\n")
+    }
+
+    /// Replaces the text, keeping the cursor where it was as far as possible.
+    fn set_contents(&mut self, contents: &str) {
+        let (row, col) = self.internal.cursor();
+        self.internal = text_area(contents);
+        self.internal
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.file.is_some() && self.contents() != self.saved_contents
+    }
+
+    /// Scratch buffers have no file, so their contents are persisted as drafts.
+    fn is_scratch(&self) -> bool {
+        self.file.is_none()
     }
 
     fn query(&self) -> String {
@@ -66,6 +98,308 @@ impl<'a> TextEditor<'a> {
     }
 }
 
+/// Text editor for SQL statements, with a tab for each buffer.
+pub struct TextEditor<'a> {
+    buffers: Vec<Buffer<'a>>,
+    /// Index of the visible buffer
+    active: usize,
+    /// Buffer that ran the query whose result is still pending
+    pending_result: Option<usize>,
+    command_tx: Option<UnboundedSender<Action>>,
+    config: Config,
+    focused: bool,
+    /// Modal editing layer, when enabled in the config
+    vim: Option<Vim>,
+    /// Name of the open connection, whose drafts the scratch buffers are persisted to
+    connection: Option<String>,
+    /// Name and contents of the scratch buffers as of the last drafts persisted
+    persisted_drafts: Vec<(String, String)>,
+    /// Whether saving the drafts failed since they were last saved
+    drafts_failed: bool,
+}
+
+impl<'a> Default for TextEditor<'a> {
+    fn default() -> Self {
+        Self {
+            buffers: vec![Buffer::new(String::from("scratch1"), "")],
+            active: 0,
+            pending_result: None,
+            command_tx: Default::default(),
+            config: Default::default(),
+            focused: false,
+            vim: None,
+            connection: None,
+            persisted_drafts: Default::default(),
+            drafts_failed: false,
+        }
+    }
+}
+
+impl<'a> TextEditor<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn buffer(&self) -> &Buffer<'a> {
+        &self.buffers[self.active]
+    }
+
+    fn buffer_mut(&mut self) -> &mut Buffer<'a> {
+        &mut self.buffers[self.active]
+    }
+
+    fn query(&self) -> String {
+        self.buffer().query()
+    }
+
+    /// Whether the active buffer is an untouched scratch buffer, which can be reused rather than
+    /// opening another tab.
+    fn is_pristine(&self) -> bool {
+        self.buffer().is_scratch() && self.buffer().contents().is_empty()
+    }
+
+    fn next_scratch_name(&self) -> String {
+        (1..)
+            .map(|n| format!("scratch{n}"))
+            .find(|name| !self.buffers.iter().any(|b| &b.name == name))
+            .unwrap_or_default()
+    }
+
+    /// Opens a new scratch buffer with the given contents, reusing the active buffer if it's
+    /// pristine.
+    fn open_scratch(&mut self, name: String, contents: &str) -> Action {
+        let buffer = Buffer::new(name, contents);
+        if self.is_pristine() {
+            *self.buffer_mut() = buffer;
+            self.select_buffer(self.active)
+        } else {
+            self.new_buffer(buffer)
+        }
+    }
+
+    /// Opens the definition of a database object, returned by a system query as a single value,
+    /// in a new buffer.
+    fn open_definition(
+        &mut self,
+        result: QueryResult,
+        name: &str,
+        qualified_name: &str,
+    ) -> color_eyre::Result<Action> {
+        let Some(ddl) = result
+            .rows
+            .first()
+            .and_then(|row| row.first().and_then(Value::text))
+        else {
+            return Ok(Action::Error(format!(
+                "No definition found for {qualified_name}"
+            )));
+        };
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::ChangeMode(Mode::EditQuery))?;
+        }
+        let base = format!("{}-ddl", name.replace(['/', '\\'], "_"));
+        let name = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base}{n}"),
+            })
+            .find(|name| !self.buffers.iter().any(|b| &b.name == name))
+            .unwrap_or_default();
+        Ok(self.open_scratch(name, &ddl))
+    }
+
+    fn new_buffer(&mut self, buffer: Buffer<'a>) -> Action {
+        self.buffers.push(buffer);
+        self.select_buffer(self.buffers.len() - 1)
+    }
+
+    /// Switches tabs, showing the new buffer's last result in the results table.
+    fn select_buffer(&mut self, index: usize) -> Action {
+        self.active = index;
+        Action::DisplayResult(self.buffer().last_result.clone().unwrap_or_default())
+    }
+
+    fn close_buffer(&mut self) -> Action {
+        self.buffers.remove(self.active);
+        if self.buffers.is_empty() {
+            self.buffers.push(Buffer::new(self.next_scratch_name(), ""));
+        }
+        self.pending_result = None;
+        self.select_buffer(self.active.min(self.buffers.len() - 1))
+    }
+
+    fn rename_buffer(&mut self, name: &str) -> Action {
+        if !is_valid_name(name) {
+            return Action::Error(format!("Invalid buffer name '{name}'"));
+        }
+        if self.buffers.iter().any(|b| b.name == name) {
+            return Action::Error(format!("A buffer named '{name}' already exists"));
+        }
+        self.buffer_mut().name = name.to_string();
+        Action::Info(format!("Renamed buffer to '{name}'"))
+    }
+
+    fn open_file(&mut self, path: PathBuf) -> color_eyre::Result<()> {
+        let contents = fs::read_to_string(&path)?;
+        let name = file_name(&path);
+        let mut buffer = Buffer::new(name, &contents);
+        buffer.saved_contents = buffer.contents();
+        buffer.file = Some(path);
+        if self.is_pristine() {
+            *self.buffer_mut() = buffer;
+        } else {
+            self.buffers.push(buffer);
+            self.active = self.buffers.len() - 1;
+        }
+        Ok(())
+    }
+
+    fn save_file(&mut self, path: PathBuf) -> color_eyre::Result<()> {
+        let buffer = self.buffer_mut();
+        let contents = buffer.contents();
+        fs::write(&path, format!("{contents}\n"))?;
+        if buffer.file.is_none() {
+            buffer.name = file_name(&path);
+        }
+        buffer.saved_contents = contents;
+        buffer.file = Some(path);
+        Ok(())
+    }
+
+    fn save_file_action(&mut self, path: PathBuf) -> Action {
+        match self.save_file(expand_home(path.clone())) {
+            Ok(()) => Action::Info(format!("Saved {}", path.display())),
+            Err(e) => Action::Error(format!("Failed to save {}: {e}", path.display())),
+        }
+    }
+
+    fn drafts_dir(&self) -> Option<PathBuf> {
+        self.connection
+            .as_ref()
+            .map(|connection| self.config.config.data_dir.join("drafts").join(connection))
+    }
+
+    /// Writes the scratch buffers to the open connection's drafts if they changed, so they
+    /// survive quitting. Drafts are prefixed with their tab position to restore them in order.
+    fn persist_drafts(&mut self) -> color_eyre::Result<()> {
+        let drafts: Vec<(String, String)> = self
+            .buffers
+            .iter()
+            .filter(|b| b.is_scratch())
+            .map(|b| (b.name.clone(), b.contents()))
+            .collect();
+        if drafts == self.persisted_drafts {
+            return Ok(());
+        }
+        if let Some(dir) = self.drafts_dir() {
+            fs::create_dir_all(&dir)?;
+            // Each draft is written aside and renamed into place, and only then are the drafts
+            // of closed tabs removed, so that a crash midway never loses them
+            let mut written = vec![];
+            for (idx, (name, contents)) in drafts.iter().enumerate() {
+                let path = dir.join(format!("{idx:02}-{name}.sql"));
+                let temp = path.with_extension("sql.tmp");
+                fs::write(&temp, contents)?;
+                fs::rename(temp, &path)?;
+                written.push(path);
+            }
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let stale = path
+                    .extension()
+                    .is_some_and(|ext| ext == "sql" || ext == "tmp");
+                if stale && !written.contains(&path) {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+        self.persisted_drafts = drafts;
+        Ok(())
+    }
+
+    /// Restores the connection's drafts. When the editor is already in use they open as extra tabs,
+    /// renamed if a buffer already has their name, so that none are lost once the tabs are persisted.
+    fn restore_drafts(&mut self) -> color_eyre::Result<()> {
+        let Some(dir) = self.drafts_dir() else {
+            return Ok(());
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(());
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+            .collect();
+        paths.sort();
+
+        let mut drafts = vec![];
+        for path in paths {
+            let stem = file_name(&path.with_extension(""));
+            let name = stem
+                .split_once('-')
+                .map(|(_, name)| name.to_string())
+                .unwrap_or(stem);
+            drafts.push((name, fs::read_to_string(path)?));
+        }
+        if drafts.is_empty() {
+            return Ok(());
+        }
+        if self.buffers.len() == 1 && self.is_pristine() {
+            self.buffers.clear();
+            self.active = 0;
+        }
+        for (name, contents) in &drafts {
+            // Reconnecting finds the drafts already open
+            if self
+                .buffers
+                .iter()
+                .any(|b| &b.name == name && &b.contents() == contents)
+            {
+                continue;
+            }
+            let name = if self.buffers.iter().any(|b| &b.name == name) {
+                self.next_scratch_name()
+            } else {
+                name.clone()
+            };
+            self.buffers.push(Buffer::new(name, contents));
+        }
+        // What is on disk, so that the next tick rewrites the drafts when the tabs differ
+        self.persisted_drafts = drafts;
+        Ok(())
+    }
+
+    fn file_prompt(&self, purpose: PromptPurpose, title: &str) -> Action {
+        let path = self
+            .buffer()
+            .file
+            .as_ref()
+            .map(|f| f.display().to_string())
+            .unwrap_or_default();
+        Action::Prompt(PromptRequest {
+            purpose,
+            title: String::from(title),
+            fields: vec![(String::from("Path"), path)],
+        })
+    }
+
+    /// Tab bar listing the buffers, with the active one highlighted.
+    fn title(&self) -> Line<'static> {
+        let mut spans = vec![];
+        for (idx, buffer) in self.buffers.iter().enumerate() {
+            let dirty = if buffer.is_dirty() { " [+]" } else { "" };
+            let tab = Span::from(format!(" {}:{}{dirty} ", idx + 1, buffer.name));
+            spans.push(if idx == self.active {
+                tab.reversed()
+            } else {
+                tab
+            });
+        }
+        spans.push(Span::from(" [alt+2]"));
+        Line::from(spans)
+    }
+}
+
 impl Component for TextEditor<'_> {
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
         match action {
@@ -76,8 +410,186 @@ impl Component for TextEditor<'_> {
                 query,
                 ..
             }) => {
-                self.internal.insert_newline();
-                self.internal.insert_str(query);
+                self.pending_result = Some(self.active);
+                self.buffer_mut().internal.insert_newline();
+                self.buffer_mut().internal.insert_str(query);
+            }
+            Action::ExecuteQuery(Query {
+                tag: QueryTag::User,
+                ..
+            }) => self.pending_result = Some(self.active),
+            Action::InsertQuery(query) => {
+                self.buffer_mut().internal.insert_newline();
+                self.buffer_mut().internal.insert_str(query);
+                return Ok(Some(Action::ChangeMode(Mode::EditQuery)));
+            }
+            Action::OpenDbConnection(connection) => {
+                self.connection = Some(connection);
+                if let Err(e) = self.restore_drafts() {
+                    return Ok(Some(Action::Error(format!(
+                        "Failed to restore drafts: {e}"
+                    ))));
+                }
+            }
+            Action::Tick | Action::Quit => match self.persist_drafts() {
+                // Saving is retried every tick, but the failure is only reported once
+                Err(e) if !self.drafts_failed => {
+                    self.drafts_failed = true;
+                    return Ok(Some(Action::Error(format!("Failed to save drafts: {e}"))));
+                }
+                Err(_) => {}
+                Ok(()) => self.drafts_failed = false,
+            },
+            Action::NewBuffer if self.focused => {
+                let name = self.next_scratch_name();
+                return Ok(Some(self.new_buffer(Buffer::new(name, ""))));
+            }
+            Action::CloseBuffer if self.focused => {
+                if self.buffer().is_dirty() {
+                    return Ok(Some(Action::Prompt(PromptRequest {
+                        purpose: PromptPurpose::CloseBuffer,
+                        title: format!("Discard unsaved changes to {}?", self.buffer().name),
+                        fields: vec![],
+                    })));
+                }
+                return Ok(Some(self.close_buffer()));
+            }
+            Action::PromptSubmitted(PromptPurpose::CloseBuffer, _) => {
+                return Ok(Some(self.close_buffer()));
+            }
+            Action::RenameBuffer if self.focused => {
+                return Ok(Some(Action::Prompt(PromptRequest {
+                    purpose: PromptPurpose::RenameBuffer,
+                    title: String::from("Rename buffer"),
+                    fields: vec![(String::from("Name"), self.buffer().name.clone())],
+                })));
+            }
+            Action::PromptSubmitted(PromptPurpose::RenameBuffer, values) => {
+                if let Some(name) = values.first() {
+                    return Ok(Some(self.rename_buffer(name.trim())));
+                }
+            }
+            Action::NextBuffer if self.focused => {
+                return Ok(Some(
+                    self.select_buffer((self.active + 1) % self.buffers.len()),
+                ));
+            }
+            Action::PreviousBuffer if self.focused => {
+                let len = self.buffers.len();
+                return Ok(Some(self.select_buffer((self.active + len - 1) % len)));
+            }
+            Action::OpenFile(path) => {
+                return Ok(Some(match self.open_file(expand_home(path.clone())) {
+                    Ok(()) => self.select_buffer(self.active),
+                    Err(e) => Action::Error(format!("Failed to open {}: {e}", path.display())),
+                }));
+            }
+            Action::PromptOpenFile if self.focused => {
+                return Ok(Some(self.file_prompt(PromptPurpose::OpenFile, "Open file")));
+            }
+            Action::SaveFile if self.focused => match self.buffer().file.clone() {
+                Some(path) => {
+                    return Ok(Some(self.save_file_action(path)));
+                }
+                None => return Ok(Some(self.file_prompt(PromptPurpose::SaveFile, "Save file"))),
+            },
+            Action::PromptSubmitted(PromptPurpose::OpenFile, values) => {
+                if let Some(path) = values.first() {
+                    return Ok(Some(Action::OpenFile(PathBuf::from(path.trim()))));
+                }
+            }
+            Action::PromptSubmitted(PromptPurpose::SaveFile, values) => {
+                if let Some(path) = values.first() {
+                    return Ok(Some(self.save_file_action(PathBuf::from(path.trim()))));
+                }
+            }
+            Action::Explain | Action::ExplainAnalyze if self.focused => {
+                let query = self.query();
+                if query.trim().is_empty() {
+                    return Ok(None);
+                }
+                let analyze = action == Action::ExplainAnalyze;
+                // Analyzing runs the statement, and though its changes are rolled back, some
+                // effects like advancing sequences are not, so ask first unless it only reads.
+                if analyze && !is_read_only(&query) {
+                    return Ok(Some(Action::Prompt(PromptRequest {
+                        purpose: PromptPurpose::ExplainAnalyze(query),
+                        title: String::from(
+                            "EXPLAIN ANALYZE runs the statement, then rolls it back. Run it?",
+                        ),
+                        fields: vec![],
+                    })));
+                }
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::Explain { query, analyze },
+                )?)));
+            }
+            Action::PromptSubmitted(PromptPurpose::ExplainAnalyze(query), _) => {
+                return Ok(Some(Action::ExecuteQuery(SystemQuery::query_for(
+                    QueryTag::Explain {
+                        query,
+                        analyze: true,
+                    },
+                )?)));
+            }
+            Action::EditBuffer if self.focused => {
+                return Ok(Some(Action::OpenInEditor(EditRequest {
+                    purpose: EditPurpose::Buffer(self.buffer().name.clone()),
+                    contents: self.buffer().contents(),
+                    extension: String::from("sql"),
+                })));
+            }
+            Action::EditorClosed(EditPurpose::Buffer(name), contents) => {
+                let Some(buffer) = self.buffers.iter_mut().find(|b| b.name == name) else {
+                    return Ok(Some(Action::Error(format!("Buffer '{name}' was closed"))));
+                };
+                buffer.set_contents(&contents);
+                if self.config.editor.run_after_external_edit && !contents.trim().is_empty() {
+                    return Ok(Some(Action::ExecuteQuery(Query {
+                        query: contents,
+                        tag: QueryTag::User,
+                        binds: None,
+                    })));
+                }
+            }
+            Action::SaveSnippet if self.focused => {
+                return Ok(Some(Action::Prompt(PromptRequest {
+                    purpose: PromptPurpose::SaveSnippet(self.query()),
+                    title: String::from("Save query"),
+                    fields: vec![(String::from("Name"), String::default())],
+                })));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_app_events(&mut self, event: AppEvent) -> color_eyre::Result<Option<Action>> {
+        match event {
+            AppEvent::QueryResult(result, QueryTag::User)
+            | AppEvent::QueryResult(result, QueryTag::InitialTable(_)) => {
+                if let Some(buffer) = self
+                    .pending_result
+                    .take()
+                    .and_then(|idx| self.buffers.get_mut(idx))
+                {
+                    buffer.last_result = Some(result);
+                }
+            }
+            AppEvent::QueryResult(result, QueryTag::TableDdl(table))
+            | AppEvent::QueryResult(result, QueryTag::TypeDefinition(table)) => {
+                return Ok(Some(self.open_definition(
+                    result,
+                    &table.name,
+                    &format!("{}.{}", table.schema, table.name),
+                )?));
+            }
+            AppEvent::QueryResult(result, QueryTag::FunctionDefinition(function)) => {
+                return Ok(Some(self.open_definition(
+                    result,
+                    &function.name,
+                    &format!("{}.{}", function.schema, function.name),
+                )?));
             }
             _ => {}
         }
@@ -85,8 +597,9 @@ impl Component for TextEditor<'_> {
     }
 
     fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
-        // Only handle arbitrary key events if the editor is in focus
-        if !self.focused {
+        // Only handle arbitrary key events if the editor is in focus, and leave keys bound to
+        // actions to the keymap.
+        if !self.focused || self.config.keybindings.is_bound(Mode::EditQuery, &key) {
             return Ok(None);
         }
 
@@ -102,7 +615,13 @@ impl Component for TextEditor<'_> {
             }
             // any other key we accept as editor input
             _ => {
-                self.internal.input(key);
+                let internal = &mut self.buffers[self.active].internal;
+                match &mut self.vim {
+                    Some(vim) => vim.input(key, internal),
+                    None => {
+                        internal.input(key);
+                    }
+                }
                 Ok(None)
             }
         }
@@ -114,6 +633,7 @@ impl Component for TextEditor<'_> {
     }
 
     fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.vim = config.editor.vim_mode.then(Vim::default);
         self.config = config;
         Ok(())
     }
@@ -123,8 +643,8 @@ impl Component for TextEditor<'_> {
         frame: &mut ratatui::Frame,
         area: ratatui::prelude::Rect,
     ) -> color_eyre::Result<()> {
-        let block = Block::bordered()
-            .title("lazydb [alt+2]")
+        let mut block = Block::bordered()
+            .title(self.title())
             .style(Style::new().fg(if self.focused {
                 Color::Cyan
             } else {
@@ -136,9 +656,142 @@ impl Component for TextEditor<'_> {
             } else {
                 BorderType::Plain
             });
-        self.internal.set_block(block);
+        let vim_mode = self.vim.as_ref().map(|vim| vim.mode());
+        if let Some(mode) = vim_mode {
+            block = block.title_bottom(format!(" -- {} -- ", mode.label()));
+        }
+        let internal = &mut self.buffer_mut().internal;
+        internal.set_block(block);
+        internal.set_cursor_style(match vim_mode {
+            Some(VimMode::Insert) => Style::default().underlined(),
+            _ => Style::default().reversed(),
+        });
 
-        frame.render_widget(&self.internal, area);
+        frame.render_widget(&*internal, area);
         Ok(())
     }
 }
+
+/// A text area holding the contents, styled like the editor's.
+fn text_area<'a>(contents: &str) -> TextArea<'a> {
+    let mut internal = TextArea::new(contents.lines().map(String::from).collect());
+    let style = Style::default().bg(Color::DarkGray).fg(Color::LightBlue);
+    internal.set_line_number_style(style);
+    internal.set_selection_style(Style::default().bg(Color::DarkGray));
+    internal
+}
+
+/// Buffer and saved query names are used as file names, so they can't be paths.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\']) && !name.starts_with('.')
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Whether a statement only reads data, going by its first keyword. `WITH` queries can contain
+/// data-modifying statements, so they don't count.
+fn is_read_only(query: &str) -> bool {
+    let keyword = query
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    matches!(keyword.as_str(), "select" | "values" | "table" | "show")
+}
+
+/// Expands a leading `~` in a path typed by the user to their home directory.
+pub fn expand_home(path: PathBuf) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~")
+        && let Some(dirs) = directories::BaseDirs::new()
+    {
+        return dirs.home_dir().join(rest);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_drafts_survive_connecting_with_editor_in_use() {
+        let data_dir = std::env::temp_dir().join(format!("lazydb-drafts-{}", std::process::id()));
+        let drafts = data_dir.join("drafts").join("local");
+        fs::create_dir_all(&drafts).unwrap();
+        fs::write(drafts.join("00-scratch1.sql"), "select 1").unwrap();
+        fs::write(drafts.join("01-report.sql"), "select 2").unwrap();
+
+        let mut editor = TextEditor::new();
+        editor.config.config.data_dir = data_dir.clone();
+        editor.buffer_mut().internal.insert_str("select 3");
+        editor
+            .update(Action::OpenDbConnection(String::from("local")))
+            .unwrap();
+        editor.update(Action::Tick).unwrap();
+        // Reconnecting doesn't open the drafts twice
+        editor
+            .update(Action::OpenDbConnection(String::from("local")))
+            .unwrap();
+
+        let buffers: Vec<(String, String)> = editor
+            .buffers
+            .iter()
+            .map(|b| (b.name.clone(), b.contents()))
+            .collect();
+        let mut persisted: Vec<(String, String)> = fs::read_dir(&drafts)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (file_name(&path), fs::read_to_string(&path).unwrap())
+            })
+            .collect();
+        persisted.sort();
+        fs::remove_dir_all(data_dir).unwrap();
+
+        let expected = [
+            ("scratch1", "select 3"),
+            ("scratch2", "select 1"),
+            ("report", "select 2"),
+        ];
+        assert_eq!(
+            buffers,
+            expected.map(|(name, contents)| (name.to_string(), contents.to_string()))
+        );
+        assert_eq!(
+            persisted,
+            [
+                ("00-scratch1.sql", "select 3"),
+                ("01-scratch2.sql", "select 1"),
+                ("02-report.sql", "select 2"),
+            ]
+            .map(|(name, contents)| (name.to_string(), contents.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_drafts_failure_reported_once() {
+        // The data dir is a file, so the drafts can't be saved under it
+        let data_dir =
+            std::env::temp_dir().join(format!("lazydb-unwritable-{}", std::process::id()));
+        fs::write(&data_dir, "").unwrap();
+
+        let mut editor = TextEditor::new();
+        editor.config.config.data_dir = data_dir.clone();
+        editor
+            .update(Action::OpenDbConnection(String::from("local")))
+            .unwrap();
+        editor.buffer_mut().internal.insert_str("select 1");
+        let first = editor.update(Action::Tick).unwrap();
+        let second = editor.update(Action::Tick).unwrap();
+        fs::remove_file(data_dir).unwrap();
+
+        assert!(matches!(first, Some(Action::Error(_))));
+        assert_eq!(second, None);
+    }
+}
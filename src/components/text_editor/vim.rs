@@ -0,0 +1,654 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui_textarea::{CursorMove, TextArea};
+
+/// Largest count a command is repeated by, so that long counts don't overflow or hang the editor
+const MAX_COUNT: usize = 99_999;
+
+/// Editing mode of the vim layer, shown in the editor border.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+}
+
+impl VimMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::Visual => "VISUAL",
+            VimMode::VisualLine => "VISUAL LINE",
+        }
+    }
+}
+
+/// Keys typed so far of a command spanning several keys, like `gg`, `dw` or `ci"`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum Pending {
+    #[default]
+    None,
+    /// `g`, waiting for the second `g`
+    Goto,
+    /// `r`, waiting for the replacement character
+    Replace,
+    /// `d`, `c` or `y` with the count typed before it, waiting for a motion or text object
+    Operator(char, usize),
+    /// An operator followed by `i` (inner) or `a` (around), waiting for the object
+    TextObject(char, bool),
+}
+
+/// Modal, vim-like editing on top of the editor's `TextArea`.
+#[derive(Debug, Default)]
+pub struct Vim {
+    mode: VimMode,
+    pending: Pending,
+    count: Option<usize>,
+    /// Where visual mode started
+    anchor: (usize, usize),
+    /// Whether the yanked text holds whole lines, which are pasted on their own lines
+    linewise: bool,
+}
+
+type Position = (usize, usize);
+
+impl Vim {
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    pub fn input(&mut self, key: KeyEvent, textarea: &mut TextArea) {
+        if self.mode == VimMode::Insert {
+            if key.code == KeyCode::Esc {
+                self.mode = VimMode::Normal;
+                textarea.move_cursor(CursorMove::Back);
+            } else {
+                textarea.input(key);
+            }
+            return;
+        }
+
+        let c = match key.code {
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => return,
+            KeyCode::Char(c) => c,
+            KeyCode::Left => 'h',
+            KeyCode::Down => 'j',
+            KeyCode::Up => 'k',
+            KeyCode::Right => 'l',
+            KeyCode::Esc => {
+                if self.is_visual() {
+                    textarea.cancel_selection();
+                }
+                self.mode = VimMode::Normal;
+                self.pending = Pending::None;
+                self.count = None;
+                return;
+            }
+            _ => return,
+        };
+
+        if c.is_ascii_digit() && (c != '0' || self.count.is_some()) {
+            let digit = c.to_digit(10).unwrap_or_default() as usize;
+            self.count = Some((self.count.unwrap_or(0) * 10 + digit).min(MAX_COUNT));
+            return;
+        }
+        let count = self.count.take().unwrap_or(1);
+
+        match std::mem::take(&mut self.pending) {
+            Pending::Goto => {
+                if c == 'g' {
+                    textarea.move_cursor(CursorMove::Top);
+                    self.refresh_line_selection(textarea);
+                }
+            }
+            Pending::Replace => {
+                textarea.delete_next_char();
+                textarea.insert_char(c);
+                textarea.move_cursor(CursorMove::Back);
+            }
+            Pending::Operator(op, op_count) => self.operator(
+                op,
+                c,
+                op_count.saturating_mul(count).min(MAX_COUNT),
+                textarea,
+            ),
+            Pending::TextObject(op, inner) => {
+                let cursor = textarea.cursor();
+                if let Some((start, end)) = text_object(textarea.lines(), cursor, inner, c) {
+                    self.apply(op, start, end, textarea);
+                }
+            }
+            Pending::None if self.is_visual() => self.visual(c, count, textarea),
+            Pending::None => self.normal(c, count, textarea),
+        }
+    }
+
+    fn is_visual(&self) -> bool {
+        matches!(self.mode, VimMode::Visual | VimMode::VisualLine)
+    }
+
+    fn normal(&mut self, c: char, count: usize, textarea: &mut TextArea) {
+        match c {
+            'i' => self.mode = VimMode::Insert,
+            'a' => {
+                if !textarea.lines()[textarea.cursor().0].is_empty() {
+                    textarea.move_cursor(CursorMove::Forward);
+                }
+                self.mode = VimMode::Insert;
+            }
+            'I' => {
+                textarea.move_cursor(CursorMove::Head);
+                self.mode = VimMode::Insert;
+            }
+            'A' => {
+                textarea.move_cursor(CursorMove::End);
+                self.mode = VimMode::Insert;
+            }
+            'o' => {
+                textarea.move_cursor(CursorMove::End);
+                textarea.insert_newline();
+                self.mode = VimMode::Insert;
+            }
+            'O' => {
+                textarea.move_cursor(CursorMove::Head);
+                textarea.insert_newline();
+                textarea.move_cursor(CursorMove::Up);
+                self.mode = VimMode::Insert;
+            }
+            // Like vim, these never join lines
+            'x' | 's' => {
+                let (row, col) = textarea.cursor();
+                let len = textarea.lines()[row].chars().count();
+                let end = col.saturating_add(count).min(len);
+                self.apply(
+                    if c == 's' { 'c' } else { 'd' },
+                    (row, col),
+                    (row, end),
+                    textarea,
+                );
+            }
+            'X' => {
+                let (row, col) = textarea.cursor();
+                self.apply('d', (row, col.saturating_sub(count)), (row, col), textarea);
+            }
+            'D' | 'C' => {
+                textarea.start_selection();
+                textarea.move_cursor(CursorMove::End);
+                textarea.cut();
+                self.linewise = false;
+                if c == 'C' {
+                    self.mode = VimMode::Insert;
+                }
+            }
+            'J' => repeat(count, || {
+                textarea.move_cursor(CursorMove::End);
+                if textarea.delete_next_char() {
+                    textarea.insert_char(' ');
+                }
+            }),
+            'p' => repeat(count, || self.paste(true, textarea)),
+            'P' => repeat(count, || self.paste(false, textarea)),
+            'u' => repeat(count, || {
+                textarea.undo();
+            }),
+            'U' => repeat(count, || {
+                textarea.redo();
+            }),
+            'r' => self.pending = Pending::Replace,
+            'g' => self.pending = Pending::Goto,
+            'd' | 'c' | 'y' => self.pending = Pending::Operator(c, count),
+            'v' => {
+                self.anchor = textarea.cursor();
+                textarea.start_selection();
+                self.mode = VimMode::Visual;
+            }
+            'V' => {
+                self.anchor = textarea.cursor();
+                self.mode = VimMode::VisualLine;
+                self.refresh_line_selection(textarea);
+            }
+            _ => {
+                motion(c, count, textarea);
+            }
+        }
+    }
+
+    fn visual(&mut self, c: char, count: usize, textarea: &mut TextArea) {
+        match c {
+            'd' | 'x' | 'c' | 'y' => {
+                let op = if c == 'x' { 'd' } else { c };
+                let cursor = textarea.cursor();
+                if self.mode == VimMode::VisualLine {
+                    let rows = (self.anchor.0.min(cursor.0), self.anchor.0.max(cursor.0));
+                    textarea.cancel_selection();
+                    self.mode = VimMode::Normal;
+                    self.lines(op, rows.0, rows.1, textarea);
+                } else {
+                    textarea.cancel_selection();
+                    self.mode = VimMode::Normal;
+                    // Visual selections include the character under the cursor.
+                    let (start, end) = (self.anchor.min(cursor), self.anchor.max(cursor));
+                    let end = (
+                        end.0,
+                        (end.1 + 1).min(textarea.lines()[end.0].chars().count()),
+                    );
+                    self.apply(op, start, end, textarea);
+                }
+            }
+            'v' | 'V' => {
+                let mode = if c == 'v' {
+                    VimMode::Visual
+                } else {
+                    VimMode::VisualLine
+                };
+                textarea.cancel_selection();
+                if self.mode == mode {
+                    self.mode = VimMode::Normal;
+                } else {
+                    self.mode = mode;
+                    let cursor = textarea.cursor();
+                    jump(textarea, self.anchor);
+                    textarea.start_selection();
+                    jump(textarea, cursor);
+                    self.refresh_line_selection(textarea);
+                }
+            }
+            'g' => self.pending = Pending::Goto,
+            _ => {
+                motion(c, count, textarea);
+                self.refresh_line_selection(textarea);
+            }
+        }
+    }
+
+    fn operator(&mut self, op: char, c: char, count: usize, textarea: &mut TextArea) {
+        let row = textarea.cursor().0;
+        let last_row = textarea.lines().len() - 1;
+        match c {
+            // Doubled operators (`dd`, `cc`, `yy`) act on whole lines.
+            _ if c == op => self.lines(op, row, (row + count - 1).min(last_row), textarea),
+            'j' => self.lines(op, row, (row + count).min(last_row), textarea),
+            'k' => self.lines(op, row.saturating_sub(count), row, textarea),
+            'G' => self.lines(op, row, last_row, textarea),
+            'i' | 'a' => self.pending = Pending::TextObject(op, c == 'i'),
+            _ => {
+                let start = textarea.cursor();
+                // `cw` changes to the end of the word, like `ce`.
+                let c = if op == 'c' && c == 'w' { 'e' } else { c };
+                if !motion(c, count, textarea) {
+                    return;
+                }
+                let mut end = textarea.cursor();
+                if c == 'e' {
+                    // `e` is inclusive of the last character of the word.
+                    end.1 = (end.1 + 1).min(textarea.lines()[end.0].chars().count());
+                }
+                self.apply(op, start.min(end), start.max(end), textarea);
+            }
+        }
+    }
+
+    /// Deletes, changes or yanks the text between two positions.
+    fn apply(&mut self, op: char, start: Position, end: Position, textarea: &mut TextArea) {
+        self.linewise = false;
+        jump(textarea, start);
+        if start != end {
+            textarea.start_selection();
+            jump(textarea, end);
+            if op == 'y' {
+                textarea.copy();
+                textarea.cancel_selection();
+                jump(textarea, start);
+            } else {
+                textarea.cut();
+            }
+        }
+        if op == 'c' {
+            self.mode = VimMode::Insert;
+        }
+    }
+
+    /// Deletes, changes or yanks whole lines.
+    fn lines(&mut self, op: char, first: usize, last: usize, textarea: &mut TextArea) {
+        let len = |row: usize, textarea: &TextArea| textarea.lines()[row].chars().count();
+        let last_row = textarea.lines().len() - 1;
+
+        if op == 'c' {
+            // Keep a line to insert into.
+            self.apply(op, (first, 0), (last, len(last, textarea)), textarea);
+            return;
+        }
+
+        // Take the newline after the lines, or before them at the end of the buffer.
+        let (start, end) = if last < last_row {
+            ((first, 0), (last + 1, 0))
+        } else if first > 0 {
+            (
+                (first - 1, len(first - 1, textarea)),
+                (last, len(last, textarea)),
+            )
+        } else {
+            ((first, 0), (last, len(last, textarea)))
+        };
+        self.apply(op, start, end, textarea);
+
+        // Yanked lines always end with a newline, wherever it was taken from.
+        let text = textarea.yank_text();
+        let text = text.strip_prefix('\n').unwrap_or(&text);
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        textarea.set_yank_text(format!("{text}\n"));
+        self.linewise = true;
+
+        let row = if op == 'd' {
+            first.min(textarea.lines().len() - 1)
+        } else {
+            first
+        };
+        jump(textarea, (row, 0));
+    }
+
+    fn paste(&self, after: bool, textarea: &mut TextArea) {
+        if self.linewise {
+            let text = textarea.yank_text();
+            let text = text.strip_suffix('\n').unwrap_or(&text);
+            // The cursor ends up on the first pasted line.
+            let rows = text.lines().count().max(1);
+            if after {
+                textarea.move_cursor(CursorMove::End);
+                textarea.insert_newline();
+                textarea.insert_str(text);
+                repeat(rows - 1, || textarea.move_cursor(CursorMove::Up));
+            } else {
+                textarea.move_cursor(CursorMove::Head);
+                textarea.insert_str(text);
+                textarea.insert_newline();
+                repeat(rows, || textarea.move_cursor(CursorMove::Up));
+            }
+            textarea.move_cursor(CursorMove::Head);
+        } else {
+            if after && !textarea.lines()[textarea.cursor().0].is_empty() {
+                textarea.move_cursor(CursorMove::Forward);
+            }
+            textarea.paste();
+            textarea.move_cursor(CursorMove::Back);
+        }
+    }
+
+    /// In visual line mode, extends the selection to cover whole lines from the anchor to the
+    /// cursor.
+    fn refresh_line_selection(&self, textarea: &mut TextArea) {
+        if self.mode != VimMode::VisualLine {
+            return;
+        }
+        let row = textarea.cursor().0;
+        let len = |row: usize| textarea.lines()[row].chars().count();
+        let (from, to) = if row >= self.anchor.0 {
+            ((self.anchor.0, 0), (row, len(row)))
+        } else {
+            ((self.anchor.0, len(self.anchor.0)), (row, 0))
+        };
+        textarea.cancel_selection();
+        jump(textarea, from);
+        textarea.start_selection();
+        jump(textarea, to);
+    }
+}
+
+/// Moves the cursor, returning whether the key is a motion.
+fn motion(c: char, count: usize, textarea: &mut TextArea) -> bool {
+    let movement = match c {
+        'h' => CursorMove::Back,
+        'j' => CursorMove::Down,
+        'k' => CursorMove::Up,
+        'l' => CursorMove::Forward,
+        'w' => CursorMove::WordForward,
+        'b' => CursorMove::WordBack,
+        'e' => CursorMove::WordEnd,
+        '}' => CursorMove::ParagraphForward,
+        '{' => CursorMove::ParagraphBack,
+        '0' | '^' => CursorMove::Head,
+        '$' => CursorMove::End,
+        'G' => CursorMove::Bottom,
+        _ => return false,
+    };
+    repeat(count, || textarea.move_cursor(movement));
+    true
+}
+
+fn repeat(count: usize, mut f: impl FnMut()) {
+    for _ in 0..count {
+        f()
+    }
+}
+
+fn jump(textarea: &mut TextArea, (row, col): Position) {
+    textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+}
+
+/// Finds the range of a text object (`w`, `W`, quotes or brackets) around the cursor, with the
+/// end exclusive. `inner` objects exclude the surrounding quotes, brackets or whitespace.
+fn text_object(
+    lines: &[String],
+    cursor: Position,
+    inner: bool,
+    object: char,
+) -> Option<(Position, Position)> {
+    let (row, col) = cursor;
+    let line: Vec<char> = lines.get(row)?.chars().collect();
+    match object {
+        'w' | 'W' => {
+            let class = |c: char| {
+                if c.is_whitespace() {
+                    0
+                } else if object == 'W' || c.is_alphanumeric() || c == '_' {
+                    1
+                } else {
+                    2
+                }
+            };
+            let target = class(*line.get(col)?);
+            let mut start = col;
+            while start > 0 && class(line[start - 1]) == target {
+                start -= 1;
+            }
+            let mut end = col + 1;
+            while end < line.len() && class(line[end]) == target {
+                end += 1;
+            }
+            if !inner {
+                while end < line.len() && line[end].is_whitespace() {
+                    end += 1;
+                }
+            }
+            Some(((row, start), (row, end)))
+        }
+        '"' | '\'' | '`' => {
+            let quotes: Vec<usize> = (0..line.len()).filter(|i| line[*i] == object).collect();
+            let (open, close) = quotes
+                .chunks_exact(2)
+                .map(|pair| (pair[0], pair[1]))
+                // The pair around the cursor, or else the next one on the line
+                .find(|(_, close)| col <= *close)?;
+            if inner {
+                Some(((row, open + 1), (row, close)))
+            } else {
+                Some(((row, open), (row, close + 1)))
+            }
+        }
+        _ => {
+            let (open, close) = match object {
+                '(' | ')' | 'b' => ('(', ')'),
+                '[' | ']' => ('[', ']'),
+                '{' | '}' | 'B' => ('{', '}'),
+                '<' | '>' => ('<', '>'),
+                _ => return None,
+            };
+            // Flatten the buffer, keeping each character's position. Newlines map to the end of
+            // their line.
+            let mut chars: Vec<(char, Position)> = vec![];
+            for (r, l) in lines.iter().enumerate() {
+                for (c, ch) in l.chars().enumerate() {
+                    chars.push((ch, (r, c)));
+                }
+                chars.push(('\n', (r, l.chars().count())));
+            }
+            // A cursor on an empty line or past its end is on the newline
+            let cursor_idx = chars
+                .iter()
+                .position(|(ch, (r, c))| *r == row && (*c >= col || *ch == '\n'))?;
+
+            let mut depth = 0;
+            let mut start = None;
+            for idx in (0..=cursor_idx).rev() {
+                let ch = chars[idx].0;
+                if ch == close && idx != cursor_idx {
+                    depth += 1;
+                } else if ch == open {
+                    if depth == 0 {
+                        start = Some(idx);
+                        break;
+                    }
+                    depth -= 1;
+                }
+            }
+            let start = start?;
+            let mut depth = 0;
+            let mut end = None;
+            for (idx, (ch, _)) in chars.iter().enumerate().skip(start + 1) {
+                if *ch == open {
+                    depth += 1;
+                } else if *ch == close {
+                    if depth == 0 {
+                        end = Some(idx);
+                        break;
+                    }
+                    depth -= 1;
+                }
+            }
+            let end = end?;
+            if inner {
+                Some((chars[start + 1].1, chars[end].1))
+            } else {
+                Some((chars[start].1, chars.get(end + 1)?.1))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_count_is_capped() {
+        let mut vim = Vim::default();
+        let mut textarea = TextArea::from(["a", "b", "c"]);
+        for c in "99999999999999999999999d99999999999999j".chars() {
+            vim.input(KeyEvent::from(KeyCode::Char(c)), &mut textarea);
+        }
+        assert_eq!(vim.count, None);
+        assert_eq!(textarea.lines(), [""]);
+    }
+
+    #[test]
+    fn test_delete_chars_within_line() {
+        let mut vim = Vim::default();
+        let mut textarea = TextArea::from(["ab", "", "cd"]);
+        for c in "l3xjxjX".chars() {
+            vim.input(KeyEvent::from(KeyCode::Char(c)), &mut textarea);
+        }
+        assert_eq!(textarea.lines(), ["a", "", "cd"]);
+        vim.input(KeyEvent::from(KeyCode::Char('s')), &mut textarea);
+        assert_eq!(vim.mode(), VimMode::Insert);
+        assert_eq!(textarea.lines(), ["a", "", "d"]);
+    }
+
+    #[test]
+    fn test_word_objects() {
+        let text = lines("select foo_bar, baz");
+        assert_eq!(
+            text_object(&text, (0, 9), true, 'w'),
+            Some(((0, 7), (0, 14)))
+        );
+        assert_eq!(
+            text_object(&text, (0, 9), false, 'w'),
+            Some(((0, 7), (0, 14)))
+        );
+        assert_eq!(
+            text_object(&text, (0, 2), false, 'w'),
+            Some(((0, 0), (0, 7)))
+        );
+    }
+
+    #[test]
+    fn test_quote_objects() {
+        let text = lines(r#"where a = "x y" and b = 'z'"#);
+        assert_eq!(
+            text_object(&text, (0, 12), true, '"'),
+            Some(((0, 11), (0, 14)))
+        );
+        assert_eq!(
+            text_object(&text, (0, 12), false, '"'),
+            Some(((0, 10), (0, 15)))
+        );
+        // Before any quotes, the next pair on the line is used.
+        assert_eq!(
+            text_object(&text, (0, 0), true, '\''),
+            Some(((0, 25), (0, 26)))
+        );
+        assert_eq!(text_object(&text, (0, 0), true, '`'), None);
+    }
+
+    #[test]
+    fn test_bracket_objects() {
+        let text = lines("count(coalesce(a, b))");
+        assert_eq!(
+            text_object(&text, (0, 16), true, '('),
+            Some(((0, 15), (0, 19)))
+        );
+        assert_eq!(
+            text_object(&text, (0, 8), true, 'b'),
+            Some(((0, 6), (0, 20)))
+        );
+        assert_eq!(
+            text_object(&text, (0, 5), false, ')'),
+            Some(((0, 5), (0, 21)))
+        );
+        assert_eq!(text_object(&text, (0, 2), true, '('), None);
+    }
+
+    #[test]
+    fn test_bracket_object_from_empty_line() {
+        let text = lines("f(a,\n\n  b) {\n\n}");
+        assert_eq!(
+            text_object(&text, (1, 0), true, '('),
+            Some(((0, 2), (2, 3)))
+        );
+        assert_eq!(
+            text_object(&text, (3, 0), false, '{'),
+            Some(((2, 5), (4, 1)))
+        );
+        // Past the end of the line
+        assert_eq!(
+            text_object(&text, (0, 9), true, 'b'),
+            Some(((0, 2), (2, 3)))
+        );
+    }
+
+    #[test]
+    fn test_multiline_bracket_object() {
+        let text = lines("select (\n  1\n)");
+        assert_eq!(
+            text_object(&text, (1, 2), true, '('),
+            Some(((0, 8), (2, 0)))
+        );
+    }
+}
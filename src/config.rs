@@ -31,6 +31,33 @@ pub struct Config {
     pub styles: Styles,
     #[serde(default)]
     pub db_connections: DbConnections,
+    #[serde(default)]
+    pub editor: EditorConfig,
+    #[serde(default)]
+    pub results: ResultsConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct EditorConfig {
+    /// Use modal, vim-like editing in the query editor
+    #[serde(default)]
+    pub vim_mode: bool,
+    /// Run the buffer once it was edited in the external editor
+    #[serde(default)]
+    pub run_after_external_edit: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ResultsConfig {
+    /// Time zone to show timestamps with time zones in: `UTC`, `local` or an offset like `+02:00`
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// strftime-like format of timestamps, as supported by chrono
+    #[serde(default)]
+    pub timestamp_format: Option<String>,
+    /// Widest a column is fitted to its contents, longer values are cut off with an ellipsis
+    #[serde(default)]
+    pub max_column_width: Option<u16>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -126,6 +153,16 @@ fn project_directory() -> Option<ProjectDirs> {
 #[derive(Clone, Debug, Default)]
 pub struct KeyBindings(pub HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>);
 
+impl KeyBindings {
+    /// Whether the key is bound to an action on its own in the given mode. Components accepting
+    /// free text input use this to leave bound keys to the keymap.
+    pub fn is_bound(&self, mode: Mode, key: &KeyEvent) -> bool {
+        self.0
+            .get(&mode)
+            .is_some_and(|keymap| keymap.contains_key(&vec![*key]))
+    }
+}
+
 impl<'de> Deserialize<'de> for KeyBindings {
     fn deserialize<D>(deserializer: D) -> color_eyre::Result<Self, D::Error>
     where
@@ -1,2 +1,5 @@
 pub mod connection;
+pub mod decode;
+pub mod params;
 pub mod system_query;
+pub mod value;
@@ -1,16 +1,15 @@
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use color_eyre::Result;
-use color_eyre::eyre::bail;
+use color_eyre::eyre::eyre;
 use serde::{Deserialize, Serialize};
-use sqlx::postgres::types::PgTimeTz;
-use sqlx::postgres::{PgColumn, PgRow};
+use sqlx::postgres::{PgArguments, PgColumn, PgRow, PgTypeInfo, PgTypeKind, PgValueFormat};
+use sqlx::query::Query;
 use sqlx::{
     Column, PgPool,
     postgres::{PgConnectOptions, PgPoolOptions},
 };
-use sqlx::{Postgres, Row};
-use time::{Time, UtcOffset};
-use uuid::Uuid;
+use sqlx::{Either, Executor, Postgres, Row, Statement, TypeInfo, ValueRef};
+
+use crate::database::{decode, params::cast_placeholders, value::Value};
 
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct ConnectionConfig {
@@ -29,7 +28,16 @@ pub struct DbConnection {
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
-    pub rows: Vec<Vec<Option<String>>>,
+    pub rows: Vec<Vec<Value>>,
+    /// The table column each result column was read from, if it isn't an expression
+    pub origins: Vec<Option<ColumnOrigin>>,
+}
+
+/// Identifies a table column by its table's OID and its position in the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnOrigin {
+    pub relation_id: u32,
+    pub attnum: i16,
 }
 
 impl DbConnection {
@@ -49,18 +57,41 @@ impl DbConnection {
         query: &str,
         binds: Option<Vec<String>>,
     ) -> color_eyre::Result<QueryResult> {
+        let rows = Self::bind(query, binds).fetch_all(&self.pool).await?;
+        Self::query_result(&rows)
+    }
+
+    /// Runs a query in a transaction that is rolled back, so that none of its changes are kept.
+    pub async fn get_rolled_back_result(
+        &self,
+        query: &str,
+        binds: Option<Vec<String>>,
+    ) -> color_eyre::Result<QueryResult> {
+        let mut transaction = self.pool.begin().await?;
+        let rows = Self::bind(query, binds)
+            .fetch_all(&mut *transaction)
+            .await?;
+        transaction.rollback().await?;
+        Self::query_result(&rows)
+    }
+
+    fn bind(query: &str, binds: Option<Vec<String>>) -> Query<'_, Postgres, PgArguments> {
         let mut sql = sqlx::query::<Postgres>(query);
         if let Some(params) = binds {
             for value in params {
                 sql = sql.bind(value)
             }
         }
-        let rows = sql.fetch_all(&self.pool).await?;
+        sql
+    }
+
+    fn query_result(rows: &[PgRow]) -> color_eyre::Result<QueryResult> {
         let mut iter = rows.iter().peekable();
 
         let first_row = iter.peek();
         let mut columns: Vec<String> = vec![];
         let mut col_info: Vec<PgColumn> = vec![];
+        let mut origins: Vec<Option<ColumnOrigin>> = vec![];
 
         // Assume the first row has the same columns as the rest of the rows
         if let Some(row) = first_row {
@@ -68,18 +99,29 @@ impl DbConnection {
             let mut cols: Vec<String> =
                 row.columns().iter().map(|c| c.name().to_string()).collect();
             columns.append(&mut cols);
+            origins = row
+                .columns()
+                .iter()
+                .map(|c| {
+                    Some(ColumnOrigin {
+                        relation_id: c.relation_id()?.0,
+                        attnum: c.relation_attribute_no()?,
+                    })
+                })
+                .collect();
         } else {
             return Ok(QueryResult {
                 rows: Default::default(),
                 columns: Default::default(),
+                origins: Default::default(),
             });
         }
 
-        let mut results: Vec<Vec<Option<String>>> = vec![];
+        let mut results: Vec<Vec<Value>> = vec![];
         for row in iter {
-            let mut r: Vec<Option<String>> = vec![];
+            let mut r: Vec<Value> = vec![];
             for col in row.columns() {
-                r.push(Self::display_value(row, col)?);
+                r.push(Self::decode_value(row, col)?);
             }
             results.push(r);
         }
@@ -87,9 +129,45 @@ impl DbConnection {
         Ok(QueryResult {
             rows: results,
             columns,
+            origins,
         })
     }
 
+    /// Bind values are sent as text, so the placeholders of a user query are cast to the types
+    /// Postgres infers for them. Without this, comparing a text parameter to e.g. an integer
+    /// column is an error. Types are cast to by their qualified catalog name, as SQL names like
+    /// `character` or `bit` imply a length of one and would truncate the values.
+    pub async fn cast_params(&self, query: &str) -> color_eyre::Result<String> {
+        let statement = (&self.pool).prepare(query).await?;
+        let Some(Either::Left(types)) = statement.parameters() else {
+            return Ok(query.to_string());
+        };
+        let oids: Vec<String> = types
+            .iter()
+            .map(|t| match t.name() {
+                // Text needs no cast
+                "TEXT" | "UNKNOWN" => String::from("0"),
+                _ => t.oid().map(|oid| oid.0).unwrap_or_default().to_string(),
+            })
+            .collect();
+        let names: Vec<Option<String>> = sqlx::query_scalar(
+            "
+SELECT
+	quote_ident(nsp.nspname) || '.' || quote_ident(typ.typname)
+FROM
+	unnest($1::oid[]) WITH ORDINALITY AS param(oid, position)
+	LEFT JOIN pg_type typ ON typ.oid = param.oid
+	LEFT JOIN pg_namespace nsp ON nsp.oid = typ.typnamespace
+ORDER BY
+	param.position;
+",
+        )
+        .bind(format!("{{{}}}", oids.join(",")))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(cast_placeholders(query, &names))
+    }
+
     fn make_connection_opts(config: &ConnectionConfig) -> PgConnectOptions {
         let mut options = PgConnectOptions::default();
 
@@ -117,61 +195,145 @@ impl DbConnection {
     }
 
     /// sqlx adds compile-time type safety for database types by connecting to a database at
-    /// compile-time. Since we don't know what the type will be until runtime, we have to check
-    /// possibilities.
-    fn display_value(row: &PgRow, col: &PgColumn) -> Result<Option<String>> {
-        let index = col.ordinal();
-
-        if let Ok(val) = row.try_get::<Option<i16>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<i8>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<i32>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<i64>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<f32>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<f64>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<bigdecimal::BigDecimal>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<&[u8]>, _>(index) {
-            // Postgres bytea type: try utf-8 first for readability but fallback to hex
-            match val {
-                None => Ok(None),
-                Some(bytes) => {
-                    if let Ok(s) = str::from_utf8(bytes) {
-                        Ok(Some(s.to_string()))
-                    } else {
-                        Ok(Some(format!("\\x{}", hex::encode(bytes))))
-                    }
-                }
+    /// compile-time. Since we don't know what the type will be until runtime, values are decoded
+    /// from their binary format based on the OID of their type. Arrays, composites and ranges of
+    /// user-defined types are told apart by their kind, and domains are decoded as their base type.
+    fn decode_value(row: &PgRow, col: &PgColumn) -> Result<Value> {
+        let value = row.try_get_raw(col.ordinal())?;
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+        let mut type_info = value.type_info().into_owned();
+        // Domains are sent in the format of their base type
+        while let PgTypeKind::Domain(base) = type_info.kind() {
+            type_info = base.clone();
+        }
+        let bytes = value.as_bytes().map_err(|e| eyre!(e))?;
+        if value.format() == PgValueFormat::Text {
+            return Ok(Value::Text(String::from_utf8_lossy(bytes).into_owned()));
+        }
+
+        let oid = |info: &PgTypeInfo| info.oid().map(|oid| oid.0).unwrap_or_default();
+        let text = match type_info.kind() {
+            _ if oid(&type_info) < decode::FIRST_NORMAL_OID => {
+                decode::display(oid(&type_info), bytes)
             }
-        } else if let Ok(val) = row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(index) {
-            Ok(val.map(|v| v.to_rfc3339()))
-        } else if let Ok(val) = row.try_get::<Option<chrono::DateTime<chrono::Local>>, _>(index) {
-            Ok(val.map(|v| v.to_rfc3339()))
-        } else if let Ok(val) = row.try_get::<Option<NaiveDateTime>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<NaiveDate>, _>(index) {
-            Ok(val.map(|v| v.format("%Y-%m-%d").to_string()))
-        } else if let Ok(val) = row.try_get::<Option<NaiveTime>, _>(index) {
-            Ok(val.map(|v| v.format("%H:%M:%S%.6f").to_string()))
-        } else if let Ok(val) = row.try_get::<Option<PgTimeTz<Time, UtcOffset>>, _>(index) {
-            Ok(val.map(|v| format!("{} {}", v.time, v.offset)))
-        } else if let Ok(val) = row.try_get::<Option<serde_json::Value>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<bool>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<Vec<String>>, _>(index) {
-            Ok(val.map(|vals| vals.join(",")))
-        } else if let Ok(val) = row.try_get::<Option<Uuid>, _>(index) {
-            Ok(val.map(|v| v.to_string()))
-        } else if let Ok(val) = row.try_get::<Option<String>, _>(index) {
-            Ok(val)
-        } else {
-            bail!(format!("Unsupported type: {}", col.type_info()))
+            PgTypeKind::Array(_) => decode::array(bytes),
+            PgTypeKind::Composite(_) => decode::record(bytes),
+            PgTypeKind::Range(subtype) => decode::range(oid(subtype), bytes),
+            _ => decode::display(oid(&type_info), bytes),
+        };
+        Ok(Value::new(oid(&type_info), bytes, text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    async fn test_connection() -> DbConnection {
+        let var = |name: &str| std::env::var(format!("LAZYDB_TEST_{name}")).ok();
+        DbConnection::create(ConnectionConfig {
+            host: var("HOST"),
+            port: var("PORT").and_then(|port| port.parse().ok()),
+            user: var("USER"),
+            password: var("PASSWORD"),
+            database_name: var("DATABASE"),
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, given by the LAZYDB_TEST_* variables"]
+    async fn test_decode_domains() {
+        let connection = test_connection().await;
+        for statement in [
+            "CREATE DOMAIN pg_temp.positive_int AS integer CHECK (VALUE > 0)",
+            "CREATE DOMAIN pg_temp.price AS numeric(10, 2)",
+            "CREATE DOMAIN pg_temp.small_price AS pg_temp.price",
+            "CREATE TABLE pg_temp.items (quantity pg_temp.positive_int, price pg_temp.small_price)",
+            "INSERT INTO pg_temp.items VALUES (3, 4.5)",
+        ] {
+            connection.get_query_result(statement, None).await.unwrap();
+        }
+
+        let result = connection
+            .get_query_result(
+                "SELECT 5::pg_temp.positive_int, 1.5::pg_temp.price, quantity, price FROM pg_temp.items",
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                Value::Int(5),
+                Value::Numeric(String::from("1.50")),
+                Value::Int(3),
+                Value::Numeric(String::from("4.50")),
+            ]]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, given by the LAZYDB_TEST_* variables"]
+    async fn test_cast_params() {
+        let connection = test_connection().await;
+        for statement in [
+            "CREATE TABLE pg_temp.countries (code char(2), flags bit(3), name text)",
+            "INSERT INTO pg_temp.countries VALUES ('US', B'101', 'United States'), ('UK', B'011', 'United Kingdom')",
+        ] {
+            connection.get_query_result(statement, None).await.unwrap();
         }
+
+        let query = connection
+            .cast_params(
+                "SELECT name FROM pg_temp.countries WHERE code = $1 AND flags = $2 AND name <> $3",
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT name FROM pg_temp.countries WHERE code = $1::pg_catalog.bpchar AND flags = $2::pg_catalog.\"bit\" AND name <> $3"
+        );
+        let result = connection
+            .get_query_result(
+                &query,
+                Some(vec![String::from("US"), String::from("101"), String::new()]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![Value::Text(String::from("United States"))]]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database, given by the LAZYDB_TEST_* variables"]
+    async fn test_rolled_back_explain() {
+        let connection = test_connection().await;
+        connection
+            .get_query_result("CREATE TABLE pg_temp.events (id int)", None)
+            .await
+            .unwrap();
+
+        let query = connection
+            .cast_params("EXPLAIN (FORMAT JSON, ANALYZE) INSERT INTO pg_temp.events VALUES ($1)")
+            .await
+            .unwrap();
+        let plan = connection
+            .get_rolled_back_result(&query, Some(vec![String::from("1")]))
+            .await
+            .unwrap();
+        assert_eq!(plan.rows.len(), 1);
+        let count = connection
+            .get_query_result("SELECT count(*) FROM pg_temp.events", None)
+            .await
+            .unwrap();
+        assert_eq!(count.rows, vec![vec![Value::Int(0)]]);
     }
 }
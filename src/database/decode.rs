@@ -0,0 +1,940 @@
+use std::{
+    fmt::Write,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike};
+use uuid::Uuid;
+
+/// Days from 0001-01-01 to 2000-01-01, the epoch of Postgres dates
+const POSTGRES_EPOCH_DAYS: i32 = 730_120;
+/// Microseconds from the Unix epoch to 2000-01-01, the epoch of Postgres timestamps
+pub const POSTGRES_EPOCH_MICROS: i64 = 946_684_800_000_000;
+const MICROS_PER_SECOND: i64 = 1_000_000;
+/// OIDs below this are assigned to built-in objects, so they are the same on every server
+pub const FIRST_NORMAL_OID: u32 = 16_384;
+
+/// Formats a value received in the binary format the way Postgres prints it as text, given the
+/// OID of its type. Types that aren't built in, like enums and most extension types, and values
+/// that can't be decoded, are shown as text when they are valid UTF-8. Otherwise they are read as
+/// a composite, which user-defined composites nested in arrays or other composites are, or shown
+/// in hex.
+pub fn display(oid: u32, bytes: &[u8]) -> String {
+    decode(oid, bytes).unwrap_or_else(|| fallback(bytes))
+}
+
+/// Formats an array of any element type, e.g. `{1,2,NULL}`.
+pub fn array(bytes: &[u8]) -> String {
+    decode_array(bytes).unwrap_or_else(|| fallback(bytes))
+}
+
+/// Formats a composite value of any type, e.g. `(1,"a b",)`.
+pub fn record(bytes: &[u8]) -> String {
+    decode_record(bytes).unwrap_or_else(|| fallback(bytes))
+}
+
+/// Formats a range given the OID of its subtype, e.g. `[1,10)`.
+pub fn range(subtype: u32, bytes: &[u8]) -> String {
+    decode_range(subtype, bytes).unwrap_or_else(|| fallback(bytes))
+}
+
+fn fallback(bytes: &[u8]) -> String {
+    match str::from_utf8(bytes) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => text.to_string(),
+        _ => decode_record(bytes).unwrap_or_else(|| format!("\\x{}", hex::encode(bytes))),
+    }
+}
+
+fn decode(oid: u32, bytes: &[u8]) -> Option<String> {
+    let mut r = Reader(bytes);
+    let text = match oid {
+        // bool
+        16 => (r.u8()? != 0).to_string(),
+        // bytea, in the hex format Postgres prints by default
+        17 => format!("\\x{}", hex::encode(bytes)),
+        // "char"
+        18 => bytes.iter().map(|b| *b as char).collect(),
+        // name, text, json, xml, pg_node_tree, unknown, bpchar, varchar, refcursor
+        19 | 25 | 114 | 142 | 194 | 705 | 1042 | 1043 | 1790 => str::from_utf8(bytes).ok()?.into(),
+        // jsonb and jsonpath have a version byte before their text
+        3802 | 4072 => {
+            r.take(1)?;
+            str::from_utf8(r.rest()?).ok()?.into()
+        }
+        20 => r.i64()?.to_string(),
+        21 => r.i16()?.to_string(),
+        23 => r.i32()?.to_string(),
+        // oid, xid, cid and the reg* aliases, which are sent as the OID they name
+        24 | 26 | 28 | 29 | 2202..=2206 | 3734 | 3769 | 4089 | 4096 | 4191 => r.u32()?.to_string(),
+        // xid8
+        5069 => r.u64()?.to_string(),
+        // int2vector and oidvector are arrays printed with spaces
+        22 | 30 => {
+            let (_, elements) = array_elements(&mut r)?;
+            let values: Option<Vec<String>> = elements.into_iter().collect();
+            values?.join(" ")
+        }
+        // tid
+        27 => format!("({},{})", r.u32()?, r.u16()?),
+        700 => {
+            let value = r.f32()?;
+            if value.is_finite() {
+                value.to_string()
+            } else {
+                float(value as f64)
+            }
+        }
+        701 => float(r.f64()?),
+        1700 => numeric(&mut r)?,
+        790 => money(r.i64()?),
+        1082 => date(r.i32()?)?,
+        1083 => clock(r.i64()?),
+        1266 => format!("{}{}", clock(r.i64()?), offset(-r.i32()?)),
+        1114 => timestamp(r.i64()?, false)?,
+        1184 => timestamp(r.i64()?, true)?,
+        1186 => {
+            let micros = r.i64()?;
+            interval(r.i32()?, r.i32()?, micros)
+        }
+        2950 => Uuid::from_slice(bytes).ok()?.to_string(),
+        // inet and cidr
+        650 | 869 => inet(&mut r, oid == 650)?,
+        829 | 774 => bytes
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(":"),
+        1560 | 1562 => {
+            let len = r.i32()? as usize;
+            let bits = r.rest()?;
+            (0..len)
+                .map(|i| {
+                    let byte = bits.get(i / 8)?;
+                    Some(if byte & (0x80 >> (i % 8)) != 0 {
+                        '1'
+                    } else {
+                        '0'
+                    })
+                })
+                .collect::<Option<String>>()?
+        }
+        600 => point(&mut r)?,
+        601 => format!("[{},{}]", point(&mut r)?, point(&mut r)?),
+        602 => {
+            let closed = r.u8()? != 0;
+            let points = points(&mut r)?;
+            if closed {
+                format!("({points})")
+            } else {
+                format!("[{points}]")
+            }
+        }
+        603 => format!("{},{}", point(&mut r)?, point(&mut r)?),
+        604 => format!("({})", points(&mut r)?),
+        628 => format!(
+            "{{{},{},{}}}",
+            float(r.f64()?),
+            float(r.f64()?),
+            float(r.f64()?)
+        ),
+        718 => format!("<{},{}>", point(&mut r)?, float(r.f64()?)),
+        3220 => {
+            let lsn = r.u64()?;
+            format!("{:X}/{:X}", lsn >> 32, lsn & 0xffff_ffff)
+        }
+        // txid_snapshot and pg_snapshot
+        2970 | 5038 => {
+            let count = r.i32()?;
+            let (xmin, xmax) = (r.i64()?, r.i64()?);
+            let xips: Option<Vec<String>> =
+                (0..count).map(|_| r.i64().map(|x| x.to_string())).collect();
+            format!("{xmin}:{xmax}:{}", xips?.join(","))
+        }
+        3614 => tsvector(&mut r)?,
+        3615 => {
+            let count = r.i32()?;
+            if count == 0 {
+                String::new()
+            } else {
+                tsquery(&mut r, 0, false)?
+            }
+        }
+        // record
+        2249 => decode_record(bytes)?,
+        // void
+        2278 => String::new(),
+        oid if range_subtype(oid).is_some() => decode_range(range_subtype(oid)?, bytes)?,
+        oid if multirange_subtype(oid).is_some() => {
+            let subtype = multirange_subtype(oid)?;
+            let count = r.i32()?;
+            let ranges: Option<Vec<String>> = (0..count)
+                .map(|_| decode_range(subtype, r.value()??))
+                .collect();
+            format!("{{{}}}", ranges?.join(","))
+        }
+        oid if is_array(oid) => decode_array(bytes)?,
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// Element types of the built-in range types
+fn range_subtype(oid: u32) -> Option<u32> {
+    match oid {
+        3904 => Some(23),
+        3906 => Some(1700),
+        3908 => Some(1114),
+        3910 => Some(1184),
+        3912 => Some(1082),
+        3926 => Some(20),
+        _ => None,
+    }
+}
+
+/// Element types of the built-in multirange types
+fn multirange_subtype(oid: u32) -> Option<u32> {
+    match oid {
+        4451 => Some(23),
+        4532 => Some(1700),
+        4533 => Some(1114),
+        4534 => Some(1184),
+        4535 => Some(1082),
+        4536 => Some(20),
+        _ => None,
+    }
+}
+
+/// Whether the OID is one of the built-in array types. Arrays carry the OID of their elements, so
+/// only the array types themselves need to be known.
+fn is_array(oid: u32) -> bool {
+    matches!(
+        oid,
+        143 | 199
+            | 271
+            | 629
+            | 651
+            | 719
+            | 775
+            | 791
+            | 1000..=1028
+            | 1034
+            | 1040
+            | 1041
+            | 1115
+            | 1182
+            | 1183
+            | 1185
+            | 1187
+            | 1231
+            | 1263
+            | 1270
+            | 1561
+            | 1563
+            | 2201
+            | 2207..=2211
+            | 2287
+            | 2949
+            | 2951
+            | 3221
+            | 3643
+            | 3645
+            | 3735
+            | 3770
+            | 3807
+            | 3905..=3913
+            | 3927
+            | 4073
+            | 4090
+            | 4097
+            | 4192
+            | 5039
+            | 6150..=6157
+    )
+}
+
+/// Reads big-endian values from the binary format.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn rest(&mut self) -> Option<&'a [u8]> {
+        self.take(self.0.len())
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.array::<1>()?[0])
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        Some(i16::from_be_bytes(self.array()?))
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        Some(i64::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_be_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        Some(f64::from_be_bytes(self.array()?))
+    }
+
+    /// A value prefixed with its length, where a length of -1 is `NULL`
+    fn value(&mut self) -> Option<Option<&'a [u8]>> {
+        match self.i32()? {
+            -1 => Some(None),
+            len => Some(Some(self.take(usize::try_from(len).ok()?)?)),
+        }
+    }
+
+    /// A NUL-terminated string
+    fn cstring(&mut self) -> Option<&'a str> {
+        let len = self.0.iter().position(|b| *b == 0)?;
+        let text = str::from_utf8(self.take(len)?).ok()?;
+        self.take(1)?;
+        Some(text)
+    }
+}
+
+fn float(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "Infinity" } else { "-Infinity" })
+    } else {
+        value.to_string()
+    }
+}
+
+/// Numeric values are sent as base 10000 digits, with the weight of the first digit and the
+/// number of decimal digits to show.
+fn numeric(r: &mut Reader) -> Option<String> {
+    let ndigits = usize::try_from(r.i16()?).ok()?;
+    let weight = r.i16()? as i32;
+    let sign = r.u16()?;
+    let scale = r.u16()? as usize;
+    match sign {
+        0xC000 => return Some(String::from("NaN")),
+        0xD000 => return Some(String::from("Infinity")),
+        0xF000 => return Some(String::from("-Infinity")),
+        _ => {}
+    }
+    let digits: Vec<i16> = (0..ndigits).map(|_| r.i16()).collect::<Option<_>>()?;
+    let digit = |idx: i32| {
+        usize::try_from(idx)
+            .ok()
+            .and_then(|idx| digits.get(idx))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    }
+    for idx in 0..=weight {
+        if idx == 0 {
+            write!(text, "{}", digit(idx)).ok()?;
+        } else {
+            write!(text, "{:04}", digit(idx)).ok()?;
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut idx = weight + 1;
+        while fraction.len() < scale {
+            write!(fraction, "{:04}", digit(idx)).ok()?;
+            idx += 1;
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Some(text)
+}
+
+/// Money is sent in cents, shown as in the C locale
+fn money(cents: i64) -> String {
+    let units = (cents / 100).unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (idx, digit) in units.chars().enumerate() {
+        if idx > 0 && (units.len() - idx).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{sign}${grouped}.{:02}", (cents % 100).unsigned_abs())
+}
+
+/// The year, with years before the common era counted backwards as Postgres does
+fn era(year: i32) -> (i32, bool) {
+    if year <= 0 {
+        (1 - year, true)
+    } else {
+        (year, false)
+    }
+}
+
+fn date(days: i32) -> Option<String> {
+    match days {
+        i32::MAX => return Some(String::from("infinity")),
+        i32::MIN => return Some(String::from("-infinity")),
+        _ => {}
+    }
+    let date = NaiveDate::from_num_days_from_ce_opt(days.checked_add(POSTGRES_EPOCH_DAYS)?)?;
+    let (year, bc) = era(date.year());
+    let suffix = if bc { " BC" } else { "" };
+    Some(format!(
+        "{year:04}-{:02}-{:02}{suffix}",
+        date.month(),
+        date.day()
+    ))
+}
+
+/// Formats a time of day or a duration in microseconds as `HH:MM:SS`, with the fraction of a
+/// second when there is one. Hours are not wrapped.
+fn clock(micros: i64) -> String {
+    let sign = if micros < 0 { "-" } else { "" };
+    let micros = micros.unsigned_abs();
+    let seconds = micros / MICROS_PER_SECOND as u64;
+    let mut text = format!(
+        "{sign}{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+    let fraction = micros % MICROS_PER_SECOND as u64;
+    if fraction != 0 {
+        text.push_str(format!(".{fraction:06}").trim_end_matches('0'));
+    }
+    text
+}
+
+/// Formats an offset east of UTC like `+02`, `-03:30` or `+05:45:30`.
+fn offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.unsigned_abs();
+    let mut text = format!("{sign}{:02}", seconds / 3600);
+    if !seconds.is_multiple_of(3600) {
+        write!(text, ":{:02}", seconds % 3600 / 60).ok();
+    }
+    if !seconds.is_multiple_of(60) {
+        write!(text, ":{:02}", seconds % 60).ok();
+    }
+    text
+}
+
+/// Formats a timestamp in microseconds since 2000-01-01. Without a query for the session's time
+/// zone, timestamps with time zones are shown in UTC.
+pub fn timestamp(micros: i64, zoned: bool) -> Option<String> {
+    match micros {
+        i64::MAX => return Some(String::from("infinity")),
+        i64::MIN => return Some(String::from("-infinity")),
+        _ => {}
+    }
+    let time = DateTime::from_timestamp_micros(micros.checked_add(POSTGRES_EPOCH_MICROS)?)?;
+    let (year, bc) = era(time.year());
+    let of_day = time.num_seconds_from_midnight() as i64 * MICROS_PER_SECOND
+        + time.timestamp_subsec_micros() as i64;
+    let zone = if zoned { "+00" } else { "" };
+    let suffix = if bc { " BC" } else { "" };
+    Some(format!(
+        "{year:04}-{:02}-{:02} {}{zone}{suffix}",
+        time.month(),
+        time.day(),
+        clock(of_day)
+    ))
+}
+
+/// Formats an interval in the default `postgres` style, e.g. `1 year 2 mons 3 days 04:05:06`.
+fn interval(days: i32, months: i32, micros: i64) -> String {
+    let plural = |n: i32, unit: &str| {
+        if n == 1 {
+            format!("{n} {unit}")
+        } else {
+            format!("{n} {unit}s")
+        }
+    };
+    let mut parts = vec![];
+    if months / 12 != 0 {
+        parts.push(plural(months / 12, "year"));
+    }
+    if months % 12 != 0 {
+        parts.push(plural(months % 12, "mon"));
+    }
+    if days != 0 {
+        parts.push(plural(days, "day"));
+    }
+    if micros != 0 || parts.is_empty() {
+        // The sign of the time is only shown when negative, unless it follows a negative part
+        let after_negative = (months < 0 || days < 0) && micros > 0;
+        let sign = if after_negative { "+" } else { "" };
+        parts.push(format!("{sign}{}", clock(micros)));
+    }
+    parts.join(" ")
+}
+
+fn inet(r: &mut Reader, cidr: bool) -> Option<String> {
+    let family = r.u8()?;
+    let bits = r.u8()?;
+    let _is_cidr = r.u8()?;
+    let len = r.u8()? as usize;
+    let address = r.take(len)?;
+    let (address, max_bits) = match family {
+        2 => (
+            Ipv4Addr::from(<[u8; 4]>::try_from(address).ok()?).to_string(),
+            32,
+        ),
+        3 => (
+            Ipv6Addr::from(<[u8; 16]>::try_from(address).ok()?).to_string(),
+            128,
+        ),
+        _ => return None,
+    };
+    Some(if cidr || bits != max_bits {
+        format!("{address}/{bits}")
+    } else {
+        address
+    })
+}
+
+fn point(r: &mut Reader) -> Option<String> {
+    Some(format!("({},{})", float(r.f64()?), float(r.f64()?)))
+}
+
+fn points(r: &mut Reader) -> Option<String> {
+    let count = r.i32()?;
+    let points: Option<Vec<String>> = (0..count).map(|_| point(r)).collect();
+    Some(points?.join(","))
+}
+
+/// Lexemes with their positions and weights, e.g. `'fat':2A 'rat':3`
+fn tsvector(r: &mut Reader) -> Option<String> {
+    let count = r.i32()?;
+    let mut lexemes = vec![];
+    for _ in 0..count {
+        let lexeme = r.cstring()?.replace('\\', "\\\\").replace('\'', "''");
+        let positions: Option<Vec<String>> = (0..r.u16()?)
+            .map(|_| {
+                let position = r.u16()?;
+                let weight = match position >> 14 {
+                    3 => "A",
+                    2 => "B",
+                    1 => "C",
+                    _ => "",
+                };
+                Some(format!("{}{weight}", position & 0x3fff))
+            })
+            .collect();
+        let positions = positions?;
+        if positions.is_empty() {
+            lexemes.push(format!("'{lexeme}'"));
+        } else {
+            lexemes.push(format!("'{lexeme}':{}", positions.join(",")));
+        }
+    }
+    Some(lexemes.join(" "))
+}
+
+/// Formats the next node of a query, whose items are stored with each operator followed by its
+/// right operand and then its left one. Operands of a lower priority than their operator are
+/// parenthesized, as are phrases on the right of another phrase.
+fn tsquery(r: &mut Reader, parent_priority: u8, right: bool) -> Option<String> {
+    const NOT: u8 = 1;
+    const AND: u8 = 2;
+    const OR: u8 = 3;
+    const PHRASE: u8 = 4;
+
+    match r.u8()? {
+        // Operand
+        1 => {
+            let weight = r.u8()?;
+            let prefix = r.u8()? != 0;
+            let lexeme = r.cstring()?.replace('\\', "\\\\").replace('\'', "''");
+            let mut text = format!("'{lexeme}'");
+            if prefix || weight != 0 {
+                text.push(':');
+                if prefix {
+                    text.push('*');
+                }
+                for (bit, letter) in [(8, 'A'), (4, 'B'), (2, 'C'), (1, 'D')] {
+                    if weight & bit != 0 {
+                        text.push(letter);
+                    }
+                }
+            }
+            Some(text)
+        }
+        // Operator
+        2 => {
+            let operator = r.u8()?;
+            let distance = if operator == PHRASE { r.i16()? } else { 0 };
+            let priority = match operator {
+                NOT => 4,
+                PHRASE => 3,
+                AND => 2,
+                OR => 1,
+                _ => return None,
+            };
+            let text = if operator == NOT {
+                format!("!{}", tsquery(r, priority, false)?)
+            } else {
+                let right = tsquery(r, priority, true)?;
+                let left = tsquery(r, priority, false)?;
+                let symbol = match operator {
+                    AND => String::from("&"),
+                    OR => String::from("|"),
+                    _ if distance == 1 => String::from("<->"),
+                    _ => format!("<{distance}>"),
+                };
+                format!("{left} {symbol} {right}")
+            };
+            let parenthesize = priority < parent_priority
+                || (right && operator == PHRASE && parent_priority == priority);
+            Some(if parenthesize {
+                format!("( {text} )")
+            } else {
+                text
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Length and lower bound of a dimension of an array
+type Dimension = (i32, i32);
+
+/// Reads the dimensions and the formatted elements of an array, in row-major order.
+fn array_elements(r: &mut Reader) -> Option<(Vec<Dimension>, Vec<Option<String>>)> {
+    let ndim = r.i32()?;
+    let _has_nulls = r.i32()?;
+    let element = r.u32()?;
+    let dims: Vec<Dimension> = (0..ndim)
+        .map(|_| Some((r.i32()?, r.i32()?)))
+        .collect::<Option<_>>()?;
+    let count: i32 = if dims.is_empty() {
+        0
+    } else {
+        dims.iter().map(|(len, _)| *len).product()
+    };
+    let elements = (0..count)
+        .map(|_| Some(r.value()?.map(|bytes| display(element, bytes))))
+        .collect::<Option<_>>()?;
+    Some((dims, elements))
+}
+
+fn decode_array(bytes: &[u8]) -> Option<String> {
+    let mut r = Reader(bytes);
+    // Boxes contain commas, so their arrays are delimited by semicolons
+    let delimiter = if bytes.get(8..12) == Some(&603u32.to_be_bytes()) {
+        ';'
+    } else {
+        ','
+    };
+    let (dims, elements) = array_elements(&mut r)?;
+    let mut text = String::new();
+    // Lower bounds other than 1 are written before the elements, e.g. `[0:1]={1,2}`
+    if dims.iter().any(|(_, lower)| *lower != 1) {
+        for (len, lower) in &dims {
+            write!(text, "[{lower}:{}]", lower + len - 1).ok()?;
+        }
+        text.push('=');
+    }
+    let mut elements = elements.into_iter();
+    write_dimension(&mut text, &dims, &mut elements, delimiter);
+    Some(text)
+}
+
+fn write_dimension(
+    text: &mut String,
+    dims: &[Dimension],
+    elements: &mut impl Iterator<Item = Option<String>>,
+    delimiter: char,
+) {
+    text.push('{');
+    if let Some(((len, _), inner)) = dims.split_first() {
+        for idx in 0..*len {
+            if idx > 0 {
+                text.push(delimiter);
+            }
+            if inner.is_empty() {
+                match elements.next().flatten() {
+                    Some(element) => text.push_str(&quote_array_element(&element, delimiter)),
+                    None => text.push_str("NULL"),
+                }
+            } else {
+                write_dimension(text, inner, elements, delimiter);
+            }
+        }
+    }
+    text.push('}');
+}
+
+fn quote_array_element(element: &str, delimiter: char) -> String {
+    let needs_quotes = element.is_empty()
+        || element.eq_ignore_ascii_case("NULL")
+        || element
+            .chars()
+            .any(|c| matches!(c, '{' | '}' | '"' | '\\') || c == delimiter || c.is_whitespace());
+    if needs_quotes {
+        format!("\"{}\"", element.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        element.to_string()
+    }
+}
+
+/// Quotes a field of a record or a bound of a range when needed, doubling quotes and backslashes.
+fn quote_field(field: &str, special: &[char]) -> String {
+    let needs_quotes = field.is_empty()
+        || field
+            .chars()
+            .any(|c| special.contains(&c) || matches!(c, '"' | '\\') || c.is_whitespace());
+    if needs_quotes {
+        format!("\"{}\"", field.replace('\\', "\\\\").replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn decode_record(bytes: &[u8]) -> Option<String> {
+    let mut r = Reader(bytes);
+    let count = r.i32()?;
+    let mut fields = vec![];
+    for _ in 0..count {
+        let oid = r.u32()?;
+        // A NULL field is left empty
+        fields.push(match r.value()? {
+            Some(bytes) => quote_field(&display(oid, bytes), &['(', ')', ',']),
+            None => String::new(),
+        });
+    }
+    // Anything left over means it wasn't a composite
+    if !r.0.is_empty() {
+        return None;
+    }
+    Some(format!("({})", fields.join(",")))
+}
+
+fn decode_range(subtype: u32, bytes: &[u8]) -> Option<String> {
+    const EMPTY: u8 = 0x01;
+    const LOWER_INCLUSIVE: u8 = 0x02;
+    const UPPER_INCLUSIVE: u8 = 0x04;
+    const LOWER_INFINITE: u8 = 0x08;
+    const UPPER_INFINITE: u8 = 0x10;
+
+    let mut r = Reader(bytes);
+    let flags = r.u8()?;
+    if flags & EMPTY != 0 {
+        return Some(String::from("empty"));
+    }
+    let mut bound = |infinite: u8| -> Option<String> {
+        if flags & infinite != 0 {
+            return Some(String::new());
+        }
+        let len = usize::try_from(r.i32()?).ok()?;
+        let value = display(subtype, r.take(len)?);
+        Some(quote_field(&value, &['(', ')', '[', ']', ',']))
+    };
+    let lower = bound(LOWER_INFINITE)?;
+    let upper = bound(UPPER_INFINITE)?;
+    Some(format!(
+        "{}{lower},{upper}{}",
+        if flags & LOWER_INCLUSIVE != 0 {
+            '['
+        } else {
+            '('
+        },
+        if flags & UPPER_INCLUSIVE != 0 {
+            ']'
+        } else {
+            ')'
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Concatenates big-endian fields into a binary value
+    fn bytes(fields: &[&[u8]]) -> Vec<u8> {
+        fields.concat()
+    }
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(display(23, &42i32.to_be_bytes()), "42");
+        assert_eq!(display(701, &f64::NEG_INFINITY.to_be_bytes()), "-Infinity");
+        assert_eq!(
+            display(790, &(-123456789i64).to_be_bytes()),
+            "-$1,234,567.89"
+        );
+        // 12345.6700: digits 1, 2345, 6700 with a weight of 1 and a scale of 4
+        let numeric = bytes(&[
+            &3i16.to_be_bytes(),
+            &1i16.to_be_bytes(),
+            &0x4000u16.to_be_bytes(),
+            &4u16.to_be_bytes(),
+            &1i16.to_be_bytes(),
+            &2345i16.to_be_bytes(),
+            &6700i16.to_be_bytes(),
+        ]);
+        assert_eq!(display(1700, &numeric), "-12345.6700");
+        // 0.05 has a weight of -1
+        let small = bytes(&[
+            &1i16.to_be_bytes(),
+            &(-1i16).to_be_bytes(),
+            &0u16.to_be_bytes(),
+            &2u16.to_be_bytes(),
+            &500i16.to_be_bytes(),
+        ]);
+        assert_eq!(display(1700, &small), "0.05");
+        let inet = [2, 24, 0, 4, 192, 168, 1, 0];
+        assert_eq!(display(869, &inet), "192.168.1.0/24");
+        assert_eq!(display(650, &[2, 32, 1, 4, 10, 0, 0, 1]), "10.0.0.1/32");
+        assert_eq!(
+            display(1560, &bytes(&[&5i32.to_be_bytes(), &[0b1010_1000]])),
+            "10101"
+        );
+        // bytea is hex even when it happens to be valid UTF-8
+        assert_eq!(display(17, b"hi\x00"), "\\x686900");
+        assert_eq!(display(17, b""), "\\x");
+    }
+
+    #[test]
+    fn test_dates_and_times() {
+        assert_eq!(display(1082, &0i32.to_be_bytes()), "2000-01-01");
+        assert_eq!(
+            display(1082, &(-730_120i32 - 365).to_be_bytes()),
+            "0001-01-01 BC"
+        );
+        let micros = (13 * 3600 + 5 * 60 + 7) * MICROS_PER_SECOND + 250_000;
+        assert_eq!(display(1083, &micros.to_be_bytes()), "13:05:07.25");
+        assert_eq!(
+            display(1184, &(86_400 * MICROS_PER_SECOND).to_be_bytes()),
+            "2000-01-02 00:00:00+00"
+        );
+        assert_eq!(display(1114, &i64::MAX.to_be_bytes()), "infinity");
+        let timetz = bytes(&[&micros.to_be_bytes(), &(-19_800i32).to_be_bytes()]);
+        assert_eq!(display(1266, &timetz), "13:05:07.25+05:30");
+        let interval = bytes(&[
+            &(4 * 3600 * MICROS_PER_SECOND).to_be_bytes(),
+            &3i32.to_be_bytes(),
+            &14i32.to_be_bytes(),
+        ]);
+        assert_eq!(display(1186, &interval), "1 year 2 mons 3 days 04:00:00");
+        assert_eq!(display(1186, &[0; 16]), "00:00:00");
+    }
+
+    #[test]
+    fn test_arrays() {
+        let text = |s: &str| bytes(&[&(s.len() as i32).to_be_bytes(), s.as_bytes()]);
+        // A 2x2 text array with a NULL and elements that need quotes
+        let array = bytes(&[
+            &2i32.to_be_bytes(),
+            &1i32.to_be_bytes(),
+            &25u32.to_be_bytes(),
+            &2i32.to_be_bytes(),
+            &1i32.to_be_bytes(),
+            &2i32.to_be_bytes(),
+            &1i32.to_be_bytes(),
+            &text("a"),
+            &text("b c"),
+            &(-1i32).to_be_bytes(),
+            &text("say \"hi\""),
+        ]);
+        assert_eq!(display(1009, &array), r#"{{a,"b c"},{NULL,"say \"hi\""}}"#);
+        // An int4 array starting at index 0
+        let array = bytes(&[
+            &1i32.to_be_bytes(),
+            &0i32.to_be_bytes(),
+            &23u32.to_be_bytes(),
+            &1i32.to_be_bytes(),
+            &0i32.to_be_bytes(),
+            &4i32.to_be_bytes(),
+            &7i32.to_be_bytes(),
+        ]);
+        assert_eq!(display(1007, &array), "[0:0]={7}");
+        let empty = bytes(&[
+            &0i32.to_be_bytes(),
+            &0i32.to_be_bytes(),
+            &23u32.to_be_bytes(),
+        ]);
+        assert_eq!(super::array(&empty), "{}");
+    }
+
+    #[test]
+    fn test_ranges_and_records() {
+        let int = |n: i32| bytes(&[&4i32.to_be_bytes(), &n.to_be_bytes()]);
+        let range = bytes(&[&[0x02], &int(1), &int(10)]);
+        assert_eq!(display(3904, &range), "[1,10)");
+        assert_eq!(display(3904, &[0x01]), "empty");
+        let unbounded = bytes(&[&[0x02 | 0x10], &int(5)]);
+        assert_eq!(display(3904, &unbounded), "[5,)");
+        let multirange = bytes(&[
+            &2i32.to_be_bytes(),
+            &(range.len() as i32).to_be_bytes(),
+            &range,
+            &(unbounded.len() as i32).to_be_bytes(),
+            &unbounded,
+        ]);
+        assert_eq!(display(4451, &multirange), "{[1,10),[5,)}");
+
+        let record = bytes(&[
+            &3i32.to_be_bytes(),
+            &23u32.to_be_bytes(),
+            &int(1),
+            &25u32.to_be_bytes(),
+            &3i32.to_be_bytes(),
+            b"a,b",
+            &25u32.to_be_bytes(),
+            &(-1i32).to_be_bytes(),
+        ]);
+        assert_eq!(display(2249, &record), r#"(1,"a,b",)"#);
+    }
+
+    #[test]
+    fn test_fallback() {
+        // Enum labels and other unknown types sent as text
+        assert_eq!(display(16_385, b"happy"), "happy");
+        assert_eq!(display(16_385, &[0, 0, 0, 1, 0xff]), "\\x00000001ff");
+        // Truncated values aren't decoded
+        assert_eq!(display(20, &[1, 2]), "\\x0102");
+    }
+}
@@ -0,0 +1,290 @@
+use std::ops::Range;
+
+/// A bind-variable placeholder found in a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Placeholder {
+    /// `$1`
+    Positional(usize),
+    /// `:name`
+    Named(String),
+    /// `?`
+    Anonymous,
+}
+
+/// A user query with its placeholders rewritten to the positional `$n` form Postgres expects.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryParameters {
+    /// The query text using only `$n` placeholders
+    pub query: String,
+    /// Display name of each parameter, where index `n - 1` names `$n`
+    pub names: Vec<String>,
+}
+
+impl QueryParameters {
+    /// Finds the `$1`, `:name` and `?` placeholders in a query, ignoring those in string
+    /// literals, quoted identifiers and comments. Explicit `$n` placeholders keep their position,
+    /// and the others are numbered after them in order of appearance, with repeated names sharing
+    /// a position.
+    pub fn parse(query: &str) -> Self {
+        let placeholders = scan(query);
+        let max_positional = placeholders
+            .iter()
+            .filter_map(|(_, p)| match p {
+                Placeholder::Positional(n) => Some(*n),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let mut names: Vec<String> = (1..=max_positional).map(|n| format!("${n}")).collect();
+        let mut rewritten = String::with_capacity(query.len());
+        let mut last = 0;
+        let mut anonymous = 0;
+        for (range, placeholder) in placeholders {
+            let position = match placeholder {
+                Placeholder::Positional(n) => n,
+                Placeholder::Named(name) => {
+                    let name = format!(":{name}");
+                    match names.iter().position(|n| n == &name) {
+                        Some(idx) => idx + 1,
+                        None => {
+                            names.push(name);
+                            names.len()
+                        }
+                    }
+                }
+                Placeholder::Anonymous => {
+                    anonymous += 1;
+                    names.push(format!("?{anonymous}"));
+                    names.len()
+                }
+            };
+            rewritten.push_str(&query[last..range.start]);
+            rewritten.push_str(&format!("${position}"));
+            last = range.end;
+        }
+        rewritten.push_str(&query[last..]);
+
+        Self {
+            query: rewritten,
+            names,
+        }
+    }
+}
+
+/// Appends a cast after each `$n` placeholder of a query, where index `n - 1` of `types` holds the
+/// type name for `$n`. Placeholders without a type are left as is.
+pub fn cast_placeholders(query: &str, types: &[Option<String>]) -> String {
+    let mut casted = String::with_capacity(query.len());
+    let mut last = 0;
+    for (range, placeholder) in scan(query) {
+        if let Placeholder::Positional(n) = placeholder
+            && let Some(Some(type_name)) = types.get(n - 1)
+        {
+            casted.push_str(&query[last..range.end]);
+            casted.push_str(&format!("::{type_name}"));
+            last = range.end;
+        }
+    }
+    casted.push_str(&query[last..]);
+    casted
+}
+
+/// The statement without the semicolons, comments and whitespace it ends with, which would break
+/// it when wrapped, e.g. in `EXPLAIN`.
+pub fn trim_statement(query: &str) -> &str {
+    let bytes = query.as_bytes();
+    let mut end = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some((next, comment)) = skip_quoted(query, i) {
+            if !comment {
+                end = next;
+            }
+            i = next;
+            continue;
+        }
+        if !bytes[i].is_ascii_whitespace() && bytes[i] != b';' {
+            end = i + 1;
+        }
+        i += 1;
+    }
+    &query[..end]
+}
+
+fn is_ident(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// When a literal, quoted identifier or comment starts at `i`, the index after it and whether it
+/// is a comment. Unterminated ones run to the end of the query.
+fn skip_quoted(query: &str, mut i: usize) -> Option<(usize, bool)> {
+    let bytes = query.as_bytes();
+    let prev = if i > 0 { Some(bytes[i - 1]) } else { None };
+    match bytes[i] {
+        b'\'' | b'"' => {
+            // Quotes are escaped by doubling, which this handles as two adjacent literals.
+            let quote = bytes[i];
+            i += 1;
+            while i < bytes.len() && bytes[i] != quote {
+                i += 1;
+            }
+            Some(((i + 1).min(bytes.len()), false))
+        }
+        b'-' if bytes.get(i + 1) == Some(&b'-') => {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            Some((i, true))
+        }
+        b'/' if bytes.get(i + 1) == Some(&b'*') => {
+            let mut depth = 0;
+            while i < bytes.len() {
+                if bytes[i..].starts_with(b"/*") {
+                    depth += 1;
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            Some((i.min(bytes.len()), true))
+        }
+        // Dollar-quoted string, e.g. $$text$$ or $tag$text$tag$
+        b'$' if !prev.is_some_and(is_ident) => {
+            let tag_len = bytes[i + 1..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                .count();
+            let tag_end = i + 1 + tag_len;
+            if bytes.get(tag_end) != Some(&b'$') || bytes[i + 1].is_ascii_digit() {
+                return None;
+            }
+            let tag = &query[i..=tag_end];
+            let end = match query[tag_end + 1..].find(tag) {
+                Some(end) => tag_end + 1 + end + tag.len(),
+                None => bytes.len(),
+            };
+            Some((end, false))
+        }
+        _ => None,
+    }
+}
+
+/// Lexes just enough SQL to find placeholders outside of literals and comments.
+fn scan(query: &str) -> Vec<(Range<usize>, Placeholder)> {
+    let bytes = query.as_bytes();
+    let mut found = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if let Some((next, _)) = skip_quoted(query, i) {
+            i = next;
+            continue;
+        }
+        let prev = if i > 0 { Some(bytes[i - 1]) } else { None };
+        match bytes[i] {
+            b'$' if !prev.is_some_and(is_ident) => {
+                let start = i;
+                i += 1;
+                let digits_end = i + bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+                let n = query[i..digits_end].parse().unwrap_or(0);
+                if n > 0 {
+                    found.push((start..digits_end, Placeholder::Positional(n)));
+                }
+                i = digits_end;
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+            b':' if !prev.is_some_and(is_ident) => {
+                let start = i;
+                i += 1;
+                let name_len = bytes[i..].iter().take_while(|b| is_ident(**b)).count();
+                if name_len > 0 && !bytes[i].is_ascii_digit() {
+                    let name = query[i..i + name_len].to_string();
+                    found.push((start..i + name_len, Placeholder::Named(name)));
+                    i += name_len;
+                }
+            }
+            b'?' => {
+                // `?|`, `?&` and `? 'key'` are jsonb operators rather than placeholders.
+                let next = query[i + 1..].trim_start().bytes().next();
+                if !matches!(bytes.get(i + 1), Some(b'|' | b'&')) && next != Some(b'\'') {
+                    found.push((i..i + 1, Placeholder::Anonymous));
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_positional() {
+        let params = QueryParameters::parse("SELECT * FROM t WHERE a = $1 AND b = $2 OR c = $1");
+        assert_eq!(
+            params.query,
+            "SELECT * FROM t WHERE a = $1 AND b = $2 OR c = $1"
+        );
+        assert_eq!(params.names, vec!["$1", "$2"]);
+    }
+
+    #[test]
+    fn test_named_and_anonymous() {
+        let params = QueryParameters::parse("SELECT :id, ?, :name, :id, ?");
+        assert_eq!(params.query, "SELECT $1, $2, $3, $1, $4");
+        assert_eq!(params.names, vec![":id", "?1", ":name", "?2"]);
+    }
+
+    #[test]
+    fn test_mixed_numbers_after_positional() {
+        let params = QueryParameters::parse("SELECT :a, $2");
+        assert_eq!(params.query, "SELECT $3, $2");
+        assert_eq!(params.names, vec!["$1", "$2", ":a"]);
+    }
+
+    #[test]
+    fn test_ignores_literals_comments_and_casts() {
+        let query = "SELECT ':a', \"$1\", $$ ? $$, $f$ :b $f$, x::int, arr[1:n], data ? 'k', \
+                     data ?| array['k'] -- :c\n/* $2 /* :d */ ? */ FROM t";
+        let params = QueryParameters::parse(query);
+        assert_eq!(params.query, query);
+        assert!(params.names.is_empty());
+    }
+
+    #[test]
+    fn test_cast_placeholders() {
+        let query = "SELECT $1, '$2', $2";
+        assert_eq!(
+            cast_placeholders(query, &[Some("INT4".to_string()), None]),
+            "SELECT $1::INT4, '$2', $2"
+        );
+    }
+
+    #[test]
+    fn test_trim_statement() {
+        assert_eq!(trim_statement("SELECT 1;"), "SELECT 1");
+        assert_eq!(
+            trim_statement("SELECT 1; -- done\n /* really */ ;\n"),
+            "SELECT 1"
+        );
+        assert_eq!(
+            trim_statement("-- first\nSELECT ';' -- 2\n"),
+            "-- first\nSELECT ';'"
+        );
+        assert_eq!(trim_statement("SELECT $$ -- $$"), "SELECT $$ -- $$");
+        assert_eq!(trim_statement("SELECT 'é' ;"), "SELECT 'é'");
+        assert_eq!(trim_statement("-- nothing"), "");
+    }
+}
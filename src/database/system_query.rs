@@ -3,7 +3,7 @@ use color_eyre::eyre::bail;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::app_event::QueryTag;
+use crate::{app_event::QueryTag, database::params::trim_statement};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Query {
@@ -12,29 +12,136 @@ pub struct Query {
     pub binds: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Table {
     pub schema: String,
     pub name: String,
 }
 
+impl Table {
+    /// The schema-qualified name, quoted for use in a statement.
+    pub fn quoted(&self) -> String {
+        format!("{}.{}", quote_ident(&self.schema), quote_ident(&self.name))
+    }
+}
+
+/// Rows of a table referenced by a foreign key, with the key's values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    pub table: Table,
+    /// Name and type of each referenced column
+    pub columns: Vec<(String, String)>,
+    pub values: Vec<String>,
+}
+
+/// A new value for a column of the row of a table identified by its primary key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellUpdate {
+    /// The primary key of the row, with its values
+    pub row: Reference,
+    /// Name and type of the updated column
+    pub column: (String, String),
+    pub value: String,
+}
+
+/// A function or procedure, identified by its input argument types, e.g. `integer, text`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Function {
+    pub schema: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
 pub struct SystemQuery {}
 
 impl SystemQuery {
     pub fn query_for(tag: QueryTag) -> Result<Query> {
         match tag.clone() {
             QueryTag::ListTables => {
+                // Every object shown in the sidebar, with the input argument types that tell
+                // overloaded functions apart. Tables and materialized views have their total size
+                // and estimated row count, summed over the partitions of partitioned tables.
                 let query = String::from(
                     "
 SELECT
-	table_schema,
-	table_name
-FROM
-	information_schema.tables
+	schema,
+	name,
+	kind,
+	arguments,
+	size,
+	estimated_rows
+FROM (
+	SELECT
+		nsp.nspname AS schema,
+		cls.relname AS name,
+		CASE cls.relkind
+			WHEN 'v' THEN 'view'
+			WHEN 'm' THEN 'materialized view'
+			WHEN 'S' THEN 'sequence'
+			ELSE 'table'
+		END AS kind,
+		'' AS arguments,
+		CASE
+			WHEN cls.relkind IN ('r', 'm') THEN pg_total_relation_size(cls.oid)
+			WHEN cls.relkind = 'p' THEN (
+				SELECT sum(pg_total_relation_size(part.relid))::int8
+				FROM pg_partition_tree(cls.oid) part
+			)
+		END AS size,
+		CASE
+			WHEN cls.relkind IN ('r', 'm') AND cls.reltuples >= 0 THEN cls.reltuples::int8
+			WHEN cls.relkind = 'p' THEN (
+				SELECT sum(leaf.reltuples)::int8
+				FROM pg_partition_tree(cls.oid) part
+				JOIN pg_class leaf ON leaf.oid = part.relid
+				WHERE part.isleaf AND leaf.reltuples >= 0
+			)
+		END AS estimated_rows
+	FROM
+		pg_class cls
+		JOIN pg_namespace nsp ON nsp.oid = cls.relnamespace
+	WHERE
+		cls.relkind IN ('r', 'p', 'f', 'v', 'm', 'S')
+	UNION ALL
+	SELECT
+		nsp.nspname,
+		pro.proname,
+		'function',
+		oidvectortypes(pro.proargtypes),
+		NULL,
+		NULL
+	FROM
+		pg_proc pro
+		JOIN pg_namespace nsp ON nsp.oid = pro.pronamespace
+	WHERE
+		pro.prokind IN ('f', 'p')
+		AND NOT EXISTS (SELECT FROM pg_depend dep WHERE dep.objid = pro.oid AND dep.deptype IN ('e', 'i'))
+	UNION ALL
+	SELECT
+		nsp.nspname,
+		typ.typname,
+		'type',
+		'',
+		NULL,
+		NULL
+	FROM
+		pg_type typ
+		JOIN pg_namespace nsp ON nsp.oid = typ.typnamespace
+		LEFT JOIN pg_class cls ON cls.oid = typ.typrelid
+	WHERE
+		(typ.typtype IN ('e', 'd', 'r') OR cls.relkind = 'c')
+		AND NOT EXISTS (SELECT FROM pg_depend dep WHERE dep.objid = typ.oid AND dep.deptype = 'e')
+) objects
 WHERE
-	table_schema NOT IN ('pg_catalog', 'information_schema')
+	schema NOT IN ('pg_catalog', 'information_schema')
+	AND schema NOT LIKE 'pg\\_toast%'
+	AND schema NOT LIKE 'pg\\_temp\\_%'
 ORDER BY
-	table_schema, table_name ASC;
+	schema, name, arguments;
 ",
                 );
                 Ok(Query {
@@ -58,12 +165,7 @@ ORDER BY
                         table.schema, table.name
                     ))
                 }
-                let quoted = format!(
-                    "\"{}\".\"{}\"",
-                    table.schema.replace('"', "\"\""),
-                    table.name.replace('"', "\"\"")
-                );
-                let query = format!("SELECT * FROM {} LIMIT 250;", quoted);
+                let query = format!("SELECT * FROM {} LIMIT 250;", table.quoted());
                 Ok(Query {
                     query,
                     binds: None,
@@ -127,6 +229,877 @@ ORDER BY
                     tag,
                 })
             }
+            QueryTag::TableIndexes(table) => {
+                let query = String::from(
+                    "
+SELECT
+	idx.indexname AS index_name,
+	CASE
+		WHEN ind.indisprimary THEN 'primary'
+		WHEN ind.indisunique THEN 'unique'
+		ELSE ''
+	END AS kind,
+	pg_size_pretty(pg_relation_size(cls.oid)) AS size,
+	stat.idx_scan AS scans,
+	ind.indisvalid AS valid,
+	idx.indexdef AS definition
+FROM
+	pg_indexes idx
+	JOIN pg_namespace nsp ON nsp.nspname = idx.schemaname
+	JOIN pg_class cls ON cls.relname = idx.indexname AND cls.relnamespace = nsp.oid
+	JOIN pg_index ind ON ind.indexrelid = cls.oid
+	LEFT JOIN pg_stat_all_indexes stat ON stat.indexrelid = cls.oid
+WHERE
+	idx.schemaname = $1 AND idx.tablename = $2
+ORDER BY
+	ind.indisprimary DESC, ind.indisunique DESC, idx.indexname;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![table.schema, table.name]),
+                    tag,
+                })
+            }
+            QueryTag::TableConstraints(table) => {
+                let query = String::from(
+                    "
+SELECT
+	con.conname AS constraint_name,
+	CASE con.contype
+		WHEN 'p' THEN 'primary key'
+		WHEN 'u' THEN 'unique'
+		WHEN 'f' THEN 'foreign key'
+		WHEN 'c' THEN 'check'
+		WHEN 'x' THEN 'exclusion'
+		WHEN 'n' THEN 'not null'
+		WHEN 't' THEN 'trigger'
+		ELSE con.contype::text
+	END AS constraint_type,
+	pg_get_constraintdef(con.oid) AS definition,
+	con.convalidated AS validated,
+	CASE
+		WHEN NOT con.condeferrable THEN ''
+		WHEN con.condeferred THEN 'deferred'
+		ELSE 'immediate'
+	END AS deferrable
+FROM
+	pg_constraint con
+	JOIN pg_class rel ON rel.oid = con.conrelid
+	JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+WHERE
+	nsp.nspname = $1 AND rel.relname = $2
+ORDER BY
+	array_position(ARRAY['p', 'u', 'f', 'c', 'x'], con.contype::text), con.conname;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![table.schema, table.name]),
+                    tag,
+                })
+            }
+            QueryTag::TableTriggers(table) => {
+                let query = String::from(
+                    "
+SELECT
+	trg.tgname AS trigger_name,
+	CASE trg.tgenabled
+		WHEN 'O' THEN 'enabled'
+		WHEN 'D' THEN 'disabled'
+		WHEN 'R' THEN 'replica'
+		WHEN 'A' THEN 'always'
+	END AS status,
+	trg.tgfoid::regproc::text AS function,
+	pg_get_triggerdef(trg.oid) AS definition
+FROM
+	pg_trigger trg
+	JOIN pg_class rel ON rel.oid = trg.tgrelid
+	JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+WHERE
+	NOT trg.tgisinternal
+	AND nsp.nspname = $1 AND rel.relname = $2
+ORDER BY
+	trg.tgname;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![table.schema, table.name]),
+                    tag,
+                })
+            }
+            QueryTag::TablePartitions(table) => {
+                // The partition key of a partitioned table, the parent of a partition and the
+                // partitions of a table, in that order.
+                let query = String::from(
+                    "
+SELECT
+	kind,
+	relation,
+	bound,
+	size
+FROM (
+	SELECT
+		0 AS sort,
+		'partition key' AS kind,
+		'' AS relation,
+		pg_get_partkeydef(rel.oid) AS bound,
+		'' AS size
+	FROM
+		pg_class rel
+		JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+	WHERE
+		rel.relkind = 'p' AND nsp.nspname = $1 AND rel.relname = $2
+	UNION ALL
+	SELECT
+		1,
+		'parent',
+		parent.oid::regclass::text,
+		pg_get_expr(rel.relpartbound, rel.oid),
+		pg_size_pretty(pg_total_relation_size(parent.oid))
+	FROM
+		pg_class rel
+		JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+		JOIN pg_inherits inh ON inh.inhrelid = rel.oid
+		JOIN pg_class parent ON parent.oid = inh.inhparent
+	WHERE
+		nsp.nspname = $1 AND rel.relname = $2
+	UNION ALL
+	SELECT
+		2,
+		'partition',
+		child.oid::regclass::text,
+		pg_get_expr(child.relpartbound, child.oid),
+		pg_size_pretty(pg_total_relation_size(child.oid))
+	FROM
+		pg_class rel
+		JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+		JOIN pg_inherits inh ON inh.inhparent = rel.oid
+		JOIN pg_class child ON child.oid = inh.inhrelid
+	WHERE
+		nsp.nspname = $1 AND rel.relname = $2
+) partitions
+ORDER BY
+	sort, relation;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![table.schema, table.name]),
+                    tag,
+                })
+            }
+            QueryTag::TableDdl(table) => {
+                // Reconstructs the statements `pg_dump -s -t` would give for a table, foreign
+                // table, view or sequence, as a single value.
+                let query = String::from(
+                    r"
+WITH rel AS (
+	SELECT
+		cls.oid,
+		cls.relkind,
+		cls.relpersistence,
+		cls.relispartition,
+		cls.relowner,
+		format('%I.%I', nsp.nspname, cls.relname) AS name,
+		CASE cls.relkind
+			WHEN 'v' THEN 'VIEW'
+			WHEN 'm' THEN 'MATERIALIZED VIEW'
+			WHEN 'S' THEN 'SEQUENCE'
+			WHEN 'f' THEN 'FOREIGN TABLE'
+			ELSE 'TABLE'
+		END AS kind
+	FROM
+		pg_class cls
+		JOIN pg_namespace nsp ON nsp.oid = cls.relnamespace
+	WHERE
+		nsp.nspname = $1 AND cls.relname = $2
+),
+columns AS (
+	SELECT
+		string_agg(
+			format('    %I %s', att.attname, format_type(att.atttypid, att.atttypmod))
+			|| CASE
+				WHEN att.attcollation <> typ.typcollation THEN format(' COLLATE %s', att.attcollation::regcollation)
+				ELSE ''
+			END
+			|| CASE
+				WHEN att.attidentity = 'a' THEN ' GENERATED ALWAYS AS IDENTITY'
+				WHEN att.attidentity = 'd' THEN ' GENERATED BY DEFAULT AS IDENTITY'
+				WHEN att.attgenerated = 's' THEN format(' GENERATED ALWAYS AS (%s) STORED', pg_get_expr(def.adbin, def.adrelid))
+				WHEN def.adbin IS NOT NULL THEN ' DEFAULT ' || pg_get_expr(def.adbin, def.adrelid)
+				ELSE ''
+			END
+			|| CASE WHEN att.attnotnull THEN ' NOT NULL' ELSE '' END
+			|| coalesce(
+				(SELECT format(' OPTIONS (%s)', string_agg(format('%I %L', opt.option_name, opt.option_value), ', '))
+				FROM pg_options_to_table(att.attfdwoptions) opt HAVING count(*) > 0),
+				''
+			),
+			E',\n' ORDER BY att.attnum
+		) AS definition
+	FROM
+		rel
+		-- Inherited columns come with INHERITS
+		JOIN pg_attribute att ON att.attrelid = rel.oid AND att.attnum > 0 AND NOT att.attisdropped AND att.attislocal
+		JOIN pg_type typ ON typ.oid = att.atttypid
+		LEFT JOIN pg_attrdef def ON def.adrelid = att.attrelid AND def.adnum = att.attnum
+),
+constraints AS (
+	SELECT
+		string_agg(
+			format('    CONSTRAINT %I %s', con.conname, pg_get_constraintdef(con.oid)),
+			E',\n' ORDER BY array_position(ARRAY['p', 'u', 'c', 'f', 'x'], con.contype::text), con.conname
+		) AS definition
+	FROM
+		rel
+		JOIN pg_constraint con ON con.conrelid = rel.oid
+	WHERE
+		con.contype IN ('p', 'u', 'c', 'f', 'x') AND con.conislocal
+),
+indexes AS (
+	SELECT
+		string_agg(pg_get_indexdef(ind.indexrelid) || ';', E'\n' ORDER BY ind.indexrelid::regclass::text) AS definition
+	FROM
+		rel
+		JOIN pg_index ind ON ind.indrelid = rel.oid
+	WHERE
+		NOT EXISTS (SELECT FROM pg_constraint con WHERE con.conindid = ind.indexrelid AND con.conrelid = rel.oid)
+),
+parents AS (
+	SELECT
+		format(E'\nINHERITS (%s)', string_agg(inh.inhparent::regclass::text, ', ' ORDER BY inh.inhseqno)) AS definition
+	FROM
+		rel
+		JOIN pg_inherits inh ON inh.inhrelid = rel.oid
+	WHERE
+		NOT rel.relispartition
+	HAVING
+		count(*) > 0
+),
+foreign_server AS (
+	SELECT
+		format(E'\nSERVER %I', srv.srvname)
+		|| coalesce(
+			(SELECT format(E'\nOPTIONS (%s)', string_agg(format('%I %L', opt.option_name, opt.option_value), ', '))
+			FROM pg_options_to_table(ft.ftoptions) opt HAVING count(*) > 0),
+			''
+		) AS definition
+	FROM
+		rel
+		JOIN pg_foreign_table ft ON ft.ftrelid = rel.oid
+		JOIN pg_foreign_server srv ON srv.oid = ft.ftserver
+),
+sequence_owner AS (
+	SELECT
+		format(E'\n    OWNED BY %s.%I', dep.refobjid::regclass, att.attname) AS definition
+	FROM
+		rel
+		JOIN pg_depend dep ON dep.objid = rel.oid AND dep.classid = 'pg_class'::regclass
+			AND dep.refclassid = 'pg_class'::regclass AND dep.deptype = 'a'
+		JOIN pg_attribute att ON att.attrelid = dep.refobjid AND att.attnum = dep.refobjsubid
+),
+comments AS (
+	SELECT
+		string_agg(
+			CASE
+				WHEN dsc.objsubid = 0 THEN format('COMMENT ON %s %s IS %L;', rel.kind, rel.name, dsc.description)
+				ELSE format('COMMENT ON COLUMN %s.%I IS %L;', rel.name, att.attname, dsc.description)
+			END,
+			E'\n' ORDER BY dsc.objsubid
+		) AS definition
+	FROM
+		rel
+		JOIN pg_description dsc ON dsc.objoid = rel.oid AND dsc.classoid = 'pg_class'::regclass
+		LEFT JOIN pg_attribute att ON att.attrelid = rel.oid AND att.attnum = dsc.objsubid
+)
+SELECT
+	concat_ws(
+		E'\n\n',
+		CASE
+			WHEN rel.relkind IN ('v', 'm') THEN format(E'CREATE %s %s AS\n%s', rel.kind, rel.name, pg_get_viewdef(rel.oid, true))
+			WHEN rel.relkind = 'S' THEN (
+				SELECT format(
+					E'CREATE SEQUENCE %s\n    AS %s\n    START WITH %s\n    INCREMENT BY %s\n    MINVALUE %s\n    MAXVALUE %s\n    CACHE %s%s%s;',
+					rel.name,
+					format_type(seq.seqtypid, NULL),
+					seq.seqstart,
+					seq.seqincrement,
+					seq.seqmin,
+					seq.seqmax,
+					seq.seqcache,
+					CASE WHEN seq.seqcycle THEN E'\n    CYCLE' ELSE '' END,
+					coalesce((SELECT definition FROM sequence_owner), '')
+				)
+				FROM pg_sequence seq
+				WHERE seq.seqrelid = rel.oid
+			)
+			WHEN rel.relispartition THEN format(
+				E'CREATE %s %s PARTITION OF %s\n%s%s%s;',
+				rel.kind,
+				rel.name,
+				(SELECT inh.inhparent::regclass FROM pg_inherits inh WHERE inh.inhrelid = rel.oid),
+				pg_get_expr(cls.relpartbound, rel.oid),
+				CASE WHEN rel.relkind = 'p' THEN ' PARTITION BY ' || pg_get_partkeydef(rel.oid) ELSE '' END,
+				coalesce(foreign_server.definition, '')
+			)
+			ELSE format(
+				E'CREATE %s%s %s (\n%s\n)%s%s%s;',
+				CASE WHEN rel.relpersistence = 'u' THEN 'UNLOGGED ' ELSE '' END,
+				rel.kind,
+				rel.name,
+				concat_ws(E',\n', columns.definition, constraints.definition),
+				coalesce(parents.definition, ''),
+				CASE WHEN rel.relkind = 'p' THEN ' PARTITION BY ' || pg_get_partkeydef(rel.oid) ELSE '' END,
+				coalesce(foreign_server.definition, '')
+			)
+		END,
+		indexes.definition,
+		comments.definition,
+		format('ALTER %s %s OWNER TO %I;', rel.kind, rel.name, pg_get_userbyid(rel.relowner))
+	) AS ddl
+FROM
+	rel
+	JOIN pg_class cls ON cls.oid = rel.oid
+	LEFT JOIN parents ON true
+	LEFT JOIN foreign_server ON true,
+	columns,
+	constraints,
+	indexes,
+	comments;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![table.schema, table.name]),
+                    tag,
+                })
+            }
+            QueryTag::FunctionDefinition(function) => {
+                let query = String::from(
+                    r"
+WITH pro AS (
+	SELECT
+		pro.oid,
+		pro.prokind,
+		pro.proowner,
+		format('%I.%I(%s)', nsp.nspname, pro.proname, oidvectortypes(pro.proargtypes)) AS name
+	FROM
+		pg_proc pro
+		JOIN pg_namespace nsp ON nsp.oid = pro.pronamespace
+	WHERE
+		nsp.nspname = $1 AND pro.proname = $2 AND oidvectortypes(pro.proargtypes) = $3
+)
+SELECT
+	concat_ws(
+		E'\n\n',
+		rtrim(pg_get_functiondef(pro.oid), E'\n') || ';',
+		(
+			SELECT format('COMMENT ON %s %s IS %L;', kind, pro.name, dsc.description)
+			FROM pg_description dsc
+			WHERE dsc.objoid = pro.oid AND dsc.classoid = 'pg_proc'::regclass
+		),
+		format('ALTER %s %s OWNER TO %I;', kind, pro.name, pg_get_userbyid(pro.proowner))
+	) AS ddl
+FROM
+	pro,
+	LATERAL (SELECT CASE pro.prokind WHEN 'p' THEN 'PROCEDURE' ELSE 'FUNCTION' END AS kind) kind;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![function.schema, function.name, function.arguments]),
+                    tag,
+                })
+            }
+            QueryTag::TypeDefinition(typ) => {
+                let query = String::from(
+                    r"
+WITH typ AS (
+	SELECT
+		typ.oid,
+		typ.typtype,
+		typ.typrelid,
+		typ.typbasetype,
+		typ.typtypmod,
+		typ.typdefault,
+		typ.typnotnull,
+		typ.typowner,
+		format('%I.%I', nsp.nspname, typ.typname) AS name,
+		CASE typ.typtype WHEN 'd' THEN 'DOMAIN' ELSE 'TYPE' END AS kind
+	FROM
+		pg_type typ
+		JOIN pg_namespace nsp ON nsp.oid = typ.typnamespace
+	WHERE
+		nsp.nspname = $1 AND typ.typname = $2
+)
+SELECT
+	concat_ws(
+		E'\n\n',
+		CASE typ.typtype
+			WHEN 'e' THEN format(
+				E'CREATE TYPE %s AS ENUM (\n%s\n);',
+				typ.name,
+				(
+					SELECT string_agg(format('    %L', enm.enumlabel), E',\n' ORDER BY enm.enumsortorder)
+					FROM pg_enum enm
+					WHERE enm.enumtypid = typ.oid
+				)
+			)
+			WHEN 'd' THEN format(
+				'CREATE DOMAIN %s AS %s%s%s%s;',
+				typ.name,
+				format_type(typ.typbasetype, typ.typtypmod),
+				' DEFAULT ' || typ.typdefault,
+				CASE WHEN typ.typnotnull THEN ' NOT NULL' ELSE '' END,
+				(
+					SELECT string_agg(format(E'\n    CONSTRAINT %I %s', con.conname, pg_get_constraintdef(con.oid)), '' ORDER BY con.conname)
+					FROM pg_constraint con
+					WHERE con.contypid = typ.oid AND con.contype = 'c'
+				)
+			)
+			WHEN 'r' THEN (
+				SELECT format(
+					'CREATE TYPE %s AS RANGE (SUBTYPE = %s%s);',
+					typ.name,
+					format_type(rng.rngsubtype, NULL),
+					CASE WHEN rng.rngsubdiff <> 0 THEN ', SUBTYPE_DIFF = ' || rng.rngsubdiff::regproc ELSE '' END
+				)
+				FROM pg_range rng
+				WHERE rng.rngtypid = typ.oid
+			)
+			ELSE format(
+				E'CREATE TYPE %s AS (\n%s\n);',
+				typ.name,
+				(
+					SELECT string_agg(format('    %I %s', att.attname, format_type(att.atttypid, att.atttypmod)), E',\n' ORDER BY att.attnum)
+					FROM pg_attribute att
+					WHERE att.attrelid = typ.typrelid AND att.attnum > 0 AND NOT att.attisdropped
+				)
+			)
+		END,
+		(
+			SELECT format('COMMENT ON %s %s IS %L;', typ.kind, typ.name, dsc.description)
+			FROM pg_description dsc
+			WHERE dsc.objoid = typ.oid AND dsc.classoid = 'pg_type'::regclass
+		),
+		format('ALTER %s %s OWNER TO %I;', typ.kind, typ.name, pg_get_userbyid(typ.typowner))
+	) AS ddl
+FROM
+	typ;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![typ.schema, typ.name]),
+                    tag,
+                })
+            }
+            QueryTag::RefreshMaterializedView(view) => Ok(Query {
+                query: format!("REFRESH MATERIALIZED VIEW {};", view.quoted()),
+                binds: None,
+                tag,
+            }),
+            QueryTag::ForeignKeys(ref relation_ids) => {
+                // One row for each column of the foreign keys of the given tables
+                let query = String::from(
+                    "
+SELECT
+	con.oid::int8 AS constraint_id,
+	con.conrelid::int8 AS relation_id,
+	con.conname AS constraint_name,
+	key.attnum,
+	nsp.nspname AS foreign_schema,
+	ref.relname AS foreign_table,
+	att.attname AS foreign_column,
+	format_type(att.atttypid, att.atttypmod) AS foreign_type
+FROM
+	pg_constraint con
+	CROSS JOIN LATERAL unnest(con.conkey, con.confkey)
+		WITH ORDINALITY AS key(attnum, foreign_attnum, ordinality)
+	JOIN pg_class ref ON ref.oid = con.confrelid
+	JOIN pg_namespace nsp ON nsp.oid = ref.relnamespace
+	JOIN pg_attribute att ON att.attrelid = con.confrelid AND att.attnum = key.foreign_attnum
+WHERE
+	con.contype = 'f' AND con.conrelid = ANY($1::oid[])
+ORDER BY
+	con.conname, con.oid, key.ordinality;
+",
+                );
+                let ids: Vec<String> = relation_ids.iter().map(|id| id.to_string()).collect();
+                Ok(Query {
+                    query,
+                    binds: Some(vec![format!("{{{}}}", ids.join(","))]),
+                    tag,
+                })
+            }
+            QueryTag::ReferencingForeignKeys(ref relation_ids) => {
+                // One row for each column of the foreign keys referencing the given tables, in the
+                // same shape as `ForeignKeys`. Keys cloned onto partitions are left out.
+                let query = String::from(
+                    "
+SELECT
+	con.oid::int8 AS constraint_id,
+	con.confrelid::int8 AS relation_id,
+	con.conname AS constraint_name,
+	key.foreign_attnum AS attnum,
+	nsp.nspname AS referencing_schema,
+	rel.relname AS referencing_table,
+	att.attname AS referencing_column,
+	format_type(att.atttypid, att.atttypmod) AS referencing_type
+FROM
+	pg_constraint con
+	CROSS JOIN LATERAL unnest(con.conkey, con.confkey)
+		WITH ORDINALITY AS key(attnum, foreign_attnum, ordinality)
+	JOIN pg_class rel ON rel.oid = con.conrelid
+	JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+	JOIN pg_attribute att ON att.attrelid = con.conrelid AND att.attnum = key.attnum
+WHERE
+	con.contype = 'f' AND con.conparentid = 0 AND con.confrelid = ANY($1::oid[])
+ORDER BY
+	nsp.nspname, rel.relname, con.conname, con.oid, key.ordinality;
+",
+                );
+                let ids: Vec<String> = relation_ids.iter().map(|id| id.to_string()).collect();
+                Ok(Query {
+                    query,
+                    binds: Some(vec![format!("{{{}}}", ids.join(","))]),
+                    tag,
+                })
+            }
+            QueryTag::ReferenceCounts(ref references) => {
+                let mut binds = vec![];
+                let counts: Vec<String> = references
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, reference)| {
+                        let conditions: Vec<String> = reference
+                            .columns
+                            .iter()
+                            .zip(reference.values.iter())
+                            .map(|((name, typ), value)| {
+                                binds.push(value.clone());
+                                format!("{} = ${}::{typ}", quote_ident(name), binds.len())
+                            })
+                            .collect();
+                        format!(
+                            "SELECT {idx} AS idx, count(*) AS count FROM {} WHERE {}",
+                            reference.table.quoted(),
+                            conditions.join(" AND ")
+                        )
+                    })
+                    .collect();
+                Ok(Query {
+                    query: format!("{}\nORDER BY idx;", counts.join("\nUNION ALL\n")),
+                    binds: Some(binds),
+                    tag,
+                })
+            }
+            QueryTag::FollowReference(ref reference) => {
+                // Values are sent as text, so they are cast to the column types.
+                let conditions: Vec<String> = reference
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (name, typ))| {
+                        format!("{} = ${}::{typ}", quote_ident(name), idx + 1)
+                    })
+                    .collect();
+                Ok(Query {
+                    query: format!(
+                        "SELECT * FROM {} WHERE {} LIMIT 250;",
+                        reference.table.quoted(),
+                        conditions.join(" AND ")
+                    ),
+                    binds: Some(reference.values.clone()),
+                    tag,
+                })
+            }
+            QueryTag::SchemaDiagram(ref schema) => {
+                // One row for each column of the tables of the schema, with the table referenced
+                // by a foreign key on it. Partitions are left out, their parent is shown.
+                let query = String::from(
+                    "
+SELECT
+	rel.relname AS table_name,
+	att.attname AS column_name,
+	format_type(att.atttypid, att.atttypmod) AS data_type,
+	EXISTS (
+		SELECT FROM pg_constraint pk
+		WHERE pk.conrelid = rel.oid AND pk.contype = 'p' AND att.attnum = ANY(pk.conkey)
+	) AS primary_key,
+	(
+		SELECT
+			CASE
+				WHEN ref.relnamespace = rel.relnamespace THEN ref.relname
+				ELSE ref_nsp.nspname || '.' || ref.relname
+			END
+		FROM
+			pg_constraint fk
+			JOIN pg_class ref ON ref.oid = fk.confrelid
+			JOIN pg_namespace ref_nsp ON ref_nsp.oid = ref.relnamespace
+		WHERE
+			fk.conrelid = rel.oid AND fk.contype = 'f' AND att.attnum = ANY(fk.conkey)
+		ORDER BY
+			fk.conname
+		LIMIT 1
+	) AS foreign_table
+FROM
+	pg_class rel
+	JOIN pg_namespace nsp ON nsp.oid = rel.relnamespace
+	JOIN pg_attribute att ON att.attrelid = rel.oid AND att.attnum > 0 AND NOT att.attisdropped
+WHERE
+	nsp.nspname = $1 AND rel.relkind IN ('r', 'p') AND NOT rel.relispartition
+ORDER BY
+	rel.relname, att.attnum;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![schema.clone()]),
+                    tag,
+                })
+            }
+            QueryTag::TableStats(table) => {
+                // A single row, with a column for each statistic
+                let query = String::from(
+                    r#"
+SELECT
+	CASE WHEN cls.reltuples >= 0 THEN cls.reltuples::int8 END AS "Estimated rows",
+	pg_size_pretty(pg_relation_size(cls.oid)) AS "Table size",
+	pg_size_pretty(pg_indexes_size(cls.oid)) AS "Index size",
+	CASE
+		WHEN cls.reltoastrelid <> 0 THEN pg_size_pretty(pg_total_relation_size(cls.reltoastrelid))
+	END AS "TOAST size",
+	pg_size_pretty(pg_total_relation_size(cls.oid)) AS "Total size",
+	stat.n_live_tup AS "Live tuples",
+	stat.n_dead_tup AS "Dead tuples",
+	round(100.0 * stat.n_dead_tup / nullif(stat.n_live_tup + stat.n_dead_tup, 0), 1)::text || '%'
+		AS "Dead tuple ratio",
+	date_trunc('second', stat.last_vacuum)::text AS "Last vacuum",
+	date_trunc('second', stat.last_autovacuum)::text AS "Last autovacuum",
+	date_trunc('second', stat.last_analyze)::text AS "Last analyze",
+	date_trunc('second', stat.last_autoanalyze)::text AS "Last autoanalyze"
+FROM
+	pg_class cls
+	JOIN pg_namespace nsp ON nsp.oid = cls.relnamespace
+	LEFT JOIN pg_stat_user_tables stat ON stat.relid = cls.oid
+WHERE
+	nsp.nspname = $1 AND cls.relname = $2;
+"#,
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![table.schema, table.name]),
+                    tag,
+                })
+            }
+            QueryTag::Explain { ref query, analyze } => {
+                let options = if analyze {
+                    "FORMAT JSON, ANALYZE, BUFFERS"
+                } else {
+                    "FORMAT JSON"
+                };
+                let statement = trim_statement(query);
+                Ok(Query {
+                    query: format!("EXPLAIN ({options}) {statement}"),
+                    binds: None,
+                    tag,
+                })
+            }
+            QueryTag::Overview => {
+                // Transactions are counted since the statistics were reset, the rate is computed
+                // from two samples taken at `sampled_at`.
+                let query = String::from(
+                    "
+SELECT
+	current_database() AS database,
+	current_setting('server_version') AS server_version,
+	pg_size_pretty(pg_database_size(current_database())) AS database_size,
+	(SELECT count(*) FROM pg_stat_activity WHERE backend_type = 'client backend') AS connections,
+	current_setting('max_connections')::int AS max_connections,
+	db.blks_hit::float8 / nullif(db.blks_hit + db.blks_read, 0) AS cache_hit_ratio,
+	db.xact_commit + db.xact_rollback AS transactions,
+	extract(epoch FROM clock_timestamp())::float8 AS sampled_at,
+	pg_is_in_recovery() AS in_recovery,
+	extract(epoch FROM now() - pg_last_xact_replay_timestamp())::float8 AS replay_delay,
+	(
+		SELECT string_agg(
+			concat_ws(' ', coalesce(nullif(rep.application_name, ''), rep.client_addr::text),
+				'(' || rep.state || ', ' || rep.sync_state || ', replay lag '
+					|| coalesce(rep.replay_lag::text, '0') || ')'),
+			', ')
+		FROM pg_stat_replication rep
+	) AS replicas
+FROM
+	pg_stat_database db
+WHERE
+	db.datname = current_database();
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: None,
+                    tag,
+                })
+            }
+            QueryTag::LargestTables => {
+                // Partitions are counted in their partitioned table, as in the sidebar
+                let query = String::from(
+                    "
+SELECT
+	schema,
+	name,
+	pg_size_pretty(size) AS size,
+	estimated_rows
+FROM (
+	SELECT
+		nsp.nspname AS schema,
+		cls.relname AS name,
+		CASE
+			WHEN cls.relkind = 'p' THEN (
+				SELECT sum(pg_total_relation_size(part.relid))::int8
+				FROM pg_partition_tree(cls.oid) part
+			)
+			ELSE pg_total_relation_size(cls.oid)
+		END AS size,
+		CASE
+			WHEN cls.relkind = 'p' THEN (
+				SELECT sum(leaf.reltuples)::int8
+				FROM pg_partition_tree(cls.oid) part
+				JOIN pg_class leaf ON leaf.oid = part.relid
+				WHERE part.isleaf AND leaf.reltuples >= 0
+			)
+			WHEN cls.reltuples >= 0 THEN cls.reltuples::int8
+		END AS estimated_rows
+	FROM
+		pg_class cls
+		JOIN pg_namespace nsp ON nsp.oid = cls.relnamespace
+	WHERE
+		cls.relkind IN ('r', 'p', 'm')
+		AND NOT cls.relispartition
+		AND nsp.nspname NOT IN ('pg_catalog', 'information_schema')
+		AND nsp.nspname NOT LIKE 'pg_toast%'
+) tables
+ORDER BY
+	size DESC
+LIMIT 10;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: None,
+                    tag,
+                })
+            }
+            QueryTag::Activity => {
+                // The columns shown in the monitor come first, the others are only shown in the
+                // details of a session.
+                let query = String::from(
+                    "
+SELECT
+	act.pid,
+	act.usename AS user,
+	act.datname AS database,
+	act.state,
+	extract(epoch FROM now() - CASE WHEN act.state = 'active' THEN act.query_start ELSE act.state_change END)::float8
+		AS duration,
+	concat_ws(': ', act.wait_event_type, act.wait_event) AS wait_event,
+	-- pg_blocking_pids is expensive, and only sessions waiting on a lock can be blocked
+	CASE WHEN act.wait_event_type = 'Lock' THEN array_to_string(pg_blocking_pids(act.pid), ',') END
+		AS blocked_by,
+	CASE WHEN act.wait_event_type = 'Lock' THEN (
+		SELECT concat_ws(' ', lck.mode, 'on', lck.locktype, lck.relation::regclass::text)
+		FROM pg_locks lck
+		WHERE lck.pid = act.pid AND NOT lck.granted
+		LIMIT 1
+	) END AS waiting_for,
+	act.query,
+	act.application_name,
+	act.client_addr::text AS client_address,
+	date_trunc('second', act.backend_start)::text AS backend_start,
+	date_trunc('second', act.xact_start)::text AS transaction_start,
+	date_trunc('second', act.query_start)::text AS query_start
+FROM
+	pg_stat_activity act
+WHERE
+	act.backend_type = 'client backend' AND act.pid <> pg_backend_pid()
+ORDER BY
+	act.state = 'active' DESC, duration DESC NULLS LAST;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: None,
+                    tag,
+                })
+            }
+            QueryTag::CancelBackend(pid) => Ok(Query {
+                query: String::from("SELECT pg_cancel_backend($1::int);"),
+                binds: Some(vec![pid.to_string()]),
+                tag,
+            }),
+            QueryTag::TerminateBackend(pid) => Ok(Query {
+                query: String::from("SELECT pg_terminate_backend($1::int);"),
+                binds: Some(vec![pid.to_string()]),
+                tag,
+            }),
+            QueryTag::TableKey(relation_id) => {
+                let query = String::from(
+                    "
+SELECT
+	nsp.nspname AS schema,
+	cls.relname AS table_name,
+	att.attnum,
+	att.attname AS column_name,
+	format_type(att.atttypid, att.atttypmod) AS data_type,
+	EXISTS (
+		SELECT FROM pg_index ind
+		WHERE ind.indrelid = cls.oid AND ind.indisprimary AND att.attnum = ANY(ind.indkey)
+	) AS is_primary
+FROM
+	pg_class cls
+	JOIN pg_namespace nsp ON nsp.oid = cls.relnamespace
+	JOIN pg_attribute att ON att.attrelid = cls.oid
+WHERE
+	cls.oid = $1::oid AND att.attnum > 0 AND NOT att.attisdropped
+ORDER BY
+	att.attnum;
+",
+                );
+                Ok(Query {
+                    query,
+                    binds: Some(vec![relation_id.to_string()]),
+                    tag,
+                })
+            }
+            QueryTag::UpdateCell(ref update) => {
+                // The key values follow the new value, all sent as text and cast to the types.
+                let (name, typ) = &update.column;
+                let conditions: Vec<String> = update
+                    .row
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (name, typ))| {
+                        format!("{} = ${}::{typ}", quote_ident(name), idx + 2)
+                    })
+                    .collect();
+                let mut binds = vec![update.value.clone()];
+                binds.extend(update.row.values.iter().cloned());
+                Ok(Query {
+                    query: format!(
+                        "UPDATE {} SET {} = $1::{typ} WHERE {} RETURNING {};",
+                        update.row.table.quoted(),
+                        quote_ident(name),
+                        conditions.join(" AND "),
+                        quote_ident(name)
+                    ),
+                    binds: Some(binds),
+                    tag,
+                })
+            }
             QueryTag::User => {
                 // NOTE: special case, not a system query. Explictly matching this case to force
                 // matching against all meaningful variants.
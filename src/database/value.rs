@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::database::decode;
+
+/// A value of a result cell, decoded by the Postgres type of its column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    /// `int2`, `int4`, `int8` and `oid` values
+    Int(i64),
+    /// `float4` and `float8` values, as Postgres prints them
+    Float(String),
+    Numeric(String),
+    Money(String),
+    /// A finite timestamp in microseconds since the Unix epoch, in UTC when `zoned`
+    Timestamp {
+        micros: i64,
+        zoned: bool,
+    },
+    Date(String),
+    Time(String),
+    Interval(String),
+    /// `json` and `jsonb` values
+    Json(String),
+    Bytes(Vec<u8>),
+    /// Text and every other type, as Postgres prints them
+    Text(String),
+}
+
+impl Value {
+    /// Decodes a value received in the binary format, given the OID of its type and the text
+    /// it's displayed as.
+    pub fn new(oid: u32, bytes: &[u8], text: String) -> Self {
+        match oid {
+            16 => Value::Bool(bytes.first().is_some_and(|b| *b != 0)),
+            20 | 21 | 23 | 26 => text.parse().map(Value::Int).unwrap_or(Value::Text(text)),
+            700 | 701 => Value::Float(text),
+            1700 => Value::Numeric(text),
+            790 => Value::Money(text),
+            // Infinite timestamps are kept as text
+            1114 | 1184 => match <[u8; 8]>::try_from(bytes)
+                .ok()
+                .map(i64::from_be_bytes)
+                .filter(|micros| *micros != i64::MAX && *micros != i64::MIN)
+                .and_then(|micros| micros.checked_add(decode::POSTGRES_EPOCH_MICROS))
+            {
+                Some(micros) => Value::Timestamp {
+                    micros,
+                    zoned: oid == 1184,
+                },
+                None => Value::Text(text),
+            },
+            1082 => Value::Date(text),
+            1083 | 1266 => Value::Time(text),
+            1186 => Value::Interval(text),
+            114 | 3802 => Value::Json(text),
+            17 => Value::Bytes(bytes.to_vec()),
+            _ => Value::Text(text),
+        }
+    }
+
+    /// The text Postgres displays for the value and reads back as it, or `None` for `NULL`.
+    pub fn text(&self) -> Option<String> {
+        Some(match self {
+            Value::Null => return None,
+            Value::Bool(value) => value.to_string(),
+            Value::Int(value) => value.to_string(),
+            Value::Timestamp { micros, zoned } => {
+                decode::timestamp(micros.checked_sub(decode::POSTGRES_EPOCH_MICROS)?, *zoned)?
+            }
+            Value::Bytes(bytes) => decode::display(17, bytes),
+            Value::Float(text)
+            | Value::Numeric(text)
+            | Value::Money(text)
+            | Value::Date(text)
+            | Value::Time(text)
+            | Value::Interval(text)
+            | Value::Json(text)
+            | Value::Text(text) => text.clone(),
+        })
+    }
+
+    /// Whether the value is a number, which is aligned to the right
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Value::Int(_) | Value::Float(_) | Value::Numeric(_) | Value::Money(_)
+        )
+    }
+
+    /// The instant of a timestamp, taking timestamps without time zones as UTC
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Value::Timestamp { micros, .. } => DateTime::from_timestamp_micros(*micros),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    process::Command,
+};
+
+use color_eyre::eyre::{bail, eyre};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What text is edited in the external editor for. It is handed back with the edited text so the
+/// component that opened the editor can recognize its answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditPurpose {
+    /// A cell of the results, by row and column
+    Cell { row: usize, column: usize },
+    /// The query editor buffer with the given name
+    Buffer(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditRequest {
+    pub purpose: EditPurpose,
+    pub contents: String,
+    /// Extension of the temporary file, which editors pick a syntax by
+    pub extension: String,
+}
+
+/// Writes the contents to a temporary file, opens it in `$VISUAL` or `$EDITOR` and returns the
+/// edited contents once the editor exits. The terminal must be given up to the editor before.
+pub fn edit(request: &EditRequest) -> color_eyre::Result<String> {
+    let (path, mut file) = temp_file(&request.extension)?;
+    file.write_all(request.contents.as_bytes())?;
+    drop(file);
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or(String::from("vi"));
+    // The editor may come with arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or(eyre!("The editor is empty"))?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    let contents = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status.map_err(|e| eyre!("Failed to run {program}: {e}"))?;
+    if !status.success() {
+        bail!("{program} exited with {status}");
+    }
+    let contents = contents?;
+    // Editors end files with a newline, which is dropped when the original had none
+    Ok(match contents.strip_suffix('\n') {
+        Some(stripped) if !request.contents.ends_with('\n') => stripped.to_string(),
+        _ => contents,
+    })
+}
+
+/// Creates a temporary file only the user can read, with a random name so that it can't be
+/// guessed and planted beforehand. The values edited may be secrets.
+fn temp_file(extension: &str) -> color_eyre::Result<(PathBuf, File)> {
+    let path = env::temp_dir().join(format!("lazydb-{}.{extension}", Uuid::new_v4().simple()));
+    let mut options = OpenOptions::new();
+    // Fails rather than following a file or link already at the path
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(&path)?;
+    Ok((path, file))
+}
@@ -11,6 +11,7 @@ mod components;
 mod config;
 mod database;
 mod errors;
+mod external_editor;
 mod logging;
 mod render_plan;
 mod tui;
@@ -21,7 +22,7 @@ async fn main() -> color_eyre::Result<()> {
     crate::logging::init()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    let mut app = App::new(args.tick_rate, args.frame_rate, args.file)?;
     app.run().await?;
     Ok(())
 }
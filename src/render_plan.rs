@@ -9,6 +9,7 @@ impl RenderPlan {
     pub fn compute_layouts(&self, mode: Mode, zoom: bool, root: Rect) -> Vec<(ComponentId, Rect)> {
         let visible_table = match mode {
             Mode::ExploreStructure => ComponentId::StructureTable,
+            Mode::ExplorePlan => ComponentId::PlanTree,
             _ => ComponentId::ResultsTable,
         };
 
@@ -27,6 +28,33 @@ impl RenderPlan {
                     (ComponentId::Messages, layout[2]),
                 ];
             }
+            // The diagram, the activity monitor and the overview take the whole width, above the
+            // messages.
+            Mode::ExploreDiagram | Mode::MonitorActivity | Mode::Overview => {
+                let layout =
+                    Layout::vertical([Constraint::Percentage(90), Constraint::Percentage(10)])
+                        .split(root);
+                let component = match mode {
+                    Mode::ExploreDiagram => ComponentId::Diagram,
+                    Mode::MonitorActivity => ComponentId::ActivityMonitor,
+                    _ => ComponentId::Overview,
+                };
+                return vec![(component, layout[0]), (ComponentId::Messages, layout[1])];
+            }
+            // The pinned and compared results side by side, above the messages
+            Mode::CompareResults => {
+                let layout =
+                    Layout::vertical([Constraint::Percentage(90), Constraint::Percentage(10)])
+                        .split(root);
+                let sides =
+                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(layout[0]);
+                return vec![
+                    (ComponentId::DiffBefore, sides[0]),
+                    (ComponentId::DiffAfter, sides[1]),
+                    (ComponentId::Messages, layout[1]),
+                ];
+            }
             // Match against other modes when zoomed:
             Mode::ExploreTables if zoom => {
                 let layout = Layout::horizontal(vec![Constraint::Fill(100)]).split(root);
@@ -42,7 +70,15 @@ impl RenderPlan {
             }
             Mode::ExploreStructure if zoom => {
                 let layout = Layout::horizontal(vec![Constraint::Fill(100)]).split(root);
-                return vec![(ComponentId::StructureTable, layout[0])];
+                return structure_with_stats(layout[0]);
+            }
+            Mode::ExplorePlan if zoom => {
+                let layout = Layout::horizontal(vec![Constraint::Fill(100)]).split(root);
+                return vec![(ComponentId::PlanTree, layout[0])];
+            }
+            Mode::ExploreSavedQueries if zoom => {
+                let layout = Layout::horizontal(vec![Constraint::Fill(100)]).split(root);
+                return vec![(ComponentId::SavedQueries, layout[0])];
             }
             _ => {}
         }
@@ -56,6 +92,9 @@ impl RenderPlan {
             Constraint::Percentage(10),
         ])
         .split(outer_layout[1]);
+        let sidebar_layout =
+            Layout::vertical([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .split(outer_layout[0]);
 
         let sidebar_comp = if matches!(mode, Mode::ExploreSchemas) {
             ComponentId::SchemaList
@@ -63,11 +102,26 @@ impl RenderPlan {
             ComponentId::TableList
         };
 
-        vec![
-            (sidebar_comp, outer_layout[0]),
+        let mut layouts = vec![
+            (sidebar_comp, sidebar_layout[0]),
+            (ComponentId::SavedQueries, sidebar_layout[1]),
             (ComponentId::TextEditor, inner_layout[0]),
-            (visible_table, inner_layout[1]),
             (ComponentId::Messages, inner_layout[2]),
-        ]
+        ];
+        if visible_table == ComponentId::StructureTable {
+            layouts.extend(structure_with_stats(inner_layout[1]));
+        } else {
+            layouts.push((visible_table, inner_layout[1]));
+        }
+        layouts
     }
 }
+
+/// The structure of a table, with its statistics on the right.
+fn structure_with_stats(area: Rect) -> Vec<(ComponentId, Rect)> {
+    let layout = Layout::horizontal([Constraint::Min(40), Constraint::Length(48)]).split(area);
+    vec![
+        (ComponentId::StructureTable, layout[0]),
+        (ComponentId::TableStats, layout[1]),
+    ]
+}